@@ -0,0 +1,50 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper::board::{Board, Move};
+use minesweeper::config::{GameConfig, WinCondition};
+use minesweeper::perft;
+
+// The first five bytes pick the board and config, every following pair of bytes
+// is a move: the column, then the row with the kind of move in the top two bits.
+fuzz_target!(|data: &[u8]| {
+    let [rows, cols, mines, options, seed, moves @ ..] = data else {
+        return;
+    };
+    let (rows, cols) = (*rows as usize % 16 + 1, *cols as usize % 16 + 1);
+    let config = GameConfig {
+        safe_radius: (options & 0x7) as usize,
+        win_condition: match options & 0x8 {
+            0 => WinCondition::FlagAllMines,
+            _ => WinCondition::OpenAllSafe,
+        },
+        deterministic: true,
+        ..GameConfig::default()
+    };
+    let mut board = Board::with_config(rows, cols, *mines as usize % (rows * cols), config);
+    for pair in moves.chunks_exact(2) {
+        // one extra row and column, so out of bounds moves are played too
+        let pos = (
+            pair[0] as usize % (cols + 1),
+            (pair[1] & 0x3f) as usize % (rows + 1),
+        );
+        if !board.initialized() {
+            if pos.0 < cols && pos.1 < rows {
+                board.init_mines(pos, Some(*seed as u64));
+                assert_eq!(perft::check(&board), Ok(()));
+            }
+            continue;
+        }
+        let mv = match pair[1] >> 6 {
+            0 => Move::Open(pos),
+            1 => Move::Flag(pos),
+            _ => Move::Chord(pos),
+        };
+        let legal = board.legal_moves().contains(&mv);
+        let accepted = board.play(mv).is_ok();
+        assert!(accepted || !legal, "{mv:?} is legal but was rejected");
+        if accepted {
+            assert_eq!(perft::check(&board), Ok(()), "{:?}", board.replay());
+        }
+    }
+});
@@ -0,0 +1,42 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper::board::{Board, GameState};
+use minesweeper::codec;
+use minesweeper::perft;
+use minesweeper::replay::Replay;
+
+// larger boards decode fine but make every run slow
+const MAX_CELLS: usize = 4096;
+
+// Replays arrive as pasted share codes or raw bytes.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(replay) = codec::decode_replay(data) {
+        let bytes = codec::encode_replay(&replay);
+        assert_eq!(codec::decode_replay(&bytes), Ok(replay.clone()));
+        play(&replay);
+    }
+    if let Ok(code) = std::str::from_utf8(data) {
+        if let Ok(bytes) = codec::from_share_code(code) {
+            let code = codec::to_share_code(&bytes);
+            assert_eq!(codec::from_share_code(&code), Ok(bytes.clone()));
+            if let Ok(replay) = codec::decode_replay(&bytes) {
+                play(&replay);
+            }
+        }
+    }
+});
+
+// every decoded replay has to load as a saved game or be rejected, never panic
+fn play(replay: &Replay) {
+    if replay.rows * replay.cols > MAX_CELLS {
+        return;
+    }
+    match Board::from_saved(replay) {
+        // a loaded game doesn't know which cell went off anymore
+        Ok(board) if board.initialized() && board.state != GameState::Lost => {
+            assert_eq!(perft::check(&board), Ok(()))
+        }
+        _ => {}
+    }
+}
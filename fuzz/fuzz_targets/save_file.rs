@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper::board::Board;
+
+// Saved games are read from disk and browser storage, anything may be in there.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(board) = serde_json::from_slice::<Board>(data) {
+        let saved = serde_json::to_vec(&board).unwrap();
+        assert!(serde_json::from_slice::<Board>(&saved).is_ok());
+    }
+    if let Ok(board) = Board::from_mbf(data) {
+        let bytes = board.to_mbf().unwrap();
+        assert_eq!(Board::from_mbf(&bytes).unwrap().to_mbf(), Ok(bytes));
+    }
+});
@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use minesweeper::codec;
+use minesweeper::sync::{SyncClient, SyncMessage};
+
+// Spectators receive encoded views and a stream of sync messages, one per line.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(view) = codec::decode_view(data) {
+        let bytes = codec::encode_view(&view);
+        assert_eq!(codec::decode_view(&bytes), Ok(view));
+    }
+    let mut client = SyncClient::default();
+    for line in data.split(|&b| b == b'\n') {
+        if let Ok(message) = serde_json::from_slice::<SyncMessage>(line) {
+            let _ = client.apply(&message);
+        }
+    }
+});
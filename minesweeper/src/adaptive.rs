@@ -0,0 +1,82 @@
+//! Adaptive difficulty: the mine density of the next board follows the player's
+//! recent results, rising after a streak of wins and dropping after a loss.
+
+pub const MIN_DENSITY: f64 = 0.08;
+pub const MAX_DENSITY: f64 = 0.25;
+
+// wins in a row raising the density by one step, a loss lowers it by two
+const STREAK: usize = 3;
+const STEP: f64 = 0.01;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Adaptive {
+    /// Mines per cell of the next board.
+    pub density: f64,
+    /// Wins since the density last changed.
+    pub streak: usize,
+}
+
+impl Default for Adaptive {
+    /// The density of the beginner board.
+    fn default() -> Adaptive {
+        Adaptive {
+            density: 10.0 / 81.0,
+            streak: 0,
+        }
+    }
+}
+
+impl Adaptive {
+    pub fn record(&mut self, won: bool) {
+        if won {
+            self.streak += 1;
+            if self.streak < STREAK {
+                return;
+            }
+            self.density += STEP;
+        } else {
+            self.density -= 2.0 * STEP;
+        }
+        self.streak = 0;
+        self.density = self.density.clamp(MIN_DENSITY, MAX_DENSITY);
+    }
+
+    /// Mines of the next board of the given size, at least one.
+    pub fn nr_mines(&self, rows: usize, cols: usize) -> usize {
+        let cells = rows * cols;
+        ((self.density * cells as f64).round() as usize).clamp(1, cells.saturating_sub(1).max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adaptive() {
+        let mut adaptive = Adaptive::default();
+        assert_eq!(adaptive.nr_mines(9, 9), 10);
+        adaptive.record(true);
+        adaptive.record(true);
+        assert_eq!(adaptive.nr_mines(9, 9), 10);
+        adaptive.record(true);
+        assert_eq!(adaptive.streak, 0);
+        assert_eq!(adaptive.nr_mines(16, 16), 34);
+
+        adaptive.record(true);
+        adaptive.record(false);
+        assert_eq!(adaptive.streak, 0);
+        assert_eq!(adaptive.nr_mines(16, 16), 29);
+
+        for _ in 0..20 {
+            adaptive.record(false);
+        }
+        assert_eq!(adaptive.density, MIN_DENSITY);
+        for _ in 0..100 {
+            adaptive.record(true);
+        }
+        assert_eq!(adaptive.density, MAX_DENSITY);
+        assert_eq!(adaptive.nr_mines(1, 2), 1);
+    }
+}
@@ -0,0 +1,268 @@
+//! Timing and luck checks over recorded games for leaderboard operators. A
+//! replay gets flagged for patterns a human hardly produces: chains of moves
+//! made within milliseconds of the cells they depend on being revealed, and
+//! guesses that all came out right against long odds. A flag is a reason to
+//! look at the game, not proof of cheating.
+//!
+//! `Report::summary` writes one line per finding:
+//!
+//! ```text
+//! moves 57 instant 3 guesses 12 survival 0.0042
+//! fast-chain 14 5 0.004
+//! lucky-guesses 12 0.0042
+//! ```
+//!
+//! The first line always comes: the moves after the first click, how many of
+//! them were instant, the guesses and the chance of surviving all of them.
+//! `fast-chain <move> <length> <fastest seconds>` is a run of instant moves
+//! starting at the move with that index in the replay, `lucky-guesses <guesses>
+//! <survival>` a perfect guess record less likely than the sensitivity allows.
+
+use std::time::Duration;
+
+use crate::board::{Board, Move};
+use crate::replay::{Replay, ReplayError};
+use crate::solver::{bounded_mine_probabilities, Solver};
+
+/// Solver steps per move, so enumerating variants can't stall the analysis.
+const SEARCH_BUDGET: usize = 20_000;
+
+/// How easily a replay gets flagged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sensitivity {
+    /// A move made sooner than this after the previous one revealed cells is
+    /// instant, no time to look at them.
+    pub reaction: Duration,
+    /// Instant moves in a row that get flagged.
+    pub chain: usize,
+    /// Guesses it takes before a perfect record is judged.
+    pub min_guesses: usize,
+    /// A perfect guess record with a lower chance of happening gets flagged.
+    pub luck: f64,
+}
+
+impl Default for Sensitivity {
+    fn default() -> Sensitivity {
+        Sensitivity {
+            reaction: Duration::from_millis(10),
+            chain: 3,
+            min_guesses: 5,
+            luck: 0.01,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Finding {
+    /// `length` instant moves starting at action `start` of the replay.
+    FastChain {
+        start: usize,
+        length: usize,
+        fastest: Duration,
+    },
+    /// Every guess survived, which had the chance `survival`.
+    LuckyGuesses { guesses: usize, survival: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report {
+    /// Moves after the first click.
+    pub moves: usize,
+    /// Moves made sooner than the reaction time after cells were revealed.
+    pub instant: usize,
+    /// Cells opened that the visible board didn't prove safe.
+    pub guesses: usize,
+    /// Chance of surviving all the guesses, from the mine probabilities at the time.
+    pub survival: f64,
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    pub fn flagged(&self) -> bool {
+        !self.findings.is_empty()
+    }
+
+    /// The report in the line format of the module documentation.
+    pub fn summary(&self) -> String {
+        let mut lines = vec![format!(
+            "moves {} instant {} guesses {} survival {:.4}",
+            self.moves, self.instant, self.guesses, self.survival
+        )];
+        for finding in &self.findings {
+            lines.push(match finding {
+                Finding::FastChain {
+                    start,
+                    length,
+                    fastest,
+                } => format!("fast-chain {start} {length} {:.3}", fastest.as_secs_f64()),
+                Finding::LuckyGuesses { guesses, survival } => {
+                    format!("lucky-guesses {guesses} {survival:.4}")
+                }
+            });
+        }
+        lines.join("\n")
+    }
+}
+
+/// Replays the game move by move, timing each move against the previous one and
+/// judging every opened cell by what the player could see before it.
+pub fn analyze(replay: &Replay, sensitivity: &Sensitivity) -> Result<Report, ReplayError> {
+    let mut board: Board = replay.board_at(1)?;
+    board.config.min_move_interval = None;
+    let mut report = Report {
+        moves: replay.actions.len().saturating_sub(1),
+        instant: 0,
+        guesses: 0,
+        survival: 1.0,
+        findings: Vec::new(),
+    };
+    let mut chain: Option<(usize, usize, Duration)> = None;
+    // the first click always reveals cells
+    let mut revealed = true;
+    for (i, pair) in replay.actions.windows(2).enumerate() {
+        let (previous, action) = (pair[0], pair[1]);
+        let gap = action.elapsed.saturating_sub(previous.elapsed);
+        if revealed && gap < sensitivity.reaction {
+            report.instant += 1;
+            let (start, length, fastest) = chain.unwrap_or((i + 1, 0, gap));
+            chain = Some((start, length + 1, fastest.min(gap)));
+        } else {
+            end_chain(&mut report, chain.take(), sensitivity);
+        }
+        if let Move::Open(pos) = action.mv {
+            let view = board.view();
+            let mut budget = SEARCH_BUDGET;
+            let safe = Solver::bounded(&view, &mut budget)
+                .is_some_and(|solver| solver.safe_cells().contains(&pos));
+            let mut budget = SEARCH_BUDGET;
+            let mine_chance = bounded_mine_probabilities(&view, &mut budget)
+                .and_then(|probabilities| probabilities.get(&pos).copied());
+            if let (false, Some(chance)) = (safe, mine_chance) {
+                report.guesses += 1;
+                report.survival *= 1.0 - chance;
+            }
+        }
+        let opened = board.open_fields.len();
+        board
+            .play(action.mv)
+            .map_err(|e| ReplayError::InvalidMove(i + 1, e))?;
+        revealed = board.open_fields.len() > opened;
+    }
+    end_chain(&mut report, chain, sensitivity);
+    if report.guesses >= sensitivity.min_guesses
+        && !board.lost()
+        && report.survival < sensitivity.luck
+    {
+        report.findings.push(Finding::LuckyGuesses {
+            guesses: report.guesses,
+            survival: report.survival,
+        });
+    }
+    Ok(report)
+}
+
+fn end_chain(
+    report: &mut Report,
+    chain: Option<(usize, usize, Duration)>,
+    sensitivity: &Sensitivity,
+) {
+    if let Some((start, length, fastest)) =
+        chain.filter(|&(_, length, _)| length >= sensitivity.chain)
+    {
+        report.findings.push(Finding::FastChain {
+            start,
+            length,
+            fastest,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Square;
+    use crate::replay::ReplayAction;
+
+    /// The game of following the hints on seed 1, a move every `gap`.
+    fn hinted_game(gap: Duration) -> Replay {
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        while let Some(mv) = board.hint() {
+            board.play(mv).unwrap();
+        }
+        let mut replay = board.replay().unwrap();
+        for (i, action) in replay.actions.iter_mut().enumerate() {
+            action.elapsed = gap * i as u32;
+        }
+        replay
+    }
+
+    #[test]
+    fn test_human_game_passes() {
+        let replay = hinted_game(Duration::from_millis(800));
+        let report = analyze(&replay, &Sensitivity::default()).unwrap();
+        assert_eq!(report.moves, replay.actions.len() - 1);
+        assert_eq!(report.instant, 0);
+        assert!(!report.flagged(), "{}", report.summary());
+    }
+
+    #[test]
+    fn test_instant_moves() {
+        let replay = hinted_game(Duration::from_millis(4));
+        let report = analyze(&replay, &Sensitivity::default()).unwrap();
+        assert!(report.instant >= 3);
+        assert!(matches!(
+            report.findings[0],
+            Finding::FastChain { start: 1, fastest, .. } if fastest == Duration::from_millis(4)
+        ));
+        let relaxed = Sensitivity {
+            reaction: Duration::from_millis(2),
+            ..Sensitivity::default()
+        };
+        assert!(!analyze(&replay, &relaxed).unwrap().flagged());
+    }
+
+    #[test]
+    fn test_lucky_guesses() {
+        // opening every safe cell in row-major order, knowing where the mines are
+        let mut board = Board::new(16, 30, 99);
+        board.init_mines((0, 0), Some(1));
+        let mut solved = board.clone();
+        while let Some(mv) = solved.hint() {
+            solved.play(mv).unwrap();
+        }
+        let safe: Vec<_> = solved.opened_cells();
+        let mut actions = vec![ReplayAction {
+            mv: Move::Open((0, 0)),
+            elapsed: Duration::ZERO,
+        }];
+        for (i, &pos) in safe.iter().enumerate() {
+            if board.view().get(pos) == Some(Square::NotYetOpened) {
+                board.open(pos).unwrap();
+                actions.push(ReplayAction {
+                    mv: Move::Open(pos),
+                    elapsed: Duration::from_secs(i as u64 + 1),
+                });
+            }
+        }
+        let replay = Replay {
+            actions,
+            ..board.replay().unwrap()
+        };
+        let report = analyze(&replay, &Sensitivity::default()).unwrap();
+        assert!(report.guesses >= 5, "{}", report.summary());
+        assert!(
+            matches!(report.findings.as_slice(), [Finding::LuckyGuesses { .. }]),
+            "{}",
+            report.summary()
+        );
+        assert!(report
+            .summary()
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("lucky-guesses"));
+    }
+}
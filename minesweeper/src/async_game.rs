@@ -0,0 +1,213 @@
+//! Generation and the solver off the caller's thread, so a frontend's frame or
+//! a server's event loop never waits on a heavy board. Requests go in with
+//! `AsyncGame::submit` and their responses come out of `poll` in order. Every
+//! request works on its own copy of the board; the caller applies the result if
+//! its board is still the one it sent.
+//!
+//! Requests run on one worker thread. The wasm build has no threads, there the
+//! requests run inside `submit` and `poll` hands out the queued responses.
+
+use std::collections::HashMap;
+#[cfg(target_arch = "wasm32")]
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::board::{Board, GenerationError, Hint, Position};
+
+pub enum Request {
+    /// Places the mines around `start`, without guessing if `no_guess` is set.
+    Generate {
+        board: Board,
+        start: Position,
+        seed: Option<u64>,
+        no_guess: bool,
+    },
+    /// The next hint, see `Board::request_hint`.
+    Hint(Board),
+    /// See `Board::mine_probabilities`.
+    Probabilities(Board),
+    /// See `Board::safe_alternative`.
+    SafeAlternative(Board, Position),
+}
+
+#[derive(Debug)]
+pub enum Response {
+    /// The board with its mines, a regular layout if no guess-free one was found
+    /// along with the error. Cancelled generations leave the board untouched.
+    Generated(Board, Result<(), GenerationError>),
+    /// The board counting the hint, and the hint.
+    Hint(Board, Option<Hint>),
+    Probabilities(HashMap<Position, f64>),
+    SafeAlternative(Option<Position>),
+}
+
+/// Number of a submitted request, its response carries the same one.
+pub type Ticket = u64;
+
+pub struct AsyncGame {
+    #[cfg(not(target_arch = "wasm32"))]
+    requests: std::sync::mpsc::Sender<(Ticket, Request)>,
+    #[cfg(not(target_arch = "wasm32"))]
+    responses: std::sync::mpsc::Receiver<(Ticket, Response)>,
+    #[cfg(target_arch = "wasm32")]
+    responses: VecDeque<(Ticket, Response)>,
+    // generations with a lower ticket are cancelled
+    cancelled_below: Arc<AtomicU64>,
+    next_ticket: Ticket,
+}
+
+impl Default for AsyncGame {
+    fn default() -> AsyncGame {
+        AsyncGame::new()
+    }
+}
+
+impl AsyncGame {
+    /// Starts the worker thread, it ends once the `AsyncGame` is dropped.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn new() -> AsyncGame {
+        let (requests, inbox) = std::sync::mpsc::channel::<(Ticket, Request)>();
+        let (outbox, responses) = std::sync::mpsc::channel();
+        let cancelled_below = Arc::new(AtomicU64::new(0));
+        let cancelled = cancelled_below.clone();
+        std::thread::spawn(move || {
+            for (ticket, request) in inbox {
+                let response = run(request, || ticket < cancelled.load(Ordering::Relaxed));
+                if outbox.send((ticket, response)).is_err() {
+                    break;
+                }
+            }
+        });
+        AsyncGame {
+            requests,
+            responses,
+            cancelled_below,
+            next_ticket: 0,
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn new() -> AsyncGame {
+        AsyncGame {
+            responses: VecDeque::new(),
+            cancelled_below: Arc::new(AtomicU64::new(0)),
+            next_ticket: 0,
+        }
+    }
+
+    pub fn submit(&mut self, request: Request) -> Ticket {
+        let ticket = self.next_ticket;
+        self.next_ticket += 1;
+        #[cfg(not(target_arch = "wasm32"))]
+        self.requests
+            .send((ticket, request))
+            .expect("the worker lives as long as the AsyncGame");
+        #[cfg(target_arch = "wasm32")]
+        self.responses.push_back((ticket, run(request, || false)));
+        ticket
+    }
+
+    /// The next response if one is ready, without waiting.
+    pub fn poll(&mut self) -> Option<(Ticket, Response)> {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.responses.try_recv().ok();
+        #[cfg(target_arch = "wasm32")]
+        return self.responses.pop_front();
+    }
+
+    /// Waits for the next response, `None` if no request is outstanding.
+    pub fn wait(&mut self) -> Option<(Ticket, Response)> {
+        #[cfg(not(target_arch = "wasm32"))]
+        return self.responses.recv().ok();
+        #[cfg(target_arch = "wasm32")]
+        return self.responses.pop_front();
+    }
+
+    /// Cancels every generation submitted so far that hasn't finished yet.
+    pub fn cancel_generations(&self) {
+        self.cancelled_below
+            .store(self.next_ticket, Ordering::Relaxed);
+    }
+}
+
+fn run(request: Request, cancelled: impl Fn() -> bool) -> Response {
+    match request {
+        Request::Generate {
+            mut board,
+            start,
+            seed,
+            no_guess,
+        } => {
+            let mut result = Ok(());
+            if no_guess || board.guess_free() {
+                result = board.init_mines_no_guess_with(start, seed, |_| !cancelled());
+            }
+            if !matches!(result, Err(GenerationError::Cancelled)) && !board.initialized() {
+                board.init_mines(start, seed);
+            }
+            Response::Generated(board, result)
+        }
+        Request::Hint(mut board) => {
+            let hint = board.request_hint();
+            Response::Hint(board, hint)
+        }
+        Request::Probabilities(board) => Response::Probabilities(board.mine_probabilities()),
+        Request::SafeAlternative(board, pos) => {
+            Response::SafeAlternative(board.safe_alternative(pos))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requests_in_order() {
+        let mut game = AsyncGame::new();
+        let generate = game.submit(Request::Generate {
+            board: Board::new(9, 9, 10),
+            start: (0, 0),
+            seed: Some(1),
+            no_guess: false,
+        });
+        let Some((ticket, Response::Generated(board, Ok(())))) = game.wait() else {
+            panic!("expected the generated board");
+        };
+        assert_eq!(ticket, generate);
+        assert_eq!(board.seed(), Some(1));
+
+        game.submit(Request::Hint(board.clone()));
+        game.submit(Request::Probabilities(board.clone()));
+        game.submit(Request::SafeAlternative(board.clone(), (3, 1)));
+        let Some((1, Response::Hint(hinted, Some(_)))) = game.wait() else {
+            panic!("expected the hint");
+        };
+        assert_eq!(hinted.hints_used.iter().sum::<usize>(), 1);
+        assert!(matches!(game.wait(), Some((2, Response::Probabilities(p))) if !p.is_empty()));
+        assert!(matches!(
+            game.wait(),
+            Some((3, Response::SafeAlternative(Some(_))))
+        ));
+        assert!(game.poll().is_none());
+    }
+
+    #[test]
+    fn test_cancel_generation() {
+        let mut game = AsyncGame::new();
+        // a layout that is never guess-free keeps the worker busy until cancelled
+        game.submit(Request::Generate {
+            board: Board::new(3, 3, 7),
+            start: (0, 0),
+            seed: Some(1),
+            no_guess: true,
+        });
+        game.cancel_generations();
+        let Some((0, Response::Generated(board, result))) = game.wait() else {
+            panic!("expected the generation");
+        };
+        assert!(matches!(result, Err(GenerationError::Cancelled)));
+        assert!(!board.initialized());
+    }
+}
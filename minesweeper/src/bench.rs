@@ -0,0 +1,99 @@
+//! Timings of the engine's hot paths on generated boards, to spot performance
+//! regressions from the command line.
+
+use std::time::Duration;
+
+use web_time::Instant;
+
+use crate::board::Board;
+use crate::config::GameConfig;
+use crate::solver::Solver;
+
+/// Total time spent in each phase over all boards.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Timings {
+    pub boards: usize,
+    /// Placing the mines and opening the first click in the center.
+    pub generation: Duration,
+    /// Opening every zero cell left closed after the first click.
+    pub flood_fill: Duration,
+    /// Cells opened by those flood fills.
+    pub flood_cells: usize,
+    /// A solver pass over the board after the first click.
+    pub solver: Duration,
+}
+
+impl Timings {
+    /// Time per board of a phase.
+    pub fn average(&self, total: Duration) -> Duration {
+        total / self.boards.max(1) as u32
+    }
+}
+
+/// Generates `boards` boards with the seeds `seed`, `seed + 1` and so on and times
+/// each phase on them. Moves are not rate limited.
+pub fn run(
+    rows: usize,
+    cols: usize,
+    nr_mines: usize,
+    config: &GameConfig,
+    boards: usize,
+    seed: u64,
+) -> Timings {
+    let mut timings = Timings {
+        boards,
+        ..Timings::default()
+    };
+    let mut config = config.clone();
+    config.min_move_interval = None;
+    for i in 0..boards {
+        let start = Instant::now();
+        let mut board = Board::with_config(rows, cols, nr_mines, config.clone());
+        board.init_mines((cols / 2, rows / 2), Some(seed.wrapping_add(i as u64)));
+        timings.generation += start.elapsed();
+
+        let start = Instant::now();
+        let solver = Solver::new(&board.view());
+        solver.safe_cells();
+        solver.mine_cells();
+        timings.solver += start.elapsed();
+
+        let mines = board.mines.clone();
+        let zeros: Vec<_> = board
+            .grid()
+            .positions()
+            .filter(|&pos| board.count(pos) == 0)
+            .filter(|&pos| !mines.as_ref().is_some_and(|mines| mines.contains(pos)))
+            .collect();
+        let opened = board.open_fields.len();
+        let start = Instant::now();
+        for pos in zeros {
+            if !board.ongoing() {
+                break;
+            }
+            if !board.open_fields.contains(pos) {
+                let _ = board.open(pos);
+            }
+        }
+        timings.flood_fill += start.elapsed();
+        timings.flood_cells += board.open_fields.len() - opened;
+    }
+    timings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run() {
+        let timings = run(16, 30, 99, &GameConfig::default(), 5, 1);
+        assert_eq!(timings.boards, 5);
+        assert!(timings.flood_cells > 0);
+        assert!(timings.average(timings.generation) <= timings.generation);
+        assert_eq!(
+            run(9, 9, 10, &GameConfig::default(), 0, 1),
+            Timings::default()
+        );
+    }
+}
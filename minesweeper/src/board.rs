@@ -1,30 +1,29 @@
-use std::collections::BTreeSet;
+use std::collections::VecDeque;
 use std::fmt::{Debug, Display, Write};
+use std::time::Duration;
 use std::vec;
 use std::{collections::HashMap, collections::HashSet};
 
+use clap::ValueEnum;
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
+use web_time::Instant;
 
-type Position = (usize, usize);
-const DIRS: [(isize, isize); 8] = [
-    (1, 1),
-    (1, 0),
-    (1, -1),
-    (0, -1),
-    (-1, -1),
-    (-1, 0),
-    (-1, 1),
-    (0, 1),
-];
+use crate::clock::GameClock;
+use crate::config::{GameConfig, DETERMINISTIC_SEED};
+use crate::metrics::{Clicks, Estimate, Metrics, PhaseSplits};
+use crate::objective::{Objective, Progress};
+use crate::render::Style;
+use crate::replay::{Replay, ReplayAction, ReplayError};
+use crate::solver;
+use crate::variant::{self, Placement};
+use crate::view::BoardView;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum GameState {
-    Init,
-    OnGoing,
-    Lost,
-    Won,
-}
+pub use minesweeper_solver::grid::{
+    row_major, wormhole_neighbors, Adjacency, CubeGrid, Grid, HexGrid, Mask, Position, SquareGrid,
+    Topology,
+};
+pub use minesweeper_solver::view::{GameState, Square};
 
 #[derive(Debug)]
 pub enum OpenError {
@@ -34,6 +33,7 @@ pub enum OpenError {
     AlreadyWon,
     MinesNotInit,
     OutOfBounds,
+    TooFast,
 }
 #[derive(Debug)]
 pub enum FlagError {
@@ -41,30 +41,353 @@ pub enum FlagError {
     AlreadyLost,
     AlreadyWon,
     MinesNotInit,
+    /// The cell is safe and the config has `assisted_flags`.
+    NotAMine,
+    OutOfBounds,
+    TooFast,
+}
+
+#[derive(Debug)]
+pub enum MoveError {
+    Open(OpenError),
+    Flag(FlagError),
+    Chord(ChordError),
+}
+
+#[derive(Debug)]
+pub enum ChordError {
+    AlreadyLost,
+    AlreadyWon,
+    FlagCountMismatch,
+    MinesNotInit,
+    NotOpen,
+    NothingToOpen,
     OutOfBounds,
+    TooFast,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Move {
+    Open(Position),
+    Flag(Position),
+    Chord(Position),
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum Square {
+#[derive(Debug, PartialEq, Eq)]
+pub enum UndoError {
+    /// No move was made since the first click.
+    NothingToUndo,
+    /// The board was not generated from a seed, so it has no replay to rebuild.
+    NoReplay,
+}
+
+#[derive(Debug)]
+pub enum GenerationError {
+    /// No layout satisfying the constraints was found within the attempt limit.
+    AttemptsExhausted,
+    /// The progress callback asked to stop.
+    Cancelled,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MinesError {
+    OutOfBounds(Position),
+    Duplicate(Position),
+    /// Every cell would be a mine.
+    NoSafeCell,
+}
+
+#[derive(Debug)]
+pub enum DiffError {
+    DimensionMismatch,
+}
+
+/// How a cell changed with a move, see `Board::last_events`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BoardEvent {
+    /// The cell was opened and shows the count.
+    Opened(Position, i8),
+    /// The cell was flagged, by the player or by winning the game.
+    Flagged(Position),
+    Unflagged(Position),
+    /// A mine was opened and the game is lost.
+    Exploded(Position),
+}
+
+/// What the player can know about a closed cell, see `Board::classify`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Certainty {
+    Safe,
     Mine,
-    Opened(u8),
-    Flag,
-    NotYetOpened,
+    /// Opening the cell is a guess.
+    Unknown,
+}
+
+/// Time added to the score for a hint of each `HintLevel`.
+pub const HINT_PENALTIES: [Duration; 3] = [
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(20),
+];
+
+/// How much a hint gives away, from the vaguest to the exact move.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HintLevel {
+    /// The opened number to look at.
+    Number,
+    /// The unopened cells around that number.
+    Region,
+    /// The cell to open.
+    #[default]
+    Cell,
+}
+
+impl HintLevel {
+    pub const ALL: [HintLevel; 3] = [HintLevel::Number, HintLevel::Region, HintLevel::Cell];
+
+    fn next(self) -> HintLevel {
+        match self {
+            HintLevel::Number => HintLevel::Region,
+            HintLevel::Region | HintLevel::Cell => HintLevel::Cell,
+        }
+    }
+}
+
+/// A hint as shown to the player, see `Board::request_hint`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Hint {
+    /// An opened number that proves a cell next to it safe.
+    Number(Position),
+    /// Unopened cells, at least one of which is provably safe.
+    Region(Vec<Position>),
+    Move(Move),
+}
+
+impl Hint {
+    pub fn level(&self) -> HintLevel {
+        match self {
+            Hint::Number(_) => HintLevel::Number,
+            Hint::Region(_) => HintLevel::Region,
+            Hint::Move(_) => HintLevel::Cell,
+        }
+    }
+
+    /// The cells the hint points at.
+    pub fn positions(&self) -> Vec<Position> {
+        match self {
+            Hint::Number(pos) => vec![*pos],
+            Hint::Region(cells) => cells.clone(),
+            Hint::Move(Move::Open(pos) | Move::Flag(pos) | Move::Chord(pos)) => vec![*pos],
+        }
+    }
+}
+
+/// Differences between two boards of the same dimensions.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoardDiff {
+    /// Cells whose visible square differs, with the square on each board.
+    pub cells: Vec<(Position, Square, Square)>,
+    /// Mines present on this board but not on the other.
+    pub mines_removed: Vec<Position>,
+    /// Mines present on the other board but not on this one.
+    pub mines_added: Vec<Position>,
+}
+
+impl BoardDiff {
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty() && self.mines_removed.is_empty() && self.mines_added.is_empty()
+    }
+}
+
+/// How far a no-guess generation got, see `NoGuessGeneration`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenerationProgress {
+    /// Layouts tried so far.
+    pub attempts: usize,
+    pub max_attempts: usize,
+    /// Steps the solver took enumerating placements, only in variants that need it.
+    pub search_steps: usize,
+    pub max_search_steps: usize,
+}
+
+impl GenerationProgress {
+    /// Share of the limits used up, generation gives up at 1.
+    pub fn fraction(&self) -> f32 {
+        let attempts = self.attempts as f32 / self.max_attempts as f32;
+        let steps = self.search_steps as f32 / self.max_search_steps as f32;
+        attempts.max(steps).min(1.0)
+    }
+}
+
+/// A no-guess generation that tries a batch of layouts at a time, so frontends
+/// can show its progress between batches and cancel it by dropping it. See
+/// `Board::no_guess_generation`.
+#[derive(Debug, Clone)]
+pub struct NoGuessGeneration {
+    start_position: Position,
+    rng: ChaCha8Rng,
+    candidate: Board,
+    attempts: usize,
+    budget: usize,
+}
+
+impl NoGuessGeneration {
+    const MAX_ATTEMPTS: usize = 10_000;
+    const SEARCH_BUDGET: usize = 2_000_000;
+
+    /// Tries up to `attempts` more layouts. Returns the seed of the first one
+    /// solvable without guessing, for `Board::init_mines` with the same first
+    /// click, or the error once the limits are used up. `None` while it goes on.
+    pub fn step(&mut self, attempts: usize) -> Option<Result<u64, GenerationError>> {
+        for _ in 0..attempts {
+            if self.attempts == Self::MAX_ATTEMPTS || self.budget == 0 {
+                return Some(Err(GenerationError::AttemptsExhausted));
+            }
+            self.attempts += 1;
+            let attempt_seed = self.rng.random();
+            self.candidate
+                .init_mines(self.start_position, Some(attempt_seed));
+            if self.candidate.estimate().is_some_and(|e| e.needs_guess()) {
+                continue;
+            }
+            if solver::solvable_without_guessing(&self.candidate, &mut self.budget) {
+                return Some(Ok(attempt_seed));
+            }
+        }
+        None
+    }
+
+    pub fn progress(&self) -> GenerationProgress {
+        GenerationProgress {
+            attempts: self.attempts,
+            max_attempts: Self::MAX_ATTEMPTS,
+            search_steps: Self::SEARCH_BUDGET - self.budget,
+            max_search_steps: Self::SEARCH_BUDGET,
+        }
+    }
+}
+
+/// A window of `rows` x `cols` cells whose top left cell is `origin`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Rect {
+    pub origin: Position,
+    pub rows: usize,
+    pub cols: usize,
+}
+
+impl Rect {
+    pub fn new(origin: Position, rows: usize, cols: usize) -> Rect {
+        Rect { origin, rows, cols }
+    }
+
+    /// The part of the window inside a `rows` x `cols` board.
+    pub fn clip(self, rows: usize, cols: usize) -> Rect {
+        let (x, y) = self.origin;
+        Rect {
+            origin: self.origin,
+            rows: self.rows.min(rows.saturating_sub(y)),
+            cols: self.cols.min(cols.saturating_sub(x)),
+        }
+    }
+
+    /// The cells of the window in row-major order.
+    pub fn positions(self) -> impl Iterator<Item = Position> {
+        let (x0, y0) = self.origin;
+        (y0..y0 + self.rows).flat_map(move |y| (x0..x0 + self.cols).map(move |x| (x, y)))
+    }
 }
 
-pub struct Board {
+/// A game in progress. With the `serde` feature the whole game, including the
+/// hidden mine layout, can be saved and restored; send a `BoardView` to clients instead.
+///
+/// The rules work on any `Topology`; views, hints, metrics and replays assume the
+/// classic square grid.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Board<T: Topology = SquareGrid> {
     pub rows: usize,
     pub cols: usize,
     pub nr_mines: usize,
-    mines: Option<HashSet<Position>>,
-    pub open_fields: HashSet<Position>,
-    pub flagged_fields: HashSet<Position>,
-    pub counts: HashMap<Position, u8>,
+    pub(crate) mines: Option<Mask>,
+    /// Mines that subtract one from the counts around them instead of adding one.
+    pub(crate) anti_mines: Mask,
+    pub open_fields: Mask,
+    pub flagged_fields: Mask,
+    /// Mines minus anti-mines around every cell, indexed `row * cols + col`.
+    pub counts: Vec<i8>,
+    /// In liar games, what every safe cell adds to its count when shown, 1 or -1,
+    /// indexed like `counts`. Empty otherwise.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) lies: Vec<i8>,
     pub state: GameState,
+    pub config: GameConfig,
+    pub clock: GameClock,
+    seed: Option<u64>,
+    start_position: Option<Position>,
+    history: Vec<ReplayAction>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_move: Option<Instant>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    last_changed: Vec<Position>,
+    /// Flags on safe cells rejected this game because of `assisted_flags`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub flags_rejected: usize,
+    /// Moves taken back this game with `undo`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub undos: usize,
+    /// Hints given this game per level, indexed by `HintLevel as usize`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hints_used: [usize; 3],
+    // level of the last hint since the last move
+    #[cfg_attr(feature = "serde", serde(skip))]
+    hint_level: Option<HintLevel>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    topology: T,
 }
 
 impl Board {
     pub fn new(rows: usize, cols: usize, nr_mines: usize) -> Board {
+        Board::with_config(rows, cols, nr_mines, GameConfig::default())
+    }
+
+    pub fn with_config(rows: usize, cols: usize, nr_mines: usize, config: GameConfig) -> Board {
+        Board::with_topology(SquareGrid, rows, cols, nr_mines, config)
+    }
+
+    /// A game on the given mine layout, started without a first click: every cell
+    /// can be opened or flagged right away. The board has no seed, so no replay.
+    pub fn with_mines(rows: usize, cols: usize, mines: &[Position]) -> Result<Board, MinesError> {
+        let mut layout = Mask::new(Grid::new(rows, cols));
+        for &pos in mines {
+            if pos.0 >= cols || pos.1 >= rows {
+                return Err(MinesError::OutOfBounds(pos));
+            }
+            if !layout.insert(pos) {
+                return Err(MinesError::Duplicate(pos));
+            }
+        }
+        if layout.len() >= rows * cols {
+            return Err(MinesError::NoSafeCell);
+        }
+        let mut board = Board::new(rows, cols, layout.len());
+        board.start_with_mines(layout);
+        Ok(board)
+    }
+}
+
+impl<T: Topology> Board<T> {
+    pub fn with_topology(
+        topology: T,
+        rows: usize,
+        cols: usize,
+        nr_mines: usize,
+        config: GameConfig,
+    ) -> Board<T> {
         assert!(rows * cols > nr_mines);
 
         Board {
@@ -72,19 +395,48 @@ impl Board {
             cols,
             nr_mines,
             mines: None,
-            flagged_fields: HashSet::new(),
-            open_fields: HashSet::new(),
-            counts: HashMap::new(),
+            anti_mines: Mask::new(Grid::new(rows, cols)),
+            flagged_fields: Mask::new(Grid::new(rows, cols)),
+            open_fields: Mask::new(Grid::new(rows, cols)),
+            counts: vec![0; rows * cols],
+            lies: Vec::new(),
             state: GameState::Init,
+            config,
+            clock: GameClock::default(),
+            seed: None,
+            start_position: None,
+            history: Vec::new(),
+            last_move: None,
+            last_changed: Vec::new(),
+            flags_rejected: 0,
+            undos: 0,
+            hints_used: [0; 3],
+            hint_level: None,
+            topology,
         }
     }
 
     fn reset_board(&mut self) {
         self.flagged_fields.clear();
         self.open_fields.clear();
-        self.counts.clear();
+        self.counts.fill(0);
+        self.lies.clear();
         self.state = GameState::Init;
+        self.clock.reset();
+        self.history.clear();
         self.mines = None;
+        self.start_position = None;
+        self.last_move = None;
+        self.last_changed.clear();
+        self.flags_rejected = 0;
+        self.undos = 0;
+        self.hints_used = [0; 3];
+        self.hint_level = None;
+    }
+
+    /// Seed the mine layout was generated from.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
     }
 
     pub fn lost(&self) -> bool {
@@ -100,113 +452,461 @@ impl Board {
     }
 
     pub fn init_mines(&mut self, start_position: Position, seed: Option<u64>) {
-        let mut rng = if let Some(seed) = seed {
-            // Seed the random generator
-            ChaCha8Rng::seed_from_u64(seed)
-        } else {
-            // Get fresh seed directly from OS
-            ChaCha8Rng::from_os_rng()
+        if let Some(mines) = self.preset_mines() {
+            self.install_mines(mines, start_position, None);
+            return;
+        }
+        // Draw a fresh seed from the OS if none is given, so every game can be replayed
+        let seed = self.seed_or_random(seed);
+        self.place_wormholes(seed);
+        let (mines, anti_mines) = self.generate_mines(start_position, seed);
+        self.place_treasures(start_position, seed, &mines);
+        self.anti_mines = Mask::from_positions(self.grid(), anti_mines);
+        let mines = Mask::from_positions(self.grid(), mines);
+        self.install_mines(mines, start_position, Some(seed));
+    }
+
+    /// `seed`, or `DETERMINISTIC_SEED` in deterministic games and a fresh one
+    /// from the OS otherwise.
+    fn seed_or_random(&self, seed: Option<u64>) -> u64 {
+        match (seed, self.config.deterministic) {
+            (Some(seed), _) => seed,
+            (None, true) => DETERMINISTIC_SEED,
+            (None, false) => rand::random(),
+        }
+    }
+
+    fn start_clock(&mut self) {
+        match self.config.deterministic {
+            true => self.clock.start_logical(),
+            false => self.clock.start(),
+        }
+    }
+
+    /// The cells of the board, for indexing and masks.
+    pub(crate) fn grid(&self) -> Grid {
+        Grid::new(self.rows, self.cols)
+    }
+
+    /// A layout loaded before the first click, for example from an MBF file. It is
+    /// played as is, without a seed and without a safe area.
+    fn preset_mines(&mut self) -> Option<Mask> {
+        match self.state {
+            GameState::Init => self.mines.take(),
+            _ => None,
+        }
+    }
+
+    /// Wormholes of the variant, if it has any. They come from their own stream
+    /// of the seed, so the mines of other variants stay the same.
+    fn place_wormholes(&mut self, seed: u64) {
+        if let Some(Ok(variant)) = self.config.variant.as_deref().map(variant::find) {
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            rng.set_stream(1);
+            let wormholes = variant.place_wormholes(self.rows, self.cols, &mut rng);
+            if !wormholes.is_empty() {
+                self.config.wormholes = wormholes;
+            }
+        }
+    }
+
+    /// Treasures of the variant, if it has any, from their own stream of the seed.
+    fn place_treasures(&mut self, start_position: Position, seed: u64, mines: &HashSet<Position>) {
+        if let Some(Ok(variant)) = self.config.variant.as_deref().map(variant::find) {
+            let safe: HashSet<Position> = self.safe_area(start_position).into_iter().collect();
+            let placement = Placement {
+                rows: self.rows,
+                cols: self.cols,
+                nr_mines: self.nr_mines,
+                safe: &safe,
+            };
+            let mut rng = ChaCha8Rng::seed_from_u64(seed);
+            rng.set_stream(3);
+            let treasures = variant.place_treasures(placement, mines, &mut rng);
+            if !treasures.is_empty() {
+                self.config.treasures = row_major(treasures);
+            }
+        }
+    }
+
+    /// In liar games, shows every safe cell one off its count, up or down at
+    /// random where both are possible. The lies come from their own stream of the
+    /// seed, a board without one lies at random.
+    fn tell_lies(&mut self, seed: Option<u64>) {
+        self.lies.clear();
+        if !self.config.liar {
+            return;
+        }
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed_or_random(seed));
+        rng.set_stream(2);
+        let signed = !self.anti_mines.is_empty();
+        self.lies = vec![0; self.counts.len()];
+        for (i, pos) in self.grid().positions().enumerate() {
+            if !self.contains(pos) || self.mines.as_ref().unwrap().contains(pos) {
+                continue;
+            }
+            let count = self.counts[i];
+            let around = self.iter_neighbors(pos).count() as i8;
+            self.lies[i] = if count == if signed { -around } else { 0 } {
+                1
+            } else if count == around || !rng.random::<bool>() {
+                -1
+            } else {
+                1
+            };
+        }
+    }
+
+    /// The mines and the anti-mines among them.
+    fn generate_mines(
+        &self,
+        start_position: Position,
+        seed: u64,
+    ) -> (HashSet<Position>, HashSet<Position>) {
+        let safe: HashSet<Position> = self.safe_area(start_position).into_iter().collect();
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let placement = Placement {
+            rows: self.rows,
+            cols: self.cols,
+            nr_mines: self.nr_mines,
+            safe: &safe,
         };
+        match self.config.variant.as_deref().map(variant::find) {
+            Some(Ok(variant)) => {
+                let mines = variant.place_mines(placement, &mut rng);
+                let anti_mines = variant.place_anti_mines(&mines, &mut rng);
+                (mines, anti_mines)
+            }
+            _ => (variant::uniform_mines(placement, &mut rng), HashSet::new()),
+        }
+    }
+
+    /// Cells guaranteed to be free of mines if the first click is at `pos`, following
+    /// `GameConfig::safe_radius`; a radius of 1 makes every first click open an area.
+    /// The radius shrinks when the mines would not fit around a larger area.
+    pub fn safe_area(&self, pos: Position) -> Vec<Position> {
+        let free = self.rows * self.cols - self.nr_mines;
+        let mut area = Mask::from_positions(self.grid(), [pos]);
+        let mut ring = vec![pos];
+        // one step at a time, a radius far beyond the board stops at its edges
+        for _ in 0..self.config.safe_radius {
+            let mut grown = area.clone();
+            let next: Vec<Position> = ring
+                .iter()
+                .flat_map(|&p| self.iter_neighbors(p))
+                .filter(|&n| grown.insert(n))
+                .collect();
+            if next.is_empty() || grown.len() > free {
+                break;
+            }
+            (area, ring) = (grown, next);
+        }
+        area.iter().collect()
+    }
 
-        let mut mines = HashSet::new();
-        while mines.len() < self.nr_mines {
-            let x: usize = rng.random_range(0..self.cols);
-            let y: usize = rng.random_range(0..self.rows);
-            if (x, y) != start_position {
-                mines.insert((x, y));
+    /// Restart the game on the same mine layout, re-opening the original first click.
+    /// Does nothing if the mines have not been initialized yet.
+    pub fn restart(&mut self) {
+        match (self.start_position, self.mines.take()) {
+            (Some(start_position), Some(mines)) => {
+                self.install_mines(mines, start_position, self.seed)
             }
+            (None, Some(mines)) if self.initialized() => self.start_with_mines(mines),
+            // a preset layout still waiting for the first click
+            (_, mines) => self.mines = mines,
         }
+    }
+
+    pub(crate) fn install_mines(
+        &mut self,
+        mines: Mask,
+        start_position: Position,
+        seed: Option<u64>,
+    ) {
         self.reset_board();
         self.mines = Some(mines);
+        self.seed = seed;
+        self.start_position = Some(start_position);
         self.state = GameState::OnGoing;
         self.set_counts();
+        self.tell_lies(seed);
+        self.start_clock();
         self.open(start_position).unwrap();
+        self.last_move = Some(Instant::now());
+    }
+
+    /// Starts a game on `mines` with every cell still closed.
+    fn start_with_mines(&mut self, mines: Mask) {
+        self.reset_board();
+        self.mines = Some(mines);
+        self.state = GameState::OnGoing;
+        self.set_counts();
+        self.start_clock();
+    }
+
+    /// Installs a position of a fixed layout. Without opened or flagged cells the
+    /// layout waits for the first click, otherwise the game goes on from there.
+    pub(crate) fn install_position(
+        &mut self,
+        mines: Mask,
+        open_fields: Mask,
+        flagged_fields: Mask,
+    ) {
+        if open_fields.is_empty() && flagged_fields.is_empty() {
+            self.reset_board();
+            self.mines = Some(mines);
+            return;
+        }
+        self.start_with_mines(mines);
+        self.open_fields = open_fields;
+        self.flagged_fields = flagged_fields;
+        self.start_position = self.opened_cells().first().copied();
+        if self.check_win_condition() == GameState::Won {
+            self.win();
+            self.last_changed.clear();
+            self.clock.stop();
+        }
+    }
+
+    /// Checks the minimum move interval, every accepted attempt counts as a move.
+    fn rate_limit(&mut self) -> bool {
+        // the wall clock has no say in deterministic games
+        if self.config.deterministic {
+            return true;
+        }
+        let now = Instant::now();
+        if let (Some(interval), Some(last)) = (self.config.min_move_interval, self.last_move) {
+            if now.duration_since(last) < interval {
+                return false;
+            }
+        }
+        self.last_move = Some(now);
+        true
     }
 
     pub fn open(&mut self, pos: Position) -> Result<GameState, OpenError> {
+        self.apply(Move::Open(pos), |board| board.open_cell(pos))
+    }
+
+    /// Runs a move, recording it and the cells it changed only if it succeeds.
+    fn apply<E>(
+        &mut self,
+        mv: Move,
+        f: impl FnOnce(&mut Self) -> Result<GameState, E>,
+    ) -> Result<GameState, E> {
+        let previous = std::mem::take(&mut self.last_changed);
+        let res = f(self);
+        if res.is_ok() {
+            self.last_changed = row_major(self.last_changed.drain(..));
+            self.record_move(mv);
+            self.hint_level = None;
+        } else {
+            self.last_changed = previous;
+        }
+        res
+    }
+
+    /// Cells whose visible state changed with the last successful move, including
+    /// every cell opened by a cascade.
+    pub fn last_changed(&self) -> &[Position] {
+        &self.last_changed
+    }
+
+    /// Moves accepted this game and not taken back.
+    pub fn nr_moves(&self) -> usize {
+        self.history.len()
+    }
+
+    /// What happened to each cell of `last_changed`, in the same order, so
+    /// frontends can animate a move without comparing whole boards.
+    pub fn last_events(&self) -> Vec<BoardEvent> {
+        self.last_changed
+            .iter()
+            .map(|&pos| {
+                if self.open_fields.contains(pos) {
+                    BoardEvent::Opened(pos, self.shown_count(pos))
+                } else if self.flagged_fields.contains(pos) {
+                    BoardEvent::Flagged(pos)
+                } else if self.lost() && self.mines.as_ref().is_some_and(|m| m.contains(pos)) {
+                    BoardEvent::Exploded(pos)
+                } else {
+                    BoardEvent::Unflagged(pos)
+                }
+            })
+            .collect()
+    }
+
+    fn open_cell(&mut self, pos: Position) -> Result<GameState, OpenError> {
         match self.state {
             GameState::Lost => Err(OpenError::AlreadyLost),
             GameState::Init => Err(OpenError::MinesNotInit),
             GameState::Won => Err(OpenError::AlreadyWon),
             GameState::OnGoing => {
-                if pos.0 >= self.cols || pos.1 >= self.rows {
-                    Err(OpenError::OutOfBounds)
-                } else if self.mines.as_ref().unwrap().contains(&pos) {
-                    self.state = GameState::Lost;
-                    Ok(GameState::Lost)
-                } else if self.flagged_fields.contains(&pos) {
+                if !self.contains(pos) {
+                    return Err(OpenError::OutOfBounds);
+                }
+                // a mine goes off even under a flag
+                let mine = self.mines.as_ref().unwrap().contains(pos);
+                if !mine && self.flagged_fields.contains(pos) {
                     Err(OpenError::AlreadyFlagged)
-                } else if self.open_fields.insert(pos) {
-                    // did not contain pos yet -> update
-                    // if this field has a zero count, then open neighboring fields also
-                    if !self.counts.contains_key(&pos) {
-                        let mut to_open = vec![];
-                        let mut next: BTreeSet<Position> = self
-                            .iter_neighbors(pos)
-                            .filter(|p| !self.open_fields.contains(p))
-                            .collect();
-                        let mut seen = Vec::with_capacity(next.len());
-
-                        while !next.is_empty() {
-                            let n = next.pop_first().unwrap();
-                            if seen.contains(&n) {
-                                continue;
-                            }
-                            seen.push(n);
-                            if self.mines.as_ref().unwrap().contains(&n) {
-                                // pass, don't open a mine
-                            } else if !self.open_fields.contains(&n) {
-                                if self.counts.contains_key(&n) {
-                                    // mine count > 0 -> stop here as new frontier
-                                    to_open.push(n);
-                                } else {
-                                    // zero count -> iterate over neighbors again
-                                    to_open.push(n);
-                                    for i in self.iter_neighbors(n) {
-                                        if !seen.contains(&i) && !self.open_fields.contains(&i) {
-                                            next.insert(i);
-                                        }
-                                    }
+                } else if !mine && self.open_fields.contains(pos) {
+                    Err(OpenError::AlreadyOpen)
+                } else if !self.rate_limit() {
+                    Err(OpenError::TooFast)
+                } else {
+                    Ok(self.reveal(pos))
+                }
+            }
+        }
+    }
+
+    /// Opens a cell without validation, cascading through zero counts.
+    fn reveal(&mut self, pos: Position) -> GameState {
+        if self.mines.as_ref().unwrap().contains(pos) {
+            self.last_changed.push(pos);
+            self.state = GameState::Lost;
+            return self.state;
+        }
+        let newly_opened = self.open_fields.insert(pos);
+        if newly_opened {
+            self.last_changed.push(pos);
+        }
+        // if this field has a zero count, then open neighboring fields also
+        if newly_opened && self.cascades(pos) {
+            // breadth-first over the zero region, marking cells as seen when queued
+            let mut seen = Mask::new(self.grid());
+            seen.insert(pos);
+            let mut next = VecDeque::from([pos]);
+            while let Some(p) = next.pop_front() {
+                for n in self.iter_neighbors(p) {
+                    if !seen.insert(n) {
+                        continue;
+                    }
+                    // don't open a mine or a flagged field
+                    if self.mines.as_ref().unwrap().contains(n)
+                        || self.flagged_fields.contains(n)
+                        || !self.open_fields.insert(n)
+                    {
+                        continue;
+                    }
+                    self.last_changed.push(n);
+                    // zero count -> iterate over its neighbors too
+                    if self.cascades(n) {
+                        next.push_back(n);
+                    }
+                }
+            }
+        }
+        if self.check_win_condition() == GameState::Won {
+            self.win();
+        }
+        self.state
+    }
+
+    /// Ends the game as won and flags every mine left unflagged, like classic
+    /// implementations do.
+    fn win(&mut self) {
+        self.state = GameState::Won;
+        let mines: Vec<Position> = self.mines.as_ref().unwrap().iter().collect();
+        for mine in mines {
+            if self.flagged_fields.insert(mine) {
+                self.last_changed.push(mine);
+            }
+        }
+    }
+
+    pub fn play(&mut self, mv: Move) -> Result<GameState, MoveError> {
+        match mv {
+            Move::Open(pos) => self.open(pos).map_err(MoveError::Open),
+            Move::Flag(pos) => self.flag(pos).map_err(MoveError::Flag),
+            Move::Chord(pos) => self.chord(pos).map_err(MoveError::Chord),
+        }
+    }
+
+    /// Opens all unflagged neighbors of an opened cell whose number of adjacent
+    /// flags equals its mine count.
+    pub fn chord(&mut self, pos: Position) -> Result<GameState, ChordError> {
+        self.apply(Move::Chord(pos), |board| board.chord_cell(pos))
+    }
+
+    fn chord_cell(&mut self, pos: Position) -> Result<GameState, ChordError> {
+        match self.state {
+            GameState::Lost => Err(ChordError::AlreadyLost),
+            GameState::Init => Err(ChordError::MinesNotInit),
+            GameState::Won => Err(ChordError::AlreadyWon),
+            GameState::OnGoing => {
+                if !self.contains(pos) {
+                    Err(ChordError::OutOfBounds)
+                } else if !self.open_fields.contains(pos) {
+                    Err(ChordError::NotOpen)
+                } else {
+                    match self.chord_targets(pos) {
+                        None => Err(ChordError::FlagCountMismatch),
+                        Some(targets) if targets.is_empty() => Err(ChordError::NothingToOpen),
+                        Some(_) if !self.rate_limit() => Err(ChordError::TooFast),
+                        Some(targets) => {
+                            for n in targets {
+                                if self.reveal(n) != GameState::OnGoing {
+                                    break;
                                 }
                             }
+                            Ok(self.state)
                         }
-                        for p in to_open {
-                            self.open_fields.insert(p);
-                        }
-                    }
-                    if self.check_win_condition() == GameState::Won {
-                        self.state = GameState::Won;
-                        Ok(GameState::Won)
-                    } else {
-                        Ok(GameState::OnGoing)
                     }
-                } else {
-                    // pos already contained -> don't update
-                    Err(OpenError::AlreadyOpen)
                 }
             }
         }
     }
 
+    /// Unopened, unflagged neighbors of an opened cell if its flag count is satisfied.
+    fn chord_targets(&self, pos: Position) -> Option<Vec<Position>> {
+        let count = self.shown_count(pos) as isize;
+        let flags = self
+            .iter_neighbors(pos)
+            .filter(|&n| self.flagged_fields.contains(n))
+            .count();
+        (flags as isize == count).then(|| {
+            self.iter_neighbors(pos)
+                .filter(|&n| !self.open_fields.contains(n) && !self.flagged_fields.contains(n))
+                .collect()
+        })
+    }
+
     pub fn flag(&mut self, pos: Position) -> Result<GameState, FlagError> {
+        self.apply(Move::Flag(pos), |board| board.flag_cell(pos))
+    }
+
+    fn flag_cell(&mut self, pos: Position) -> Result<GameState, FlagError> {
         match self.state {
             GameState::Lost => Err(FlagError::AlreadyLost),
             GameState::Init => Err(FlagError::MinesNotInit),
             GameState::Won => Err(FlagError::AlreadyWon),
             GameState::OnGoing => {
-                if pos.0 >= self.cols || pos.1 >= self.rows {
-                    Err(FlagError::OutOfBounds)
-                } else if self.open_fields.contains(&pos) {
+                if !self.contains(pos) {
+                    return Err(FlagError::OutOfBounds);
+                }
+                let flagged = self.flagged_fields.contains(pos);
+                if self.open_fields.contains(pos) {
                     // field is already open, can't be flagged.
                     Err(FlagError::AlreadyOpen)
-                } else if self.flagged_fields.contains(&pos) {
+                } else if !flagged
+                    && self.config.assisted_flags
+                    && !self.mines.as_ref().unwrap().contains(pos)
+                {
+                    self.flags_rejected += 1;
+                    Err(FlagError::NotAMine)
+                } else if !self.rate_limit() {
+                    Err(FlagError::TooFast)
+                } else if flagged {
                     // unflag
-                    self.flagged_fields.remove(&pos);
+                    self.flagged_fields.remove(pos);
+                    self.last_changed.push(pos);
                     Ok(GameState::OnGoing)
                 } else {
                     self.flagged_fields.insert(pos);
+                    self.last_changed.push(pos);
                     if self.check_win_condition() == GameState::Won {
-                        self.state = GameState::Won;
+                        self.win();
                         Ok(GameState::Won)
                     } else {
                         Ok(GameState::OnGoing)
@@ -216,107 +916,596 @@ impl Board {
         }
     }
 
+    fn record_move(&mut self, mv: Move) {
+        // playing on resumes a paused clock
+        self.clock.resume();
+        let elapsed = self.clock.split();
+        self.history.push(ReplayAction { mv, elapsed });
+        if matches!(self.state, GameState::Lost | GameState::Won) {
+            self.clock.stop();
+        }
+    }
+
     fn check_win_condition(&self) -> GameState {
-        match self.state {
-            GameState::OnGoing => {
-                if self.flagged_fields.len() == self.nr_mines
-                    && self.open_fields.len() + self.flagged_fields.len() == self.cols * self.rows
-                {
-                    if self.flagged_fields == *self.mines.as_ref().unwrap() {
-                        GameState::Won
-                    } else {
-                        GameState::OnGoing
-                    }
-                } else {
-                    GameState::OnGoing
-                }
+        match (self.state, self.progress()) {
+            (GameState::OnGoing, Some(progress)) if self.objective().reached(&progress) => {
+                GameState::Won
             }
-            s => s,
+            (state, _) => state,
+        }
+    }
+
+    /// What wins the game, the objective of the variant or else the win condition.
+    pub fn objective(&self) -> &dyn Objective {
+        match self.config.variant.as_deref().map(variant::find) {
+            Some(Ok(variant)) => variant.objective().unwrap_or(&self.config.win_condition),
+            _ => &self.config.win_condition,
         }
     }
 
+    /// What the objective is checked against, `None` before the mines are placed.
+    pub fn progress(&self) -> Option<Progress<'_>> {
+        Some(Progress {
+            rows: self.rows,
+            cols: self.cols,
+            nr_mines: self.nr_mines,
+            mines: self.mines.as_ref()?,
+            opened: &self.open_fields,
+            flagged: &self.flagged_fields,
+            treasures: &self.config.treasures,
+        })
+    }
+
     fn set_counts(&mut self) {
-        self.counts.clear();
+        self.counts = vec![0; self.rows * self.cols];
+        let grid = self.grid();
         // iterate over mines, find their neighbors and count
-        for &m in self.mines.as_ref().unwrap().iter() {
-            let neighs = self.iter_neighbors(m);
-            for n in neighs {
-                self.counts.entry(n).and_modify(|c| *c += 1).or_insert(1);
+        for m in self.mines.as_ref().unwrap().iter() {
+            let value = if self.anti_mines.contains(m) { -1 } else { 1 };
+            for n in self.iter_neighbors(m) {
+                self.counts[grid.index(n).unwrap()] += value;
             }
         }
     }
 
-    pub fn iter_neighbors(&self, (x, y): Position) -> impl Iterator<Item = Position> {
-        let (r, c) = (self.rows as isize, self.cols as isize);
-        let x = x as isize;
-        let y = y as isize;
-        DIRS.iter()
-            .map(move |(dx, dy)| (x + dx, y + dy))
-            .filter(move |(nx, ny)| {
-                *nx >= 0 && *nx < c && *ny >= 0 && *ny < r && (*nx, *ny) != (x, y)
-            })
-            .map(|(nx, ny)| (nx as usize, ny as usize))
+    /// Mines minus anti-mines around `pos`.
+    pub fn count(&self, pos: Position) -> i8 {
+        self.grid()
+            .index(pos)
+            .and_then(|i| self.counts.get(i))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// The number on `pos` once opened, its count unless the game is a liar game.
+    pub fn shown_count(&self, pos: Position) -> i8 {
+        let lie = self.grid().index(pos).and_then(|i| self.lies.get(i));
+        self.count(pos) + lie.copied().unwrap_or(0)
+    }
+
+    /// Whether opening `pos` opens its neighbors as well: a count of 0 next to
+    /// anti-mines doesn't cascade.
+    pub(crate) fn cascades(&self, pos: Position) -> bool {
+        self.count(pos) == 0
+            && (self.anti_mines.is_empty()
+                || !self
+                    .iter_neighbors(pos)
+                    .any(|n| self.mines.as_ref().is_some_and(|mines| mines.contains(n))))
+    }
+
+    /// Whether `pos` holds an anti-mine, only available once the game is over.
+    pub fn is_anti_mine(&self, pos: Position) -> bool {
+        matches!(self.state, GameState::Lost | GameState::Won) && self.anti_mines.contains(pos)
+    }
+
+    pub fn iter_neighbors(&self, pos: Position) -> impl Iterator<Item = Position> {
+        let (rows, cols, topology) = (self.rows, self.cols, self.topology);
+        let (wrap_edges, adjacency) = (self.config.wrap_edges, self.config.adjacency);
+        let plain = move |p| topology.adjacent(rows, cols, p, wrap_edges, adjacency);
+        plain(pos).chain(wormhole_neighbors(&self.config.wormholes, pos, plain))
+    }
+
+    pub fn topology(&self) -> T {
+        self.topology
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        self.topology.contains(self.rows, self.cols, pos)
     }
 
     fn _neighboring_mines(&self, pos: Position) -> u8 {
         self.iter_neighbors(pos)
-            .filter(|pos| self.mines.as_ref().unwrap().contains(pos))
+            .filter(|&pos| self.mines.as_ref().unwrap().contains(pos))
             .count() as u8
     }
 
+    /// What the player sees at `pos`, the cell of `get_board_state` without
+    /// building the whole board.
+    pub fn square(&self, pos: Position) -> Square {
+        if self.state == GameState::Init {
+            Square::NotYetOpened
+        } else if self.flagged_fields.contains(pos) {
+            Square::Flag
+        } else if self.state == GameState::Lost
+            && self.mines.as_ref().is_some_and(|mines| mines.contains(pos))
+        {
+            Square::Mine
+        } else if self.open_fields.contains(pos) {
+            Square::Opened(self.shown_count(pos))
+        } else {
+            Square::NotYetOpened
+        }
+    }
+
+    /// The squares of `rect` in row-major order, cut off at the edges of the
+    /// board. Frontends of large boards draw or send only the cells in view.
+    pub fn iter_region(&self, rect: Rect) -> impl Iterator<Item = (Position, Square)> + '_ {
+        rect.clip(self.rows, self.cols)
+            .positions()
+            .map(|pos| (pos, self.square(pos)))
+    }
+
     pub fn get_board_state(&self) -> Vec<Vec<Square>> {
         let mut map = vec![vec![Square::NotYetOpened; self.cols]; self.rows];
         if self.state == GameState::Init {
             return map;
         }
         for (x, y) in self.open_fields.iter() {
-            map[*y][*x] = Square::Opened(self.counts.get(&(*x, *y)).unwrap_or(&0u8).to_owned());
+            map[y][x] = Square::Opened(self.shown_count((x, y)));
         }
         if self.state == GameState::Lost {
             for (x, y) in self.mines.as_ref().unwrap().iter() {
-                map[*y][*x] = Square::Mine;
+                map[y][x] = Square::Mine;
             }
         }
         for (x, y) in self.flagged_fields.iter() {
-            map[*y][*x] = Square::Flag;
+            map[y][x] = Square::Flag;
         }
         map
     }
 
-    pub fn get_frontier(&self) -> HashSet<Position> {
-        let mut frontier = HashSet::new();
-        for &open in self.open_fields.iter() {
-            let neighbors = self.iter_neighbors(open);
-            for n in neighbors {
-                if !self.open_fields.contains(&n) {
-                    frontier.insert(open);
-                    break;
+    /// All moves that would currently be accepted, in row-major order.
+    /// Before initialization every cell can be opened as the first click,
+    /// flagging a flagged cell removes the flag. With `assisted_flags` only
+    /// mines can be flagged. `min_move_interval` is not taken into account, the
+    /// moves are those of the position whenever they are made.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        match self.state {
+            GameState::Init => {
+                for y in 0..self.rows {
+                    for x in 0..self.cols {
+                        moves.push(Move::Open((x, y)));
+                    }
                 }
             }
-        }
-        frontier
-    }
-}
-
-impl Debug for Board {
+            GameState::OnGoing => {
+                for y in 0..self.rows {
+                    for x in 0..self.cols {
+                        let pos = (x, y);
+                        if self.open_fields.contains(pos) {
+                            if self.chord_targets(pos).is_some_and(|t| !t.is_empty()) {
+                                moves.push(Move::Chord(pos));
+                            }
+                            continue;
+                        }
+                        let flagged = self.flagged_fields.contains(pos);
+                        if !flagged {
+                            moves.push(Move::Open(pos));
+                        }
+                        if flagged
+                            || !self.config.assisted_flags
+                            || self.mines.as_ref().unwrap().contains(pos)
+                        {
+                            moves.push(Move::Flag(pos));
+                        }
+                    }
+                }
+            }
+            GameState::Lost | GameState::Won => {}
+        }
+        moves
+    }
+
+    pub fn diff(&self, other: &Board<T>) -> Result<BoardDiff, DiffError> {
+        if self.rows != other.rows || self.cols != other.cols {
+            return Err(DiffError::DimensionMismatch);
+        }
+        let empty = Mask::new(self.grid());
+        let (own_mines, other_mines) = (
+            self.mines.as_ref().unwrap_or(&empty),
+            other.mines.as_ref().unwrap_or(&empty),
+        );
+        let (own_state, other_state) = (self.get_board_state(), other.get_board_state());
+        let mut diff = BoardDiff::default();
+        for y in 0..self.rows {
+            for x in 0..self.cols {
+                let pos = (x, y);
+                if own_state[y][x] != other_state[y][x] {
+                    diff.cells.push((pos, own_state[y][x], other_state[y][x]));
+                }
+                match (own_mines.contains(pos), other_mines.contains(pos)) {
+                    (true, false) => diff.mines_removed.push(pos),
+                    (false, true) => diff.mines_added.push(pos),
+                    _ => {}
+                }
+            }
+        }
+        Ok(diff)
+    }
+
+    /// The mine layout in row-major order, only available once the game is over.
+    pub fn reveal_mines(&self) -> Option<Vec<Position>> {
+        match self.state {
+            GameState::Lost | GameState::Won => Some(self.mines.as_ref()?.iter().collect()),
+            GameState::Init | GameState::OnGoing => None,
+        }
+    }
+
+    pub fn opened_cells(&self) -> Vec<Position> {
+        self.open_fields.iter().collect()
+    }
+
+    pub fn flagged_cells(&self) -> Vec<Position> {
+        self.flagged_fields.iter().collect()
+    }
+
+    /// Opened cells bordering at least one unopened cell.
+    pub fn get_frontier(&self) -> Vec<Position> {
+        self.open_fields
+            .iter()
+            .filter(|&open| {
+                self.iter_neighbors(open)
+                    .any(|n| !self.open_fields.contains(n))
+            })
+            .collect()
+    }
+
+    /// Playing time of the game so far, without time spent paused.
+    pub fn elapsed(&self) -> Duration {
+        self.clock.elapsed()
+    }
+
+    /// Pauses the clock of an ongoing game, the next move resumes it.
+    pub fn pause(&mut self) {
+        self.clock.pause();
+    }
+
+    pub fn resume(&mut self) {
+        self.clock.resume();
+    }
+}
+
+// The solver, views, metrics and replays only know the square grid.
+impl Board {
+    /// Whether the variant asks for games that can be solved without guessing,
+    /// frontends should then start them with `init_mines_no_guess`.
+    pub fn guess_free(&self) -> bool {
+        matches!(
+            self.config.variant.as_deref().map(variant::find),
+            Some(Ok(variant)) if variant.guess_free()
+        )
+    }
+
+    /// Like `init_mines`, but keeps generating layouts until one can be solved from
+    /// the first click without guessing. The stored seed is the one of the accepted
+    /// layout, so replays go through `init_mines` unchanged.
+    ///
+    /// Variants whose numbers the solver can only read by enumerating placements,
+    /// like liar games, also give up once the enumeration has taken too many steps
+    /// over all attempts. Layouts that `Estimate::needs_guess` rules out don't get
+    /// to the solver at all.
+    pub fn init_mines_no_guess(
+        &mut self,
+        start_position: Position,
+        seed: Option<u64>,
+    ) -> Result<(), GenerationError> {
+        self.init_mines_no_guess_with(start_position, seed, |_| true)
+    }
+
+    /// Like `init_mines_no_guess`, calling `on_progress` between batches of
+    /// attempts. Generation stops with `GenerationError::Cancelled` once it
+    /// returns false.
+    pub fn init_mines_no_guess_with(
+        &mut self,
+        start_position: Position,
+        seed: Option<u64>,
+        mut on_progress: impl FnMut(GenerationProgress) -> bool,
+    ) -> Result<(), GenerationError> {
+        const BATCH: usize = 16;
+        let Some(mut generation) = self.no_guess_generation(start_position, seed) else {
+            let mines = self
+                .preset_mines()
+                .expect("only preset layouts skip generation");
+            self.install_mines(mines, start_position, None);
+            return Ok(());
+        };
+        loop {
+            if let Some(result) = generation.step(BATCH) {
+                self.init_mines(start_position, Some(result?));
+                return Ok(());
+            }
+            if !on_progress(generation.progress()) {
+                return Err(GenerationError::Cancelled);
+            }
+        }
+    }
+
+    /// The no-guess generation of `init_mines_no_guess` to run batch by batch.
+    /// `None` if the board waits with preset mines, `init_mines` plays those.
+    pub fn no_guess_generation(
+        &self,
+        start_position: Position,
+        seed: Option<u64>,
+    ) -> Option<NoGuessGeneration> {
+        if self.state == GameState::Init && self.mines.is_some() {
+            return None;
+        }
+        Some(NoGuessGeneration {
+            start_position,
+            rng: ChaCha8Rng::seed_from_u64(self.seed_or_random(seed)),
+            candidate: Board::with_config(self.rows, self.cols, self.nr_mines, self.config.clone()),
+            attempts: 0,
+            budget: NoGuessGeneration::SEARCH_BUDGET,
+        })
+    }
+
+    /// Takes back the last move, also the one that lost the game, by replaying
+    /// the others. The clock keeps running and hints stay counted.
+    pub fn undo(&mut self) -> Result<(), UndoError> {
+        let replay = self.replay().ok_or(UndoError::NoReplay)?;
+        let steps = replay.actions.len().saturating_sub(1);
+        if steps == 0 {
+            return Err(UndoError::NothingToUndo);
+        }
+        let mut board = replay
+            .board_at(steps)
+            .expect("accepted moves replay on the same mines");
+        board.history = replay.actions[..steps].to_vec();
+        board.clock = self.clock.clone();
+        board.clock.take_back();
+        board.flags_rejected = self.flags_rejected;
+        board.hints_used = self.hints_used;
+        board.undos = self.undos + 1;
+        board.last_move = self.last_move;
+        board.last_changed.clear();
+        *self = board;
+        Ok(())
+    }
+
+    /// Rebuilds a saved game to continue it, the clock goes on from the time of
+    /// the last move.
+    pub fn from_saved(replay: &Replay) -> Result<Board, ReplayError> {
+        let mut board = replay.play()?;
+        board.history = replay.actions.clone();
+        let splits = replay.actions.iter().map(|action| action.elapsed).collect();
+        board.clock = GameClock::resumed(splits, board.ongoing(), replay.config.deterministic);
+        board.last_changed.clear();
+        Ok(board)
+    }
+
+    /// Every accepted move so far, `None` before the first click.
+    pub fn replay(&self) -> Option<Replay> {
+        Some(Replay {
+            rows: self.rows,
+            cols: self.cols,
+            nr_mines: self.nr_mines,
+            seed: self.seed?,
+            config: self.config.clone(),
+            actions: self.history.clone(),
+        })
+    }
+
+    pub fn view(&self) -> BoardView {
+        BoardView::from(self)
+    }
+
+    /// 3BV per second of a won game, the usual speedrun score. Every hint adds
+    /// the `HINT_PENALTIES` of its level to the time.
+    pub fn score(&self) -> Option<f64> {
+        if self.state != GameState::Won {
+            return None;
+        }
+        let penalty: Duration = HintLevel::ALL
+            .iter()
+            .map(|&level| HINT_PENALTIES[level as usize] * self.hints_used[level as usize] as u32)
+            .sum();
+        let time = (self.clock.final_time()? + penalty).as_secs_f64();
+        let bbbv = self.metrics()?.bbbv as f64;
+        Some(if time > 0.0 {
+            bbbv / time
+        } else {
+            f64::INFINITY
+        })
+    }
+
+    /// Difficulty metrics of the mine layout, `None` before the first click.
+    pub fn metrics(&self) -> Option<Metrics> {
+        self.mines.as_ref().map(|mines| {
+            Metrics::with_neighbors(self.grid(), &mines.iter().collect(), |pos| {
+                self.iter_neighbors(pos).collect()
+            })
+        })
+    }
+
+    /// A quick difficulty estimate of the layout, `None` before the first click.
+    pub fn estimate(&self) -> Option<Estimate> {
+        let mines = self.mines.as_ref()?;
+        let grid = self.grid();
+        let safe: Vec<Position> = grid.positions().filter(|&p| !mines.contains(p)).collect();
+        let zeros = safe.iter().filter(|&&p| self.cascades(p)).count();
+        // numbers without a zero neighbor, which no opening reveals
+        let isolated = safe
+            .iter()
+            .filter(|&&p| !self.cascades(p))
+            .filter(|&&p| {
+                !self
+                    .iter_neighbors(p)
+                    .any(|n| !mines.contains(n) && self.cascades(n))
+            })
+            .count();
+        Some(Estimate {
+            density: mines.len() as f64 / (self.rows * self.cols) as f64,
+            opened: self.open_fields.len(),
+            zero_share: zeros as f64 / safe.len().max(1) as f64,
+            bbbv_bounds: (isolated + usize::from(zeros > 0), isolated + zeros),
+        })
+    }
+
+    /// Time spent in each phase of the game so far, from the split of every move
+    /// and the 3BV it cleared. `None` before the first click or without a replay.
+    pub fn phase_splits(&self) -> Option<PhaseSplits> {
+        let replay = self.replay()?;
+        let mines = self.mines.as_ref()?.iter().collect();
+        let clicks = Clicks::with_neighbors(self.grid(), &mines, |pos| {
+            self.iter_neighbors(pos).collect()
+        });
+        let mut board = replay.board_at(1).ok()?;
+        board.config.min_move_interval = None;
+        let mut moves = Vec::new();
+        for (i, action) in replay.actions.iter().enumerate() {
+            if i > 0 {
+                board.play(action.mv).ok()?;
+            }
+            let (openings, cleared) = clicks.progress(|pos| board.open_fields.contains(pos));
+            moves.push((action.elapsed, openings, cleared));
+        }
+        Some(PhaseSplits::new(
+            &moves,
+            clicks.bbbv(),
+            clicks.openings() > 0,
+        ))
+    }
+
+    /// Suggests the next move: opening a provably safe cell if there is one (or
+    /// removing a flag from one), otherwise opening the cell least likely to be a mine.
+    /// `None` unless the game is ongoing.
+    pub fn hint(&self) -> Option<Move> {
+        self.suggestion().map(|(mv, _)| mv)
+    }
+
+    /// The next hint for the player, counted in `hints_used`. The first hint after
+    /// a move has the `first_hint` level of the config and every further one goes
+    /// a level deeper, up to the move itself. Guesses are always given as the move.
+    pub fn request_hint(&mut self) -> Option<Hint> {
+        let (mv, safe) = self.suggestion()?;
+        let (Move::Open(pos) | Move::Flag(pos) | Move::Chord(pos)) = mv;
+        // the opened neighbor with the fewest unopened cells around it
+        let view = self.view();
+        let number = if safe {
+            self.iter_neighbors(pos)
+                .filter_map(|n| Some((n, view.constraint(n)?)))
+                .min_by_key(|&(n, constraint)| (constraint.unopened, n.1, n.0))
+                .map(|(n, _)| n)
+        } else {
+            None
+        };
+        let mut level = self
+            .hint_level
+            .map_or(self.config.first_hint, HintLevel::next);
+        let hint = loop {
+            match (level, number) {
+                (HintLevel::Number, Some(n)) => break Hint::Number(n),
+                (HintLevel::Region, Some(n)) => {
+                    break Hint::Region(row_major(
+                        self.iter_neighbors(n)
+                            .filter(|&p| !self.open_fields.contains(p)),
+                    ))
+                }
+                (HintLevel::Cell, _) => break Hint::Move(mv),
+                _ => level = level.next(),
+            }
+        };
+        self.hints_used[level as usize] += 1;
+        self.hint_level = Some(level);
+        Some(hint)
+    }
+
+    /// What the visible board proves about `pos`. `None` unless the game is
+    /// ongoing and the cell is closed.
+    pub fn classify(&self, pos: Position) -> Option<Certainty> {
+        let solver = self.closed_cell_solver(pos)?;
+        Some(if solver.safe_cells().contains(&pos) {
+            Certainty::Safe
+        } else if solver.mine_cells().contains(&pos) {
+            Certainty::Mine
+        } else {
+            Certainty::Unknown
+        })
+    }
+
+    /// A provably safe cell to open instead, when opening `pos` would be a guess
+    /// or certain death. For frontends warning players against needless guesses.
+    pub fn safe_alternative(&self, pos: Position) -> Option<Position> {
+        let safe = self.closed_cell_solver(pos)?.safe_cells();
+        if safe.contains(&pos) {
+            return None;
+        }
+        safe.iter()
+            .copied()
+            .find(|&p| !self.flagged_fields.contains(p))
+            .or(safe.first().copied())
+    }
+
+    fn closed_cell_solver(&self, pos: Position) -> Option<solver::Solver> {
+        if !self.ongoing() || !self.contains(pos) || self.open_fields.contains(pos) {
+            return None;
+        }
+        Some(solver::Solver::new(&self.view()))
+    }
+
+    /// The hinted move and whether it is provably safe.
+    fn suggestion(&self) -> Option<(Move, bool)> {
+        if self.state != GameState::OnGoing {
+            return None;
+        }
+        let safe = solver::Solver::new(&self.view()).safe_cells();
+        if let Some(&pos) = safe.iter().find(|&&p| !self.flagged_fields.contains(p)) {
+            return Some((Move::Open(pos), true));
+        }
+        if let Some(&pos) = safe.first() {
+            return Some((Move::Flag(pos), true));
+        }
+        let probabilities = self.mine_probabilities();
+        row_major(
+            probabilities
+                .keys()
+                .copied()
+                .filter(|&p| !self.flagged_fields.contains(p)),
+        )
+        .into_iter()
+        .min_by(|a, b| probabilities[a].total_cmp(&probabilities[b]))
+        .map(|pos| (Move::Open(pos), false))
+    }
+
+    /// Chance of each unopened cell holding a mine given what the player can see.
+    /// Empty unless the game is ongoing.
+    pub fn mine_probabilities(&self) -> HashMap<Position, f64> {
+        if self.state != GameState::OnGoing {
+            return HashMap::new();
+        }
+        solver::mine_probabilities(&self.view())
+    }
+}
+
+impl<T: Topology> Debug for Board<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for y in 0..self.rows {
             for x in 0..self.cols {
                 let pos = (x, y);
 
-                if !self.open_fields.contains(&pos) {
-                    if self.flagged_fields.contains(&pos) {
+                if !self.open_fields.contains(pos) {
+                    if self.flagged_fields.contains(pos) {
                         f.write_str("🚩 ")?;
-                    } else if self.mines.as_ref().unwrap().contains(&pos) {
+                    } else if self.mines.as_ref().unwrap().contains(pos) {
                         f.write_str("💣 ")?;
                     } else {
                         f.write_str("🟪 ")?;
                     }
-                } else if self.mines.as_ref().unwrap().contains(&pos) {
+                } else if self.mines.as_ref().unwrap().contains(pos) {
                     f.write_str("💣 ")?;
                 } else {
-                    let mine_count = self.counts.get(&pos).unwrap_or(&0).to_owned();
-                    write!(f, " {} ", mine_count)?;
+                    write!(f, " {} ", self.count(pos))?;
                     // f.write_str("⬜ ")?;
                 }
             }
@@ -326,65 +1515,17 @@ impl Debug for Board {
     }
 }
 
-impl Display for Board {
+/// Emoji cells without colors, see `render::Style` for the other styles.
+impl<T: Topology> Display for Board<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.state {
-            GameState::Init => {
-                for _ in 0..self.rows {
-                    for _ in 0..self.cols {
-                        f.write_str("🟪 ")?;
-                    }
-                }
-            }
-            GameState::OnGoing => {
-                for y in 0..self.rows {
-                    for x in 0..self.cols {
-                        let pos = (x, y);
-                        if !self.open_fields.contains(&pos) {
-                            if self.flagged_fields.contains(&pos) {
-                                f.write_str("🚩 ")?;
-                            } else {
-                                f.write_str("🟪 ")?;
-                            }
-                        } else {
-                            let mine_count = self.counts.get(&pos).unwrap_or(&0).to_owned();
-                            write!(f, " {} ", mine_count)?;
-                        }
-                    }
-                    f.write_char('\n')?;
-                }
-            }
-            GameState::Lost | GameState::Won => {
-                for y in 0..self.rows {
-                    for x in 0..self.cols {
-                        let pos = (x, y);
-
-                        if !self.open_fields.contains(&pos) {
-                            if self.flagged_fields.contains(&pos) {
-                                f.write_str("🚩 ")?;
-                            } else if self.mines.as_ref().unwrap().contains(&pos) {
-                                f.write_str("💣 ")?;
-                            } else {
-                                f.write_str("🟪 ")?;
-                            }
-                        } else if self.mines.as_ref().unwrap().contains(&pos) {
-                            f.write_str("💣 ")?;
-                        } else {
-                            let mine_count = self.counts.get(&pos).unwrap_or(&0).to_owned();
-                            write!(f, " {} ", mine_count)?;
-                        }
-                    }
-                    f.write_char('\n')?;
-                }
-            }
-        }
-        Ok(())
+        f.write_str(&Style::default().render(self))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::WinCondition;
 
     fn setup_board_9_9_10(start_position: Position, seed: u64) -> Board {
         let mut board = Board::new(9, 9, 10);
@@ -396,7 +1537,7 @@ mod tests {
     fn test_mines() {
         let board = setup_board_9_9_10((0, 0), 1);
         println!("{:?}", board);
-        let mut v = Vec::from_iter(board.mines.as_ref().unwrap().clone());
+        let mut v: Vec<Position> = board.mines.as_ref().unwrap().iter().collect();
         v.sort();
         let expected: Vec<(usize, usize)> = vec![
             (0, 7),
@@ -436,6 +1577,15 @@ mod tests {
         println!("{:?}", board);
     }
     #[test]
+    fn test_open_large_empty_area() {
+        let mut board = Board::with_mines(300, 300, &[(299, 299)]).unwrap();
+        board.flag((0, 299)).unwrap();
+        assert_eq!(board.open((150, 150)).unwrap(), GameState::OnGoing);
+        assert_eq!(board.opened_cells().len(), 300 * 300 - 2);
+        assert_eq!(board.last_changed().len(), 300 * 300 - 2);
+        assert!(!board.open_fields.contains((0, 299)));
+    }
+    #[test]
     fn test_open_already_open_field() {
         let mut board = setup_board_9_9_10((0, 0), 1);
         println!("{:?}", board);
@@ -447,6 +1597,618 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_diff() {
+        let board = setup_board_9_9_10((0, 0), 1);
+        let same = setup_board_9_9_10((0, 0), 1);
+        assert!(board.diff(&same).unwrap().is_empty());
+
+        let other = setup_board_9_9_10((0, 0), 2);
+        let diff = board.diff(&other).unwrap();
+        assert_eq!(diff.mines_removed.len(), diff.mines_added.len());
+        assert!(!diff.mines_removed.is_empty());
+        for pos in diff.mines_removed.iter() {
+            assert!(board.mines.as_ref().unwrap().contains(*pos));
+            assert!(!other.mines.as_ref().unwrap().contains(*pos));
+        }
+
+        let smaller = Board::new(5, 5, 3);
+        assert!(matches!(
+            board.diff(&smaller),
+            Err(DiffError::DimensionMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_iter_region() {
+        let mut board = setup_board_9_9_10((0, 0), 1);
+        board.flag((3, 1)).unwrap();
+        let all = Rect::new((0, 0), 9, 9);
+        let matches_state = |board: &Board| {
+            let state = board.get_board_state();
+            board
+                .iter_region(all)
+                .all(|((x, y), square)| square == state[y][x])
+        };
+        assert!(matches_state(&board));
+        board.open((6, 1)).unwrap();
+        assert_eq!(board.square((6, 1)), Square::Mine);
+        assert!(matches_state(&board));
+
+        let corner: Vec<_> = board.iter_region(Rect::new((7, 7), 5, 5)).collect();
+        let positions: Vec<_> = corner.iter().map(|&(pos, _)| pos).collect();
+        assert_eq!(positions, [(7, 7), (8, 7), (7, 8), (8, 8)]);
+        assert_eq!(board.iter_region(Rect::new((9, 0), 3, 3)).count(), 0);
+    }
+
+    #[test]
+    fn test_legal_moves() {
+        let mut board = Board::new(9, 9, 10);
+        assert_eq!(board.legal_moves().len(), 81);
+
+        board.init_mines((0, 0), Some(1));
+        board.flag((3, 1)).unwrap();
+        let moves = board.legal_moves();
+        let closed = 81 - board.open_fields.len();
+        let chords = moves.iter().filter(|m| matches!(m, Move::Chord(_)));
+        // every closed cell can be flagged, every unflagged closed cell opened
+        assert_eq!(moves.len() - chords.count(), 2 * closed - 1);
+        assert!(moves.contains(&Move::Flag((3, 1))));
+        assert!(!moves.contains(&Move::Open((3, 1))));
+        assert!(!moves.contains(&Move::Open((0, 0))));
+
+        board.open((4, 3)).unwrap();
+        assert!(board.legal_moves().is_empty());
+    }
+
+    #[test]
+    fn test_legal_moves_are_accepted() {
+        for assisted_flags in [false, true] {
+            let config = GameConfig {
+                assisted_flags,
+                ..GameConfig::default()
+            };
+            let mut board = Board::with_config(9, 9, 10, config);
+            board.init_mines((0, 0), Some(1));
+            board.flag((3, 1)).unwrap();
+            if !assisted_flags {
+                board.flag((8, 8)).unwrap();
+            }
+            let moves = board.legal_moves();
+            let mines = board.mines.clone().unwrap();
+            let safe_flag = moves
+                .iter()
+                .any(|mv| matches!(mv, Move::Flag(pos) if !mines.contains(*pos)));
+            assert_eq!(safe_flag, !assisted_flags);
+            for mv in moves {
+                assert!(board.clone().play(mv).is_ok(), "{mv:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_last_changed() {
+        let mut board = setup_board_9_9_10((0, 0), 1);
+        // the first click cascades
+        assert!(board.last_changed().len() > 1);
+        assert_eq!(board.last_changed(), board.opened_cells().as_slice());
+        board.flag((3, 1)).unwrap();
+        assert_eq!(board.last_changed(), &[(3, 1)]);
+        // failed moves keep the previous highlight
+        assert!(board.open((0, 0)).is_err());
+        assert_eq!(board.last_changed(), &[(3, 1)]);
+        board.open((3, 0)).unwrap();
+        assert_eq!(board.last_changed(), &[(3, 0)]);
+        assert!(board.to_string().contains("[1]"));
+    }
+
+    #[test]
+    fn test_last_events() {
+        let mut board = setup_board_9_9_10((0, 0), 1);
+        let events = board.last_events();
+        assert_eq!(events.len(), board.opened_cells().len());
+        assert_eq!(events[0], BoardEvent::Opened((0, 0), 0));
+        assert!(events.contains(&BoardEvent::Opened((2, 0), 1)));
+        board.flag((3, 0)).unwrap();
+        assert_eq!(board.last_events(), [BoardEvent::Flagged((3, 0))]);
+        board.flag((3, 0)).unwrap();
+        assert_eq!(board.last_events(), [BoardEvent::Unflagged((3, 0))]);
+        board.open((3, 1)).unwrap();
+        assert_eq!(board.last_events(), [BoardEvent::Exploded((3, 1))]);
+    }
+
+    #[test]
+    fn test_hint() {
+        let mut board = setup_board_9_9_10((0, 0), 1);
+        // (3, 1) is the only closed neighbor of the 1 at (2, 0), so (3, 0) is safe
+        let Some(Move::Open(pos)) = board.hint() else {
+            panic!("expected a safe cell to open");
+        };
+        assert!(!board.mines.as_ref().unwrap().contains(pos));
+        board.flag((3, 0)).unwrap();
+        assert_ne!(board.hint(), Some(Move::Open((3, 0))));
+        assert_eq!(Board::new(9, 9, 10).hint(), None);
+    }
+
+    #[test]
+    fn test_assisted_flags() {
+        let config = GameConfig {
+            assisted_flags: true,
+            ..GameConfig::default()
+        };
+        let mut board = Board::with_config(9, 9, 10, config);
+        board.init_mines((0, 0), Some(1));
+        assert!(matches!(board.flag((3, 0)), Err(FlagError::NotAMine)));
+        assert!(!board.flagged_fields.contains((3, 0)));
+        assert_eq!(board.flags_rejected, 1);
+        assert_eq!(board.flag((3, 1)).unwrap(), GameState::OnGoing);
+        assert_eq!(board.flag((3, 1)).unwrap(), GameState::OnGoing);
+        assert_eq!(board.replay().unwrap().actions.len(), 3);
+        board.restart();
+        assert_eq!(board.flags_rejected, 0);
+    }
+
+    #[test]
+    fn test_undo() {
+        let mut board = setup_board_9_9_10((0, 0), 1);
+        assert_eq!(board.undo(), Err(UndoError::NothingToUndo));
+        let opened = board.open_fields.clone();
+        board.flag((3, 1)).unwrap();
+        board.open((3, 0)).unwrap();
+        board.open((4, 3)).unwrap();
+        assert!(board.lost());
+        assert!(board.clock.final_time().is_some());
+
+        board.undo().unwrap();
+        assert!(board.ongoing());
+        assert!(board.clock.is_running());
+        assert_eq!(board.clock.splits().len(), 3);
+        assert_eq!(board.replay().unwrap().actions.len(), 3);
+        board.undo().unwrap();
+        board.undo().unwrap();
+        assert_eq!(board.open_fields, opened);
+        assert!(board.flagged_fields.is_empty());
+        assert_eq!(board.undos, 3);
+        assert_eq!(board.undo(), Err(UndoError::NothingToUndo));
+
+        let mut preset = Board::with_mines(3, 3, &[(0, 0)]).unwrap();
+        preset.open((2, 2)).unwrap();
+        assert_eq!(preset.undo(), Err(UndoError::NoReplay));
+    }
+
+    #[test]
+    fn test_from_saved() {
+        let mut board = setup_board_9_9_10((0, 0), 1);
+        board.flag((3, 1)).unwrap();
+        board.open((3, 0)).unwrap();
+        let bytes = crate::codec::encode_replay(&board.replay().unwrap());
+        let replay = crate::codec::decode_replay(&bytes).unwrap();
+        let saved = Board::from_saved(&replay).unwrap();
+        assert_eq!(saved.view(), board.view());
+        // the codec keeps the times of the moves in milliseconds
+        assert_eq!(saved.replay(), Some(replay.clone()));
+        assert!(saved.clock.is_running());
+        assert_eq!(saved.clock.splits().len(), 3);
+        assert!(saved.elapsed() >= replay.actions[2].elapsed);
+        assert!(saved.last_changed().is_empty());
+    }
+
+    #[test]
+    fn test_safe_alternative() {
+        let mut board = setup_board_9_9_10((0, 0), 1);
+        // the 1 at (2, 0) only touches (3, 1) among the closed cells
+        assert_eq!(board.classify((3, 1)), Some(Certainty::Mine));
+        assert_eq!(board.classify((3, 0)), Some(Certainty::Safe));
+        assert_eq!(board.classify((8, 8)), Some(Certainty::Unknown));
+        assert_eq!(board.classify((0, 0)), None);
+        assert_eq!(board.safe_alternative((3, 0)), None);
+        let alternative = board.safe_alternative((8, 8)).unwrap();
+        assert_eq!(board.classify(alternative), Some(Certainty::Safe));
+        assert!(board.safe_alternative((3, 1)).is_some());
+        board.open((4, 3)).unwrap();
+        assert_eq!(board.safe_alternative((8, 8)), None);
+    }
+
+    #[test]
+    fn test_progressive_hints() {
+        let config = GameConfig {
+            first_hint: HintLevel::Number,
+            ..GameConfig::default()
+        };
+        let mut board = Board::with_config(9, 9, 10, config);
+        board.init_mines((0, 0), Some(1));
+        let Some(Move::Open(safe)) = board.hint() else {
+            panic!("expected a safe cell to open");
+        };
+        let Some(Hint::Number(number)) = board.request_hint() else {
+            panic!("expected a number first");
+        };
+        assert!(board.open_fields.contains(number));
+        assert!(board.iter_neighbors(number).any(|n| n == safe));
+        let Some(Hint::Region(region)) = board.request_hint() else {
+            panic!("expected a region second");
+        };
+        assert!(region.contains(&safe));
+        assert_eq!(board.request_hint(), Some(Hint::Move(Move::Open(safe))));
+        assert_eq!(board.request_hint(), Some(Hint::Move(Move::Open(safe))));
+        assert_eq!(board.hints_used, [1, 1, 2]);
+
+        board.open(safe).unwrap();
+        assert_eq!(
+            board.request_hint().map(|h| h.level()),
+            Some(HintLevel::Number)
+        );
+        board.restart();
+        assert_eq!(board.hints_used, [0; 3]);
+    }
+
+    #[test]
+    fn test_safe_radius() {
+        let config = GameConfig {
+            safe_radius: 2,
+            ..GameConfig::default()
+        };
+        let mut board = Board::with_config(9, 9, 10, config);
+        assert_eq!(board.safe_area((0, 0)).len(), 9);
+        assert_eq!(board.safe_area((4, 4)).len(), 25);
+        for seed in 0..20 {
+            board.init_mines((4, 4), Some(seed));
+            let mines = board.mines.as_ref().unwrap();
+            assert!(board.safe_area((4, 4)).iter().all(|&p| !mines.contains(p)));
+        }
+        // 5x5 area around the center leaves no room for 60 mines on a 9x9 board
+        let crowded = Board::with_config(9, 9, 60, board.config.clone());
+        assert_eq!(crowded.safe_area((4, 4)).len(), 9);
+        // a radius read from a save can be anything
+        let mut boundless = crowded;
+        boundless.config.safe_radius = usize::MAX;
+        assert_eq!(boundless.safe_area((4, 4)).len(), 9);
+        boundless.nr_mines = 10;
+        assert_eq!(boundless.safe_area((4, 4)).len(), 49);
+    }
+
+    #[test]
+    fn test_score() {
+        let mut board = setup_board_9_9_10((0, 0), 1);
+        assert_eq!(board.score(), None);
+        board.pause();
+        assert!(board.clock.is_paused());
+        board.flag((3, 1)).unwrap();
+        assert!(!board.clock.is_paused());
+
+        let mines: Vec<Position> = board.mines.as_ref().unwrap().iter().collect();
+        for y in 0..9 {
+            for x in 0..9 {
+                if !mines.contains(&(x, y)) && !board.open_fields.contains((x, y)) {
+                    board.open((x, y)).unwrap();
+                }
+            }
+        }
+        for &pos in mines.iter() {
+            if !board.flagged_fields.contains(pos) {
+                board.flag(pos).unwrap();
+            }
+        }
+        assert_eq!(board.state, GameState::Won);
+        assert!(board.score().unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_first_click_opens_area() {
+        let config = GameConfig {
+            safe_radius: 1,
+            ..GameConfig::default()
+        };
+        for seed in 0..50 {
+            let mut board = Board::with_config(16, 30, 99, config.clone());
+            board.init_mines((0, 0), Some(seed));
+            assert!(board.cascades((0, 0)));
+            assert!(board.opened_cells().len() > 1);
+        }
+    }
+
+    #[test]
+    fn test_win_without_flags() {
+        let config = GameConfig {
+            win_condition: WinCondition::OpenAllSafe,
+            ..GameConfig::default()
+        };
+        let mut board = Board::with_config(9, 9, 10, config);
+        board.init_mines((0, 0), Some(1));
+        let mines = board.mines.clone().unwrap();
+        for pos in minesweeper_solver::grid::Grid::new(9, 9).positions() {
+            if !mines.contains(pos) && !board.open_fields.contains(pos) {
+                board.open(pos).unwrap();
+            }
+        }
+        assert_eq!(board.state, GameState::Won);
+        // the remaining mines are flagged automatically
+        assert_eq!(board.flagged_fields, mines);
+        let flags = board.view().squares.into_iter().flatten();
+        assert_eq!(flags.filter(|s| *s == Square::Flag).count(), 10);
+        assert!(board.last_changed().contains(&(3, 1)));
+    }
+
+    #[test]
+    fn test_restart_same_layout() {
+        let mut board = setup_board_9_9_10((0, 0), 1);
+        let initial_open = board.open_fields.clone();
+        board.flag((3, 1)).unwrap();
+        board.open((4, 3)).unwrap();
+        assert!(board.lost());
+
+        board.restart();
+        assert!(board.ongoing());
+        assert_eq!(board.open_fields, initial_open);
+        assert!(board.flagged_fields.is_empty());
+        assert!(board
+            .diff(&setup_board_9_9_10((0, 0), 1))
+            .unwrap()
+            .is_empty());
+
+        let mut fresh = Board::new(9, 9, 10);
+        fresh.restart();
+        assert!(!fresh.initialized());
+    }
+
+    #[test]
+    fn test_min_move_interval() {
+        let config = GameConfig {
+            min_move_interval: Some(std::time::Duration::from_secs(60)),
+            ..GameConfig::default()
+        };
+        let mut board = Board::with_config(9, 9, 10, config);
+        board.init_mines((0, 0), Some(1));
+        assert!(matches!(board.open((5, 7)), Err(OpenError::TooFast)));
+        assert!(matches!(board.flag((3, 1)), Err(FlagError::TooFast)));
+        assert!(board.flagged_fields.is_empty());
+
+        // rejected moves don't hold up the next one
+        board.last_move = None;
+        assert!(matches!(board.open((0, 0)), Err(OpenError::AlreadyOpen)));
+        assert!(matches!(board.flag((0, 0)), Err(FlagError::AlreadyOpen)));
+        board.flag((3, 1)).unwrap();
+        assert!(matches!(board.open((5, 7)), Err(OpenError::TooFast)));
+
+        board.config.min_move_interval = None;
+        board.flag((3, 1)).unwrap();
+    }
+
+    #[test]
+    fn test_phase_splits() {
+        let config = GameConfig {
+            deterministic: true,
+            ..GameConfig::default()
+        };
+        let mut board = Board::with_config(9, 9, 10, config);
+        assert_eq!(board.phase_splits(), None);
+        board.init_mines((0, 0), Some(1));
+        let mines: Vec<Position> = board.mines.as_ref().unwrap().iter().collect();
+        for y in 0..9 {
+            for x in 0..9 {
+                if !mines.contains(&(x, y)) && !board.open_fields.contains((x, y)) {
+                    board.open((x, y)).unwrap();
+                }
+            }
+        }
+        let phases = board.phase_splits().unwrap();
+        // the first click at (0, 0) clears an opening
+        assert_eq!(phases.first_opening, crate::clock::LOGICAL_TICK);
+        assert!(phases.endgame > Duration::ZERO);
+        let total = phases.first_opening + phases.mid_game + phases.endgame;
+        assert_eq!(total, board.elapsed());
+    }
+
+    #[test]
+    fn test_clock_follows_game() {
+        let mut board = Board::new(9, 9, 10);
+        assert!(!board.clock.started());
+        board.init_mines((0, 0), Some(1));
+        assert!(board.clock.is_running());
+        assert_eq!(board.clock.splits().len(), 1);
+
+        board.flag((3, 1)).unwrap();
+        assert!(board.open((0, 1)).is_err());
+        assert_eq!(board.clock.splits().len(), 2);
+
+        board.open((4, 3)).unwrap();
+        assert_eq!(board.clock.splits().len(), 3);
+        assert!(board.clock.final_time().is_some());
+    }
+
+    #[test]
+    fn test_row_major_order() {
+        let mut board = setup_board_9_9_10((0, 0), 1);
+        board.flag((8, 0)).unwrap();
+        board.flag((3, 1)).unwrap();
+        assert_eq!(board.flagged_cells(), vec![(8, 0), (3, 1)]);
+
+        let frontier = board.get_frontier();
+        assert!(frontier
+            .windows(2)
+            .all(|w| (w[0].1, w[0].0) < (w[1].1, w[1].0)));
+        assert!(frontier.contains(&(2, 0)));
+        assert!(!frontier.contains(&(0, 0)));
+        assert_eq!(board.opened_cells().len(), board.open_fields.len());
+    }
+
+    #[test]
+    fn test_chord() {
+        let mut board = setup_board_9_9_10((0, 0), 1);
+        // (2, 0) shows a 1 for the mine at (3, 1)
+        assert!(matches!(
+            board.chord((2, 0)),
+            Err(ChordError::FlagCountMismatch)
+        ));
+        assert!(matches!(board.chord((5, 5)), Err(ChordError::NotOpen)));
+        assert!(!board.legal_moves().contains(&Move::Chord((2, 0))));
+
+        board.flag((3, 1)).unwrap();
+        assert!(board.legal_moves().contains(&Move::Chord((2, 0))));
+        assert_eq!(board.chord((2, 0)).unwrap(), GameState::OnGoing);
+        assert!(board.open_fields.contains((3, 0)));
+        assert!(matches!(
+            board.chord((2, 0)),
+            Err(ChordError::NothingToOpen)
+        ));
+
+        // a wrongly placed flag makes the chord explode
+        let mut board = setup_board_9_9_10((0, 0), 1);
+        board.flag((3, 0)).unwrap();
+        assert_eq!(board.chord((2, 0)).unwrap(), GameState::Lost);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let mut board = setup_board_9_9_10((0, 0), 1);
+        board.flag((3, 1)).unwrap();
+        let json = serde_json::to_string(&board).unwrap();
+        let mut restored: Board = serde_json::from_str(&json).unwrap();
+        assert!(board.diff(&restored).unwrap().is_empty());
+        assert_eq!(restored.counts, board.counts);
+        assert_eq!(restored.clock.splits(), board.clock.splits());
+        assert!(restored.clock.is_running());
+
+        restored.restart();
+        assert!(restored.flagged_fields.is_empty());
+        assert_eq!(restored.open((4, 3)).unwrap(), GameState::Lost);
+    }
+
+    #[test]
+    fn test_no_guess_generation() {
+        for seed in 0..5 {
+            let mut board = Board::new(9, 9, 10);
+            board.init_mines_no_guess((4, 4), Some(seed)).unwrap();
+            assert!(solver::solvable_without_guessing(&board, &mut {
+                usize::MAX
+            }));
+
+            // the stored seed reproduces the accepted layout
+            let mut replayed = Board::new(9, 9, 10);
+            replayed.init_mines((4, 4), board.seed());
+            assert!(board.diff(&replayed).unwrap().is_empty());
+
+            let metrics = board.metrics().unwrap();
+            let estimate = board.estimate().unwrap();
+            assert!(!estimate.needs_guess());
+            assert!(
+                estimate.bbbv_bounds.0 <= metrics.bbbv && metrics.bbbv <= estimate.bbbv_bounds.1
+            );
+            assert_eq!(estimate.density, 10.0 / 81.0);
+        }
+
+        // only the first click and one other cell are safe, which never resolves
+        let mut board = Board::new(3, 3, 7);
+        assert!(matches!(
+            board.init_mines_no_guess((0, 0), Some(1)),
+            Err(GenerationError::AttemptsExhausted)
+        ));
+        assert!(!board.initialized());
+    }
+
+    #[test]
+    fn test_no_guess_generation_steps() {
+        let board = Board::new(16, 16, 40);
+        let mut generation = board.no_guess_generation((4, 4), Some(3)).unwrap();
+        let seed = loop {
+            if let Some(result) = generation.step(1) {
+                break result.unwrap();
+            }
+        };
+        let progress = generation.progress();
+        assert!(progress.attempts > 0 && progress.fraction() < 1.0);
+        let mut whole = Board::new(16, 16, 40);
+        whole.init_mines_no_guess((4, 4), Some(3)).unwrap();
+        assert_eq!(whole.seed(), Some(seed));
+
+        let mut calls = 0;
+        let mut cancelled = Board::new(16, 30, 99);
+        let result = cancelled.init_mines_no_guess_with((4, 4), Some(3), |progress| {
+            calls += 1;
+            progress.attempts < 32
+        });
+        assert!(matches!(result, Err(GenerationError::Cancelled)));
+        assert_eq!(calls, 2);
+        assert!(!cancelled.initialized());
+
+        // a layout waiting for its first click keeps its mines
+        let mut preset = Board::from_layout_str("...\n...\n..*").unwrap();
+        assert!(preset.no_guess_generation((0, 0), None).is_none());
+        preset.init_mines_no_guess((0, 0), None).unwrap();
+        assert_eq!(preset.open_fields.len(), 8);
+    }
+
+    #[test]
+    fn test_with_mines() {
+        let mut board = Board::with_mines(3, 3, &[(2, 2)]).unwrap();
+        assert!(board.ongoing());
+        assert_eq!(board.count((1, 1)), 1);
+        assert_eq!(board.flag((2, 2)).unwrap(), GameState::OnGoing);
+        assert_eq!(board.open((0, 0)).unwrap(), GameState::Won);
+
+        board.restart();
+        assert!(board.ongoing());
+        assert!(board.opened_cells().is_empty() && board.flagged_cells().is_empty());
+        assert_eq!(board.open((2, 2)).unwrap(), GameState::Lost);
+
+        assert_eq!(
+            Board::with_mines(3, 3, &[(3, 0)]).err(),
+            Some(MinesError::OutOfBounds((3, 0)))
+        );
+        assert_eq!(
+            Board::with_mines(3, 3, &[(1, 1), (1, 1)]).err(),
+            Some(MinesError::Duplicate((1, 1)))
+        );
+        assert_eq!(
+            Board::with_mines(1, 2, &[(0, 0), (1, 0)]).err(),
+            Some(MinesError::NoSafeCell)
+        );
+    }
+
+    #[test]
+    fn test_hex_board() {
+        let config = GameConfig {
+            safe_radius: 1,
+            win_condition: WinCondition::OpenAllSafe,
+            ..GameConfig::default()
+        };
+        let mut board = Board::with_topology(HexGrid, 6, 6, 8, config);
+        // the first click and its six neighbors
+        assert_eq!(board.safe_area((2, 2)).len(), 7);
+        board.init_mines((2, 2), Some(1));
+        let mines = board.mines.clone().unwrap();
+        for pos in minesweeper_solver::grid::Grid::new(6, 6).positions() {
+            let count = HexGrid
+                .neighbors(6, 6, pos, false)
+                .filter(|&n| mines.contains(n))
+                .count();
+            assert_eq!(board.count(pos) as usize, count);
+            if !mines.contains(pos) && !board.open_fields.contains(pos) {
+                board.open(pos).unwrap();
+            }
+        }
+        assert_eq!(board.state, GameState::Won);
+    }
+
+    #[test]
+    fn test_wrapped_board() {
+        let config = GameConfig {
+            wrap_edges: true,
+            ..GameConfig::default()
+        };
+        let mut board = Board::with_config(4, 5, 1, config);
+        assert_eq!(board.iter_neighbors((0, 0)).count(), 8);
+        board.mines = Some(Mask::from_positions(board.grid(), [(4, 3)]));
+        board.init_mines((2, 1), None);
+        // the mine in the bottom right corner touches the top left one
+        assert_eq!(board.count((0, 0)), 1);
+        assert_eq!(board.view().constraint((0, 0)).unwrap().unopened, 1);
+        assert_eq!(board.open_fields.len(), 19);
+        assert_eq!(board.metrics().unwrap().bbbv, 1);
+        assert_eq!(board.flag((4, 3)).unwrap(), GameState::Won);
+    }
+
     #[test]
     fn test_open_bomb() {
         let mut board = setup_board_9_9_10((0, 0), 1);
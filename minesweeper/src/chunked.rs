@@ -0,0 +1,369 @@
+//! Enormous boards, say 10,000 x 10,000, whose mines are placed chunk by chunk
+//! as the player gets near them. Memory and generation time follow the explored
+//! area instead of the size of the board.
+
+use std::collections::{HashMap, VecDeque};
+
+use rand::seq::index;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::board::{FlagError, GameState, Grid, Mask, OpenError, Position, Rect, Square};
+
+/// Side of the square chunks the mines are placed in.
+pub const CHUNK_SIZE: usize = 64;
+
+// the first streams of the seed are used by `Board`
+const FIRST_CHUNK_STREAM: u64 = 3;
+
+struct Chunk {
+    mines: Mask,
+    open: Mask,
+    flagged: Mask,
+}
+
+/// A board with the classic rules where only the chunks of `CHUNK_SIZE` x
+/// `CHUNK_SIZE` cells around opened and flagged cells exist. Every chunk holds
+/// its share of the mines by area, placed uniformly within the chunk from its
+/// own stream of the seed, so a chunk comes out the same whenever it is created.
+///
+/// The first click has to go through `init_mines`, like on `Board`.
+pub struct ChunkedBoard {
+    pub rows: usize,
+    pub cols: usize,
+    /// Can end up a few below the number asked for when the safe area takes up
+    /// most of its chunks.
+    pub nr_mines: usize,
+    pub state: GameState,
+    /// Cells within this many steps of the first click never hold a mine.
+    pub safe_radius: usize,
+    seed: u64,
+    start_position: Option<Position>,
+    chunks: HashMap<Position, Chunk>,
+    nr_opened: usize,
+}
+
+impl ChunkedBoard {
+    pub fn new(rows: usize, cols: usize, nr_mines: usize) -> ChunkedBoard {
+        ChunkedBoard {
+            rows,
+            cols,
+            nr_mines: nr_mines.min((rows * cols).saturating_sub(1)),
+            state: GameState::Init,
+            safe_radius: 0,
+            seed: 0,
+            start_position: None,
+            chunks: HashMap::new(),
+            nr_opened: 0,
+        }
+    }
+
+    /// Opens the first cell, after which the chunks are placed from `seed`, or a
+    /// random seed if `None`.
+    pub fn init_mines(&mut self, start_position: Position, seed: Option<u64>) {
+        self.seed = seed.unwrap_or_else(rand::random);
+        self.start_position = Some(start_position);
+        self.chunks.clear();
+        self.nr_opened = 0;
+        self.state = GameState::OnGoing;
+        // the chunks under the safe area may not fit their share of the mines
+        let (x, y) = start_position;
+        let r = self.safe_radius;
+        let top_left = (x.saturating_sub(r), y.saturating_sub(r));
+        let bottom_right = ((x + r).min(self.cols - 1), (y + r).min(self.rows - 1));
+        for cy in top_left.1 / CHUNK_SIZE..=bottom_right.1 / CHUNK_SIZE {
+            for cx in top_left.0 / CHUNK_SIZE..=bottom_right.0 / CHUNK_SIZE {
+                let placed = self.chunk((cx * CHUNK_SIZE, cy * CHUNK_SIZE)).mines.len();
+                self.nr_mines -= self.quota((cx, cy)) - placed;
+            }
+        }
+        self.open(start_position).unwrap();
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn grid(&self) -> Grid {
+        Grid::new(self.rows, self.cols)
+    }
+
+    /// Number of chunks placed so far.
+    pub fn generated_chunks(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn nr_opened(&self) -> usize {
+        self.nr_opened
+    }
+
+    pub fn open(&mut self, pos: Position) -> Result<GameState, OpenError> {
+        match self.state {
+            GameState::Init => return Err(OpenError::MinesNotInit),
+            GameState::Lost => return Err(OpenError::AlreadyLost),
+            GameState::Won => return Err(OpenError::AlreadyWon),
+            GameState::OnGoing => {}
+        }
+        if !self.grid().contains(pos) {
+            return Err(OpenError::OutOfBounds);
+        }
+        let (local, chunk) = self.local(pos);
+        if chunk.open.contains(local) {
+            return Err(OpenError::AlreadyOpen);
+        }
+        if chunk.flagged.contains(local) {
+            return Err(OpenError::AlreadyFlagged);
+        }
+        if chunk.mines.contains(local) {
+            self.state = GameState::Lost;
+            return Ok(self.state);
+        }
+        self.reveal(pos);
+        if self.nr_opened == self.grid().len() - self.nr_mines {
+            self.state = GameState::Won;
+        }
+        Ok(self.state)
+    }
+
+    /// Opens `pos` and, breadth-first, the zero region around it.
+    fn reveal(&mut self, pos: Position) {
+        let mut next = VecDeque::from([pos]);
+        self.mark_open(pos);
+        while let Some(p) = next.pop_front() {
+            if self.count(p) > 0 {
+                continue;
+            }
+            for n in self.grid().neighbors(p) {
+                let (local, chunk) = self.local(n);
+                if !chunk.open.contains(local) && !chunk.flagged.contains(local) {
+                    self.mark_open(n);
+                    next.push_back(n);
+                }
+            }
+        }
+    }
+
+    fn mark_open(&mut self, pos: Position) {
+        let (local, chunk) = self.local(pos);
+        chunk.open.insert(local);
+        self.nr_opened += 1;
+    }
+
+    /// Flags `pos`, or removes its flag.
+    pub fn flag(&mut self, pos: Position) -> Result<GameState, FlagError> {
+        match self.state {
+            GameState::Init => return Err(FlagError::MinesNotInit),
+            GameState::Lost => return Err(FlagError::AlreadyLost),
+            GameState::Won => return Err(FlagError::AlreadyWon),
+            GameState::OnGoing => {}
+        }
+        if !self.grid().contains(pos) {
+            return Err(FlagError::OutOfBounds);
+        }
+        let (local, chunk) = self.local(pos);
+        if chunk.open.contains(local) {
+            return Err(FlagError::AlreadyOpen);
+        }
+        if !chunk.flagged.insert(local) {
+            chunk.flagged.remove(local);
+        }
+        Ok(self.state)
+    }
+
+    /// Mines around `pos`, placing the chunks it touches if needed.
+    pub fn count(&mut self, pos: Position) -> u8 {
+        let neighbors: Vec<Position> = self.grid().neighbors(pos).collect();
+        neighbors
+            .into_iter()
+            .filter(|&n| {
+                let (local, chunk) = self.local(n);
+                chunk.mines.contains(local)
+            })
+            .count() as u8
+    }
+
+    /// What the player sees at `pos`. Chunks that were never placed are closed.
+    pub fn get(&self, pos: Position) -> Square {
+        let is = |pos: Position, mask: fn(&Chunk) -> &Mask| {
+            let (chunk, local) = split(pos);
+            self.chunks
+                .get(&chunk)
+                .is_some_and(|chunk| mask(chunk).contains(local))
+        };
+        if is(pos, |c| &c.flagged) {
+            Square::Flag
+        } else if is(pos, |c| &c.open) {
+            // opening a cell placed the chunks around it
+            let count = self
+                .grid()
+                .neighbors(pos)
+                .filter(|&n| is(n, |c| &c.mines))
+                .count();
+            Square::Opened(count as i8)
+        } else if self.state == GameState::Lost && is(pos, |c| &c.mines) {
+            Square::Mine
+        } else {
+            Square::NotYetOpened
+        }
+    }
+
+    /// The squares of the `rows` x `cols` window whose top left cell is `origin`,
+    /// cut off at the edges of the board.
+    pub fn region(&self, origin: Position, rows: usize, cols: usize) -> Vec<Vec<Square>> {
+        let (x0, y0) = origin;
+        (y0..(y0 + rows).min(self.rows))
+            .map(|y| {
+                (x0..(x0 + cols).min(self.cols))
+                    .map(|x| self.get((x, y)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The squares of `rect` in row-major order, without collecting the window.
+    pub fn iter_region(&self, rect: Rect) -> impl Iterator<Item = (Position, Square)> + '_ {
+        rect.clip(self.rows, self.cols)
+            .positions()
+            .map(|pos| (pos, self.get(pos)))
+    }
+
+    /// The chunk holding `pos`, placing it first if needed, and the position of
+    /// `pos` inside it.
+    fn local(&mut self, pos: Position) -> (Position, &mut Chunk) {
+        let local = split(pos).1;
+        (local, self.chunk(pos))
+    }
+
+    fn chunk(&mut self, pos: Position) -> &mut Chunk {
+        let (index, _) = split(pos);
+        if !self.chunks.contains_key(&index) {
+            let chunk = self.place(index);
+            self.chunks.insert(index, chunk);
+        }
+        self.chunks.get_mut(&index).unwrap()
+    }
+
+    fn place(&self, (cx, cy): Position) -> Chunk {
+        let (x0, y0) = (cx * CHUNK_SIZE, cy * CHUNK_SIZE);
+        let grid = Grid::new(
+            CHUNK_SIZE.min(self.rows - y0),
+            CHUNK_SIZE.min(self.cols - x0),
+        );
+        let start = self
+            .start_position
+            .expect("chunks are placed after the first click");
+        let r = self.safe_radius;
+        let free: Vec<Position> = grid
+            .positions()
+            .filter(|&(x, y)| {
+                x0 + x + r < start.0
+                    || start.0 + r < x0 + x
+                    || y0 + y + r < start.1
+                    || start.1 + r < y0 + y
+            })
+            .collect();
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        let chunks_per_row = self.cols.div_ceil(CHUNK_SIZE);
+        rng.set_stream(FIRST_CHUNK_STREAM + (cy * chunks_per_row + cx) as u64);
+        let amount = self.quota((cx, cy)).min(free.len());
+        let mines = index::sample(&mut rng, free.len(), amount)
+            .into_iter()
+            .map(|i| free[i]);
+        Chunk {
+            mines: Mask::from_positions(grid, mines),
+            open: Mask::new(grid),
+            flagged: Mask::new(grid),
+        }
+    }
+
+    /// The share of the mines for a chunk, proportional to its area. The shares
+    /// are cut from a running total over the chunks in row-major order, so they
+    /// always add up to the number of mines.
+    fn quota(&self, (cx, cy): Position) -> usize {
+        let height = CHUNK_SIZE.min(self.rows - cy * CHUNK_SIZE);
+        let before =
+            |cx: usize| cy * CHUNK_SIZE * self.cols + (cx * CHUNK_SIZE).min(self.cols) * height;
+        let share = |cells: usize| {
+            (self.nr_mines as u128 * cells as u128 / (self.rows * self.cols) as u128) as usize
+        };
+        share(before(cx + 1)) - share(before(cx))
+    }
+}
+
+/// The chunk of `pos` and the position inside that chunk.
+fn split((x, y): Position) -> (Position, Position) {
+    (
+        (x / CHUNK_SIZE, y / CHUNK_SIZE),
+        (x % CHUNK_SIZE, y % CHUNK_SIZE),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quotas_add_up() {
+        let board = ChunkedBoard::new(150, 200, 4321);
+        let (rows, cols) = (150usize.div_ceil(CHUNK_SIZE), 200usize.div_ceil(CHUNK_SIZE));
+        let total: usize = (0..rows)
+            .flat_map(|cy| (0..cols).map(move |cx| (cx, cy)))
+            .map(|chunk| board.quota(chunk))
+            .sum();
+        assert_eq!(total, 4321);
+    }
+
+    #[test]
+    fn test_huge_board() {
+        let mut board = ChunkedBoard::new(10_000, 10_000, 15_000_000);
+        board.safe_radius = 1;
+        board.init_mines((5_000, 5_000), Some(1));
+        assert_eq!(board.state, GameState::OnGoing);
+        assert_eq!(board.get((5_000, 5_000)), Square::Opened(0));
+        assert!(board.generated_chunks() < 10);
+        let window: Vec<_> = board
+            .iter_region(Rect::new((4_990, 4_990), 20, 20))
+            .collect();
+        assert_eq!(window.len(), 400);
+        assert!(window.contains(&((5_000, 5_000), Square::Opened(0))));
+        assert_eq!(board.iter_region(Rect::new((9_990, 0), 5, 100)).count(), 50);
+
+        // the same seed places the same mines, whichever chunk is placed first
+        let mut other = ChunkedBoard::new(10_000, 10_000, 15_000_000);
+        other.safe_radius = 1;
+        other.init_mines((5_000, 5_000), Some(1));
+        other.count((9_000, 9_000));
+        for x in 4_900..5_100 {
+            let pos = (x, 5_100);
+            assert_eq!(board.count(pos), other.count(pos));
+        }
+        assert_eq!(board.nr_mines, 15_000_000);
+    }
+
+    #[test]
+    fn test_play() {
+        let mut board = ChunkedBoard::new(100, 100, 0);
+        assert!(matches!(board.open((0, 0)), Err(OpenError::MinesNotInit)));
+        board.init_mines((50, 50), Some(3));
+        assert_eq!(board.state, GameState::Won);
+        assert_eq!(board.nr_opened(), 100 * 100);
+
+        let mut board = ChunkedBoard::new(100, 100, 9_000);
+        board.safe_radius = 1;
+        board.init_mines((0, 0), Some(3));
+        assert_eq!(board.get((0, 0)), Square::Opened(0));
+        assert!(board.nr_opened() >= 4);
+        assert_eq!(board.flag((5, 5)).unwrap(), GameState::OnGoing);
+        assert_eq!(board.get((5, 5)), Square::Flag);
+        assert!(matches!(board.open((5, 5)), Err(OpenError::AlreadyFlagged)));
+        let mine = (0..100)
+            .flat_map(|y| (0..100).map(move |x| (x, y)))
+            .find(|&pos| {
+                let (local, chunk) = board.local(pos);
+                chunk.mines.contains(local)
+            })
+            .unwrap();
+        assert_eq!(board.open(mine).unwrap(), GameState::Lost);
+        assert_eq!(board.get(mine), Square::Mine);
+    }
+}
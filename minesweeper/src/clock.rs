@@ -0,0 +1,286 @@
+use std::time::Duration;
+
+use web_time::Instant;
+
+/// Time a logical clock advances per move.
+pub const LOGICAL_TICK: Duration = Duration::from_secs(1);
+
+/// Monotonic game clock, so every frontend reports exactly the same timing.
+///
+/// The clock starts on the first click, records a split for every accepted
+/// move and stops once the game is won or lost. Time spent paused is not counted.
+/// A logical clock ignores the wall clock and advances one `LOGICAL_TICK` per
+/// move, for reproducible games.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "SavedClock", into = "SavedClock")
+)]
+pub struct GameClock {
+    start: Option<Instant>,
+    end: Option<Instant>,
+    paused_at: Option<Instant>,
+    paused_for: Duration,
+    /// Time played before `start`, by a game this one continues. Instants
+    /// can't go back further than the process or the page on some platforms.
+    carried: Duration,
+    splits: Vec<Duration>,
+    logical: bool,
+}
+
+impl GameClock {
+    pub fn start(&mut self) {
+        *self = GameClock {
+            start: Some(Instant::now()),
+            ..GameClock::default()
+        };
+    }
+
+    pub fn start_logical(&mut self) {
+        self.start();
+        self.logical = true;
+    }
+
+    pub fn stop(&mut self) {
+        if self.is_running() {
+            self.resume();
+            self.end = Some(Instant::now());
+        }
+    }
+
+    /// Stops counting time until `resume` is called, only while running.
+    pub fn pause(&mut self) {
+        if self.is_running() && self.paused_at.is_none() {
+            self.paused_at = Some(Instant::now());
+        }
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused_for += paused_at.elapsed();
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    pub fn reset(&mut self) {
+        *self = GameClock::default();
+    }
+
+    /// Records the elapsed time of a move.
+    pub fn split(&mut self) -> Duration {
+        if !self.is_running() {
+            return self.elapsed();
+        }
+        let elapsed = match self.logical {
+            true => LOGICAL_TICK * (self.splits.len() as u32 + 1),
+            false => self.elapsed(),
+        };
+        self.splits.push(elapsed);
+        elapsed
+    }
+
+    /// A clock continuing a game after the moves made at `splits`, running
+    /// unless `running` is false.
+    pub(crate) fn resumed(splits: Vec<Duration>, running: bool, logical: bool) -> GameClock {
+        let now = Instant::now();
+        GameClock {
+            start: Some(now),
+            end: (!running).then_some(now),
+            paused_at: None,
+            paused_for: Duration::ZERO,
+            carried: splits.last().copied().unwrap_or_default(),
+            splits,
+            logical,
+        }
+    }
+
+    /// Drops the split of a move taken back, running again if it ended the game.
+    pub(crate) fn take_back(&mut self) {
+        self.splits.pop();
+        self.resume();
+        self.end = None;
+    }
+
+    pub fn started(&self) -> bool {
+        self.start.is_some()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.start.is_some() && self.end.is_none()
+    }
+
+    pub fn is_logical(&self) -> bool {
+        self.logical
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        let Some(start) = self.start else {
+            return Duration::ZERO;
+        };
+        if self.logical {
+            return self.splits.last().copied().unwrap_or_default();
+        }
+        let until = self.end.or(self.paused_at).unwrap_or_else(Instant::now);
+        self.carried + until.duration_since(start).saturating_sub(self.paused_for)
+    }
+
+    pub fn elapsed_millis(&self) -> u128 {
+        self.elapsed().as_millis()
+    }
+
+    /// Time of the finished game, `None` while the game is still running.
+    pub fn final_time(&self) -> Option<Duration> {
+        self.end.map(|_| self.elapsed())
+    }
+
+    /// Elapsed time at each move since the start of the game.
+    pub fn splits(&self) -> &[Duration] {
+        &self.splits
+    }
+}
+
+/// Serialized form of a clock, instants are process local so only the elapsed
+/// time is stored and a restored clock resumes from there.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedClock {
+    elapsed: Option<Duration>,
+    running: bool,
+    #[serde(default)]
+    paused: bool,
+    splits: Vec<Duration>,
+    #[serde(default)]
+    logical: bool,
+}
+
+#[cfg(feature = "serde")]
+impl From<GameClock> for SavedClock {
+    fn from(clock: GameClock) -> Self {
+        SavedClock {
+            elapsed: clock.started().then(|| clock.elapsed()),
+            running: clock.is_running(),
+            paused: clock.is_paused(),
+            splits: clock.splits,
+            logical: clock.logical,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<SavedClock> for GameClock {
+    fn from(saved: SavedClock) -> Self {
+        let Some(elapsed) = saved.elapsed else {
+            return GameClock::default();
+        };
+        let now = Instant::now();
+        GameClock {
+            start: Some(now),
+            end: (!saved.running).then_some(now),
+            paused_at: (saved.running && saved.paused).then_some(now),
+            paused_for: Duration::ZERO,
+            carried: elapsed,
+            splits: saved.splits,
+            logical: saved.logical,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clock_lifecycle() {
+        let mut clock = GameClock::default();
+        assert!(!clock.started());
+        assert_eq!(clock.split(), Duration::ZERO);
+        assert!(clock.splits().is_empty());
+
+        clock.start();
+        clock.split();
+        clock.split();
+        assert_eq!(clock.splits().len(), 2);
+        assert!(clock.splits()[0] <= clock.splits()[1]);
+        assert!(clock.final_time().is_none());
+
+        clock.stop();
+        let final_time = clock.final_time().unwrap();
+        clock.split();
+        assert_eq!(clock.splits().len(), 2);
+        assert_eq!(clock.elapsed(), final_time);
+    }
+
+    #[test]
+    fn test_clock_pause() {
+        let mut clock = GameClock::default();
+        clock.pause();
+        assert!(!clock.is_paused());
+
+        clock.start();
+        clock.pause();
+        assert!(clock.is_paused());
+        let paused = clock.elapsed();
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(clock.elapsed(), paused);
+
+        clock.resume();
+        assert!(!clock.is_paused());
+        assert!(clock.elapsed() < paused + Duration::from_millis(20));
+
+        clock.pause();
+        clock.stop();
+        assert!(!clock.is_paused());
+        assert!(clock.final_time().is_some());
+    }
+
+    #[test]
+    fn test_logical_clock() {
+        let mut clock = GameClock::default();
+        clock.start_logical();
+        assert_eq!(clock.elapsed(), Duration::ZERO);
+        std::thread::sleep(Duration::from_millis(5));
+        assert_eq!(clock.split(), LOGICAL_TICK);
+        assert_eq!(clock.split(), 2 * LOGICAL_TICK);
+        clock.take_back();
+        assert_eq!(clock.elapsed(), LOGICAL_TICK);
+        clock.stop();
+        assert_eq!(clock.final_time(), Some(LOGICAL_TICK));
+
+        clock.start();
+        assert!(!clock.is_logical());
+    }
+
+    #[test]
+    fn test_resume_longer_than_process() {
+        let played = Duration::from_secs(400 * 24 * 3600);
+        let mut clock = GameClock::resumed(vec![Duration::from_secs(1), played], true, false);
+        assert!(clock.elapsed() >= played);
+        assert!(clock.split() >= played);
+        clock.stop();
+        assert!(clock.final_time().unwrap() >= played);
+
+        let stopped = GameClock::resumed(vec![played], false, false);
+        assert_eq!(stopped.final_time(), Some(played));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_saved_clock_longer_than_process() {
+        let played = Duration::from_secs(400 * 24 * 3600);
+        let clock = GameClock::resumed(vec![played], false, false);
+        let json = serde_json::to_string(&clock).unwrap();
+        let restored: GameClock = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.final_time(), Some(played));
+
+        let mut running = GameClock::resumed(vec![played], true, false);
+        running.pause();
+        let json = serde_json::to_string(&running).unwrap();
+        let restored: GameClock = serde_json::from_str(&json).unwrap();
+        assert!(restored.is_paused());
+        assert_eq!(restored.elapsed(), running.elapsed());
+    }
+}
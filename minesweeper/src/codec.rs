@@ -0,0 +1,693 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crate::board::{Adjacency, GameState, HintLevel, Move, Position, Rect, Square};
+use crate::config::{GameConfig, WinCondition};
+use crate::replay::{Replay, ReplayAction};
+use crate::view::BoardView;
+
+// first byte of every encoding, followed by the format version
+const VIEW_TAG: u8 = b'V';
+const REPLAY_TAG: u8 = b'R';
+const REGION_TAG: u8 = b'C';
+// raised when an encoding changes, the decoders then keep reading the older
+// versions; newer ones are rejected with `UnsupportedVersion`
+const VERSION: u8 = 1;
+
+// replays don't carry their cells, and larger boards would take gigabytes to
+// replay; no real game comes anywhere near
+const MAX_REPLAY_CELLS: usize = 1 << 24;
+
+const SHARE_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input ended in the middle of a value.
+    UnexpectedEnd,
+    /// The input holds another kind of encoding.
+    WrongTag,
+    UnsupportedVersion(u8),
+    /// A value is out of range, e.g. a cell outside the board.
+    InvalidValue,
+    /// Bytes were left over after the encoded value.
+    TrailingBytes,
+}
+
+/// Encodes a view with four bits per cell, small enough to send after every move.
+/// With anti-mines or wormholes the counts can be negative or above 8 and every
+/// cell takes a byte.
+pub fn encode_view(view: &BoardView) -> Vec<u8> {
+    let mut out = vec![VIEW_TAG, VERSION];
+    write_varint(&mut out, view.rows as u64);
+    write_varint(&mut out, view.cols as u64);
+    write_varint(&mut out, view.nr_mines as u64);
+    out.push(state_code(view.state));
+    out.push(view.wrap_edges as u8);
+    write_varint(&mut out, view.nr_anti_mines as u64);
+    out.push(adjacency_code(view.adjacency));
+    write_wormholes(&mut out, view.cols, &view.wormholes);
+    out.push(view.liar as u8);
+    if view.nr_anti_mines > 0 || !view.wormholes.is_empty() {
+        out.extend(view.squares.iter().flatten().map(|&s| wide_square_code(s)));
+        return out;
+    }
+    let nibbles: Vec<u8> = view
+        .squares
+        .iter()
+        .flatten()
+        .map(|&s| square_code(s))
+        .collect();
+    for pair in nibbles.chunks(2) {
+        out.push(pair[0] | pair.get(1).unwrap_or(&0) << 4);
+    }
+    out
+}
+
+pub fn decode_view(bytes: &[u8]) -> Result<BoardView, DecodeError> {
+    let mut reader = Reader::new(bytes, VIEW_TAG)?;
+    let rows = reader.usize()?;
+    let cols = reader.usize()?;
+    let nr_mines = reader.usize()?;
+    let state = state_from_code(reader.byte()?)?;
+    let wrap_edges = bool_from_code(reader.byte()?)?;
+    let nr_anti_mines = reader.usize()?;
+    let adjacency = adjacency_from_code(reader.byte()?)?;
+    let cells = rows.checked_mul(cols).ok_or(DecodeError::InvalidValue)?;
+    if cells == 0 {
+        return Err(DecodeError::InvalidValue);
+    }
+    let wormholes = reader.wormholes(cols, cells)?;
+    let liar = bool_from_code(reader.byte()?)?;
+    let mut squares = Vec::with_capacity(rows);
+    if nr_anti_mines > 0 || !wormholes.is_empty() {
+        let codes = reader.bytes(cells)?;
+        for row in codes.chunks(cols) {
+            squares.push(
+                row.iter()
+                    .map(|&code| wide_square_from_code(code))
+                    .collect::<Result<_, _>>()?,
+            );
+        }
+    } else {
+        let packed = reader.bytes(cells.div_ceil(2))?;
+        for y in 0..rows {
+            let mut row = Vec::with_capacity(cols);
+            for x in 0..cols {
+                let i = y * cols + x;
+                row.push(square_from_code(packed[i / 2] >> (4 * (i % 2)) & 0xf)?);
+            }
+            squares.push(row);
+        }
+    }
+    reader.finish()?;
+    Ok(BoardView {
+        rows,
+        cols,
+        nr_mines,
+        state,
+        squares,
+        wrap_edges,
+        nr_anti_mines,
+        adjacency,
+        wormholes,
+        liar,
+    })
+}
+
+/// Encodes the squares of `rect`, row-major, with a byte per cell. A large board
+/// is sent as a series of regions, e.g. the part in view, so neither side needs
+/// the whole board at once. `rect` has to be clipped to the board.
+pub fn encode_region(rect: Rect, squares: impl IntoIterator<Item = Square>) -> Vec<u8> {
+    let mut out = vec![REGION_TAG, VERSION];
+    write_varint(&mut out, rect.origin.0 as u64);
+    write_varint(&mut out, rect.origin.1 as u64);
+    write_varint(&mut out, rect.rows as u64);
+    write_varint(&mut out, rect.cols as u64);
+    out.extend(squares.into_iter().map(wide_square_code));
+    out
+}
+
+pub fn decode_region(bytes: &[u8]) -> Result<(Rect, Vec<Square>), DecodeError> {
+    let mut reader = Reader::new(bytes, REGION_TAG)?;
+    let origin = (reader.usize()?, reader.usize()?);
+    let rect = Rect::new(origin, reader.usize()?, reader.usize()?);
+    let cells = rect
+        .rows
+        .checked_mul(rect.cols)
+        .ok_or(DecodeError::InvalidValue)?;
+    let squares = reader
+        .bytes(cells)?
+        .iter()
+        .map(|&code| wide_square_from_code(code))
+        .collect::<Result<_, _>>()?;
+    reader.finish()?;
+    Ok((rect, squares))
+}
+
+/// Encodes a replay as varints, each move taking about three bytes. Move times
+/// are kept to the millisecond.
+pub fn encode_replay(replay: &Replay) -> Vec<u8> {
+    let mut out = vec![REPLAY_TAG, VERSION];
+    write_varint(&mut out, replay.rows as u64);
+    write_varint(&mut out, replay.cols as u64);
+    write_varint(&mut out, replay.nr_mines as u64);
+    write_varint(&mut out, replay.seed);
+    // 0 for no interval, the interval in milliseconds plus one otherwise
+    let interval = replay
+        .config
+        .min_move_interval
+        .map_or(0, |d| d.as_millis() as u64 + 1);
+    write_varint(&mut out, interval);
+    write_varint(&mut out, replay.config.safe_radius as u64);
+    out.push(match replay.config.win_condition {
+        WinCondition::FlagAllMines => 0,
+        WinCondition::OpenAllSafe => 1,
+    });
+    out.push(replay.config.wrap_edges as u8);
+    // the name of the variant prefixed by its length, 0 for none
+    let variant = replay.config.variant.as_deref().unwrap_or_default();
+    write_varint(&mut out, variant.len() as u64);
+    out.extend_from_slice(variant.as_bytes());
+    out.push(adjacency_code(replay.config.adjacency));
+    write_wormholes(&mut out, replay.cols, &replay.config.wormholes);
+    out.push(replay.config.liar as u8);
+    out.push(replay.config.assisted_flags as u8);
+    out.push(replay.config.deterministic as u8);
+    write_varint(&mut out, replay.actions.len() as u64);
+    let mut previous = 0;
+    for action in replay.actions.iter() {
+        let (kind, (x, y)) = match action.mv {
+            Move::Open(pos) => (0, pos),
+            Move::Flag(pos) => (1, pos),
+            Move::Chord(pos) => (2, pos),
+        };
+        write_varint(&mut out, ((y * replay.cols + x) * 3 + kind) as u64);
+        // times only grow, so the difference to the previous move stays small
+        let millis = action.elapsed.as_millis() as u64;
+        write_varint(&mut out, millis.saturating_sub(previous));
+        previous = previous.max(millis);
+    }
+    out
+}
+
+pub fn decode_replay(bytes: &[u8]) -> Result<Replay, DecodeError> {
+    let mut reader = Reader::new(bytes, REPLAY_TAG)?;
+    let rows = reader.usize()?;
+    let cols = reader.usize()?;
+    let nr_mines = reader.usize()?;
+    let seed = reader.varint()?;
+    let min_move_interval = match reader.varint()? {
+        0 => None,
+        millis => Some(Duration::from_millis(millis - 1)),
+    };
+    let safe_radius = reader.usize()?;
+    let win_condition = match reader.byte()? {
+        0 => WinCondition::FlagAllMines,
+        1 => WinCondition::OpenAllSafe,
+        _ => return Err(DecodeError::InvalidValue),
+    };
+    let wrap_edges = bool_from_code(reader.byte()?)?;
+    let variant = match reader.usize()? {
+        0 => None,
+        len => Some(
+            String::from_utf8(reader.bytes(len)?.to_vec())
+                .map_err(|_| DecodeError::InvalidValue)?,
+        ),
+    };
+    let adjacency = adjacency_from_code(reader.byte()?)?;
+    let cells = rows.checked_mul(cols).ok_or(DecodeError::InvalidValue)?;
+    // the board needs a safe cell for the first click
+    if cells > MAX_REPLAY_CELLS || nr_mines >= cells {
+        return Err(DecodeError::InvalidValue);
+    }
+    let config = GameConfig {
+        min_move_interval,
+        safe_radius,
+        win_condition,
+        wrap_edges,
+        variant,
+        adjacency,
+        wormholes: reader.wormholes(cols, cells)?,
+        liar: bool_from_code(reader.byte()?)?,
+        // hints are not part of the game record
+        first_hint: HintLevel::default(),
+        assisted_flags: bool_from_code(reader.byte()?)?,
+        // placed again from the seed by the variant
+        treasures: Vec::new(),
+        deterministic: bool_from_code(reader.byte()?)?,
+    };
+    let count = reader.usize()?;
+    // every action takes at least two bytes, don't trust the count for allocating
+    let mut actions = Vec::with_capacity(count.min(reader.bytes.len() / 2));
+    let mut elapsed = 0u64;
+    for _ in 0..count {
+        let code = reader.usize()?;
+        let (index, kind) = (code / 3, code % 3);
+        if index >= cells {
+            return Err(DecodeError::InvalidValue);
+        }
+        let pos = (index % cols, index / cols);
+        let mv = match kind {
+            0 => Move::Open(pos),
+            1 => Move::Flag(pos),
+            _ => Move::Chord(pos),
+        };
+        elapsed = elapsed
+            .checked_add(reader.varint()?)
+            .ok_or(DecodeError::InvalidValue)?;
+        actions.push(ReplayAction {
+            mv,
+            elapsed: Duration::from_millis(elapsed),
+        });
+    }
+    reader.finish()?;
+    Ok(Replay {
+        rows,
+        cols,
+        nr_mines,
+        seed,
+        config,
+        actions,
+    })
+}
+
+/// URL safe base64 without padding, for codes that are pasted into chats or links.
+pub fn to_share_code(bytes: &[u8]) -> String {
+    let mut code = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..=chunk.len() {
+            code.push(SHARE_ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+        }
+    }
+    code
+}
+
+pub fn from_share_code(code: &str) -> Result<Vec<u8>, DecodeError> {
+    let values = code
+        .trim()
+        .bytes()
+        .map(|c| {
+            SHARE_ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .map(|v| v as u32)
+        })
+        .collect::<Option<Vec<u32>>>()
+        .ok_or(DecodeError::InvalidValue)?;
+    let mut bytes = Vec::with_capacity(values.len() * 3 / 4);
+    for chunk in values.chunks(4) {
+        if chunk.len() == 1 {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |n, (i, v)| n | v << (18 - 6 * i));
+        for i in 0..chunk.len() - 1 {
+            bytes.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Compresses an encoding with zstd, worthwhile for long replays and large views.
+#[cfg(feature = "zstd")]
+pub fn compress(bytes: &[u8]) -> Vec<u8> {
+    zstd::encode_all(bytes, 19).expect("compressing to memory does not fail")
+}
+
+#[cfg(feature = "zstd")]
+pub fn decompress(bytes: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    zstd::decode_all(bytes).map_err(|_| DecodeError::InvalidValue)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn state_code(state: GameState) -> u8 {
+    match state {
+        GameState::Init => 0,
+        GameState::OnGoing => 1,
+        GameState::Lost => 2,
+        GameState::Won => 3,
+    }
+}
+
+fn state_from_code(code: u8) -> Result<GameState, DecodeError> {
+    match code {
+        0 => Ok(GameState::Init),
+        1 => Ok(GameState::OnGoing),
+        2 => Ok(GameState::Lost),
+        3 => Ok(GameState::Won),
+        _ => Err(DecodeError::InvalidValue),
+    }
+}
+
+fn bool_from_code(code: u8) -> Result<bool, DecodeError> {
+    match code {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(DecodeError::InvalidValue),
+    }
+}
+
+fn adjacency_code(adjacency: Adjacency) -> u8 {
+    match adjacency {
+        Adjacency::King => 0,
+        Adjacency::Knight => 1,
+    }
+}
+
+fn adjacency_from_code(code: u8) -> Result<Adjacency, DecodeError> {
+    match code {
+        0 => Ok(Adjacency::King),
+        1 => Ok(Adjacency::Knight),
+        _ => Err(DecodeError::InvalidValue),
+    }
+}
+
+// 0 to 8 are opened counts
+fn square_code(square: Square) -> u8 {
+    match square {
+        Square::Opened(count) => count.clamp(0, 8) as u8,
+        Square::NotYetOpened => 9,
+        Square::Flag => 10,
+        Square::Mine => 11,
+    }
+}
+
+fn square_from_code(code: u8) -> Result<Square, DecodeError> {
+    match code {
+        0..=8 => Ok(Square::Opened(code as i8)),
+        9 => Ok(Square::NotYetOpened),
+        10 => Ok(Square::Flag),
+        11 => Ok(Square::Mine),
+        _ => Err(DecodeError::InvalidValue),
+    }
+}
+
+// -8 to 8 are opened counts shifted by 8, larger counts follow the other squares
+fn wide_square_code(square: Square) -> u8 {
+    match square {
+        Square::Opened(count @ 9..) => count as u8 + 11,
+        Square::Opened(count) => (count.max(-8) + 8) as u8,
+        Square::NotYetOpened => 17,
+        Square::Flag => 18,
+        Square::Mine => 19,
+    }
+}
+
+fn wide_square_from_code(code: u8) -> Result<Square, DecodeError> {
+    match code {
+        0..=16 => Ok(Square::Opened(code as i8 - 8)),
+        17 => Ok(Square::NotYetOpened),
+        18 => Ok(Square::Flag),
+        19 => Ok(Square::Mine),
+        20..=138 => Ok(Square::Opened((code - 11) as i8)),
+        _ => Err(DecodeError::InvalidValue),
+    }
+}
+
+// the number of pairs, then the row-major index of both cells of every pair
+fn write_wormholes(out: &mut Vec<u8>, cols: usize, wormholes: &[(Position, Position)]) {
+    write_varint(out, wormholes.len() as u64);
+    for &((ax, ay), (bx, by)) in wormholes {
+        write_varint(out, (ay * cols + ax) as u64);
+        write_varint(out, (by * cols + bx) as u64);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8], tag: u8) -> Result<Reader<'a>, DecodeError> {
+        let mut reader = Reader { bytes };
+        if reader.byte()? != tag {
+            return Err(DecodeError::WrongTag);
+        }
+        match reader.byte()? {
+            VERSION => Ok(reader),
+            version => Err(DecodeError::UnsupportedVersion(version)),
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < len {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (head, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(head)
+    }
+
+    fn varint(&mut self) -> Result<u64, DecodeError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecodeError::InvalidValue)
+    }
+
+    fn usize(&mut self) -> Result<usize, DecodeError> {
+        usize::try_from(self.varint()?).map_err(|_| DecodeError::InvalidValue)
+    }
+
+    fn wormholes(
+        &mut self,
+        cols: usize,
+        cells: usize,
+    ) -> Result<Vec<(Position, Position)>, DecodeError> {
+        let count = self.usize()?;
+        // every pair takes at least two bytes, don't trust the count for allocating
+        let mut wormholes = Vec::with_capacity(count.min(self.bytes.len() / 2));
+        let mut cell = || match self.usize()? {
+            index if index < cells => Ok((index % cols, index / cols)),
+            _ => Err(DecodeError::InvalidValue),
+        };
+        for _ in 0..count {
+            wormholes.push((cell()?, cell()?));
+        }
+        // every cell has at most one partner
+        let mut seen = HashSet::new();
+        if !wormholes
+            .iter()
+            .all(|&(a, b)| seen.insert(a) && seen.insert(b))
+        {
+            return Err(DecodeError::InvalidValue);
+        }
+        Ok(wormholes)
+    }
+
+    fn finish(&self) -> Result<(), DecodeError> {
+        match self.bytes.is_empty() {
+            true => Ok(()),
+            false => Err(DecodeError::TrailingBytes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    fn played_game() -> Board {
+        let mut board = Board::new(16, 30, 99);
+        board.init_mines((0, 0), Some(3));
+        while board.ongoing() {
+            let Some(mv) = board.hint() else { break };
+            board.play(mv).unwrap();
+        }
+        board
+    }
+
+    #[test]
+    fn test_view_round_trip() {
+        let mut board = Board::new(9, 9, 10);
+        assert_eq!(decode_view(&encode_view(&board.view())), Ok(board.view()));
+        board.init_mines((0, 0), Some(1));
+        board.flag((3, 1)).unwrap();
+        let view = board.view();
+        let bytes = encode_view(&view);
+        // header plus half a byte per cell
+        assert_eq!(bytes.len(), 11 + 41);
+        assert_eq!(decode_view(&bytes), Ok(view));
+
+        board.open((8, 0)).unwrap();
+        assert_eq!(decode_view(&encode_view(&board.view())), Ok(board.view()));
+
+        let config = GameConfig::default().with_variant(&crate::variant::AntiMines);
+        let mut board = Board::with_config(9, 9, 10, config);
+        board.init_mines((4, 4), Some(2));
+        let view = board.view();
+        let bytes = encode_view(&view);
+        // a byte per cell for the signed counts
+        assert_eq!(bytes.len(), 11 + 81);
+        assert_eq!(decode_view(&bytes), Ok(view));
+
+        let config = GameConfig::default().with_variant(&crate::variant::Wormholes);
+        let mut board = Board::with_config(9, 9, 10, config);
+        board.init_mines((4, 4), Some(2));
+        let view = board.view();
+        assert_eq!(view.wormholes.len(), 2);
+        assert_eq!(decode_view(&encode_view(&view)), Ok(view));
+    }
+
+    #[test]
+    fn test_region_round_trip() {
+        let board = played_game();
+        let rect = Rect::new((25, 10), 10, 10).clip(board.rows, board.cols);
+        let squares: Vec<Square> = board.iter_region(rect).map(|(_, s)| s).collect();
+        assert_eq!(squares.len(), 6 * 5);
+        let bytes = encode_region(rect, squares.iter().copied());
+        assert_eq!(decode_region(&bytes), Ok((rect, squares)));
+        assert_eq!(
+            decode_region(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::UnexpectedEnd)
+        );
+        assert_eq!(
+            decode_region(&encode_view(&board.view())),
+            Err(DecodeError::WrongTag)
+        );
+    }
+
+    #[test]
+    fn test_replay_round_trip() {
+        let mut replay = played_game().replay().unwrap();
+        replay.config.min_move_interval = Some(Duration::from_millis(40));
+        for action in replay.actions.iter_mut() {
+            action.elapsed = Duration::from_millis(action.elapsed.as_millis() as u64);
+        }
+        replay.config.win_condition = WinCondition::OpenAllSafe;
+        let bytes = encode_replay(&replay);
+        let decoded = decode_replay(&bytes).unwrap();
+        assert_eq!(decoded, replay);
+        assert_eq!(decoded.outcome().unwrap(), replay.outcome().unwrap());
+        let mut wrapped = replay.clone();
+        wrapped.config.wrap_edges = true;
+        wrapped.config.variant = Some("torus".to_string());
+        wrapped.config.adjacency = Adjacency::Knight;
+        wrapped.config.wormholes = vec![((0, 0), (29, 15)), ((3, 4), (5, 4))];
+        wrapped.config.liar = true;
+        wrapped.config.assisted_flags = true;
+        wrapped.config.deterministic = true;
+        assert_eq!(decode_replay(&encode_replay(&wrapped)), Ok(wrapped));
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let bytes = encode_replay(&played_game().replay().unwrap());
+        assert_eq!(decode_view(&bytes), Err(DecodeError::WrongTag));
+        assert_eq!(
+            decode_replay(&bytes[..bytes.len() - 1]),
+            Err(DecodeError::UnexpectedEnd)
+        );
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert_eq!(decode_replay(&extra), Err(DecodeError::TrailingBytes));
+        let mut future = bytes;
+        future[1] = VERSION + 1;
+        assert_eq!(
+            decode_replay(&future),
+            Err(DecodeError::UnsupportedVersion(VERSION + 1))
+        );
+        assert_eq!(decode_view(&[]), Err(DecodeError::UnexpectedEnd));
+
+        // sizes and counts are checked before allocating
+        let huge = [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x3f];
+        let view: Vec<u8> = [VIEW_TAG, VERSION]
+            .into_iter()
+            .chain(huge)
+            .chain([0, 0, 0, 0, 0, 0, 0, 0])
+            .collect();
+        assert_eq!(decode_view(&view), Err(DecodeError::InvalidValue));
+        let replay: Vec<u8> = [
+            REPLAY_TAG, VERSION, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        ]
+        .into_iter()
+        .chain(huge)
+        .collect();
+        assert_eq!(decode_replay(&replay), Err(DecodeError::UnexpectedEnd));
+
+        // boards that can't be replayed, one full of mines and one far too large
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        let mut full = encode_replay(&board.replay().unwrap());
+        full[4] = 81;
+        assert_eq!(decode_replay(&full), Err(DecodeError::InvalidValue));
+        let mut huge = vec![REPLAY_TAG, VERSION, 0xff, 0x7f, 0xff, 0x7f];
+        huge.extend_from_slice(&replay[4..]);
+        assert_eq!(decode_replay(&huge), Err(DecodeError::InvalidValue));
+        // a wormhole cell with two partners
+        let mut tangled = board.replay().unwrap();
+        tangled.config.wormholes = vec![((0, 0), (1, 0)), ((1, 0), (2, 0))];
+        assert_eq!(
+            decode_replay(&encode_replay(&tangled)),
+            Err(DecodeError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_share_code() {
+        for len in 0..8 {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 97 + 200) as u8).collect();
+            let code = to_share_code(&bytes);
+            assert!(code
+                .bytes()
+                .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_'));
+            assert_eq!(from_share_code(&code), Ok(bytes));
+        }
+        assert_eq!(to_share_code(b"Man"), "TWFu");
+        assert_eq!(from_share_code("TW+u"), Err(DecodeError::InvalidValue));
+        assert_eq!(from_share_code("TWFuT"), Err(DecodeError::UnexpectedEnd));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_size_against_json() {
+        let board = played_game();
+        let replay = board.replay().unwrap();
+        let view = board.view();
+        let (replay_json, replay_bytes) = (
+            serde_json::to_vec(&replay).unwrap().len(),
+            encode_replay(&replay).len(),
+        );
+        let (view_json, view_bytes) = (
+            serde_json::to_vec(&view).unwrap().len(),
+            encode_view(&view).len(),
+        );
+        println!("replay: {replay_bytes} bytes, {replay_json} as JSON");
+        println!("view: {view_bytes} bytes, {view_json} as JSON");
+        assert!(replay_bytes * 10 < replay_json);
+        assert!(view_bytes * 10 < view_json);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_compression_round_trip() {
+        let bytes = encode_view(&played_game().view());
+        assert_eq!(decompress(&compress(&bytes)), Ok(bytes));
+    }
+}
@@ -0,0 +1,201 @@
+//! The text commands of the terminal game, shared with the GUI console so a game
+//! can be replayed by pasting the same lines into either.
+
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::board::{Hint, Move, Position};
+
+static RE_OPEN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*((open|o)\s*)?\(?(?<x>\d+)\s*(,|\s)\s*(?<y>\d+)\)?\s*$").unwrap()
+});
+static RE_FLAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(flag|f)\s*\(?(?<x>\d+)\s*(,|\s)\s*(?<y>\d+)\)?\s*$").unwrap()
+});
+static RE_CHORD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(chord|c)\s*\(?(?<x>\d+)\s*(,|\s)\s*(?<y>\d+)\)?\s*$").unwrap()
+});
+static RE_RESTART: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(restart|r)(?<same>\s+same)?\s*$").unwrap());
+static RE_HINT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*(hint|h)\s*$").unwrap());
+static RE_UNDO: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*(undo|u)\s*$").unwrap());
+// paths keep their case, unlike the rest of the line
+static RE_FILE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*(?i:(?<cmd>save|load))\s+(?<path>.*\S)\s*$").unwrap());
+static RE_SEED: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*seed(\s+(?<seed>\d+))?\s*$").unwrap());
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameCommand {
+    /// Opens a cell, the first one also places the mines.
+    Open(Position),
+    Flag(Position),
+    /// Opens the closed neighbors of a number with as many flags around it.
+    Chord(Position),
+    /// A new board, or the same mines again with `same`.
+    Restart {
+        same: bool,
+    },
+    Hint,
+    /// Takes back the last move.
+    Undo,
+    /// Shows the seed of the board, or starts a new board that uses the given seed.
+    Seed(Option<u64>),
+    /// Writes the game to a file to continue it later.
+    Save(String),
+    /// Continues a game written by `Save`.
+    Load(String),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CommandError {
+    Unknown,
+    /// A number does not fit its type.
+    InvalidNumber,
+    /// A command other than open, flag or chord in a list of moves.
+    NotAMove,
+}
+
+impl FromStr for GameCommand {
+    type Err = CommandError;
+
+    fn from_str(line: &str) -> Result<GameCommand, CommandError> {
+        if let Some(caps) = RE_FILE.captures(line) {
+            let path = caps["path"].to_string();
+            return Ok(match caps["cmd"].to_lowercase().as_str() {
+                "save" => GameCommand::Save(path),
+                _ => GameCommand::Load(path),
+            });
+        }
+        let line = line.to_lowercase();
+        let position = |caps: &regex::Captures<'_>| match (
+            caps["x"].parse::<usize>(),
+            caps["y"].parse::<usize>(),
+        ) {
+            (Ok(x), Ok(y)) => Ok((x, y)),
+            _ => Err(CommandError::InvalidNumber),
+        };
+        if let Some(caps) = RE_RESTART.captures(&line) {
+            Ok(GameCommand::Restart {
+                same: caps.name("same").is_some(),
+            })
+        } else if RE_HINT.is_match(&line) {
+            Ok(GameCommand::Hint)
+        } else if RE_UNDO.is_match(&line) {
+            Ok(GameCommand::Undo)
+        } else if let Some(caps) = RE_SEED.captures(&line) {
+            match caps.name("seed").map(|seed| seed.as_str().parse()) {
+                None => Ok(GameCommand::Seed(None)),
+                Some(Ok(seed)) => Ok(GameCommand::Seed(Some(seed))),
+                Some(Err(_)) => Err(CommandError::InvalidNumber),
+            }
+        } else if let Some(caps) = RE_FLAG.captures(&line) {
+            position(&caps).map(GameCommand::Flag)
+        } else if let Some(caps) = RE_CHORD.captures(&line) {
+            position(&caps).map(GameCommand::Chord)
+        } else if let Some(caps) = RE_OPEN.captures(&line) {
+            position(&caps).map(GameCommand::Open)
+        } else {
+            Err(CommandError::Unknown)
+        }
+    }
+}
+
+/// The moves of a move file, one open, flag or chord command per line with its
+/// line number. Blank lines and lines starting with `#` are skipped.
+pub fn parse_moves(source: &str) -> Result<Vec<(usize, Move)>, (usize, CommandError)> {
+    let mut moves = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let mv = match trimmed.parse::<GameCommand>() {
+            Ok(GameCommand::Open(pos)) => Move::Open(pos),
+            Ok(GameCommand::Flag(pos)) => Move::Flag(pos),
+            Ok(GameCommand::Chord(pos)) => Move::Chord(pos),
+            Ok(_) => return Err((i + 1, CommandError::NotAMove)),
+            Err(e) => return Err((i + 1, e)),
+        };
+        moves.push((i + 1, mv));
+    }
+    Ok(moves)
+}
+
+/// Describes a hint for the player.
+pub fn hint_message(hint: Option<&Hint>) -> String {
+    match hint {
+        Some(Hint::Number((x, y))) => format!("Hint: look at the number at ({x},{y})."),
+        Some(Hint::Region(cells)) => {
+            let cells: Vec<String> = cells.iter().map(|(x, y)| format!("({x},{y})")).collect();
+            format!("Hint: one of {} is safe.", cells.join(" "))
+        }
+        Some(Hint::Move(Move::Open((x, y)))) => format!("Hint: open ({x},{y})."),
+        Some(Hint::Move(Move::Flag((x, y)))) => {
+            format!("Hint: ({x},{y}) is safe, remove its flag.")
+        }
+        Some(Hint::Move(Move::Chord((x, y)))) => format!("Hint: chord ({x},{y})."),
+        None => "No hint available, open any cell to start.".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        let parse = |line: &str| line.parse::<GameCommand>();
+        assert_eq!(parse("3,4"), Ok(GameCommand::Open((3, 4))));
+        assert_eq!(parse("(3, 4)\n"), Ok(GameCommand::Open((3, 4))));
+        assert_eq!(parse("open 3 4"), Ok(GameCommand::Open((3, 4))));
+        assert_eq!(parse("F 5 5"), Ok(GameCommand::Flag((5, 5))));
+        assert_eq!(parse("flag(5,5)"), Ok(GameCommand::Flag((5, 5))));
+        assert_eq!(parse("c 2 0"), Ok(GameCommand::Chord((2, 0))));
+        assert_eq!(parse("chord (2,0)"), Ok(GameCommand::Chord((2, 0))));
+        assert_eq!(parse("r"), Ok(GameCommand::Restart { same: false }));
+        assert_eq!(
+            parse("restart same"),
+            Ok(GameCommand::Restart { same: true })
+        );
+        assert_eq!(parse("hint"), Ok(GameCommand::Hint));
+        assert_eq!(parse("u"), Ok(GameCommand::Undo));
+        assert_eq!(parse(" undo\n"), Ok(GameCommand::Undo));
+        assert_eq!(parse("seed"), Ok(GameCommand::Seed(None)));
+        assert_eq!(parse("seed 42"), Ok(GameCommand::Seed(Some(42))));
+        assert_eq!(
+            parse("save Games/Expert 1.game\n"),
+            Ok(GameCommand::Save("Games/Expert 1.game".to_string()))
+        );
+        assert_eq!(
+            parse("LOAD a.game"),
+            Ok(GameCommand::Load("a.game".to_string()))
+        );
+        assert_eq!(parse("save"), Err(CommandError::Unknown));
+
+        assert_eq!(parse("open"), Err(CommandError::Unknown));
+        assert_eq!(parse("jump 1 2"), Err(CommandError::Unknown));
+        assert_eq!(
+            parse("99999999999999999999 1"),
+            Err(CommandError::InvalidNumber)
+        );
+    }
+
+    #[test]
+    fn test_parse_moves() {
+        let source = "# first click\n4,4\n\nflag 3 1\n  chord (2,1)\n";
+        assert_eq!(
+            parse_moves(source),
+            Ok(vec![
+                (2, Move::Open((4, 4))),
+                (4, Move::Flag((3, 1))),
+                (5, Move::Chord((2, 1)))
+            ])
+        );
+        assert_eq!(parse_moves(""), Ok(Vec::new()));
+        assert_eq!(parse_moves("1,1\nundo"), Err((2, CommandError::NotAMove)));
+        assert_eq!(parse_moves("jump 1 2"), Err((1, CommandError::Unknown)));
+    }
+}
@@ -1,6 +1,89 @@
-use clap::Parser;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::board::{Adjacency, HintLevel, Position};
+use crate::render::{Glyphs, Style};
+use crate::variant;
+
+/// Game rules enforced by the engine, shared by every frontend.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct GameConfig {
+    /// Minimum time between two moves, moves made faster are rejected.
+    pub min_move_interval: Option<Duration>,
+    /// Cells within this many steps of the first click never hold a mine,
+    /// 0 only keeps the clicked cell itself free.
+    pub safe_radius: usize,
+    pub win_condition: WinCondition,
+    /// Cells on opposite edges touch, so the board has no edges or corners.
+    pub wrap_edges: bool,
+    /// Name of the registered `Variant` placing the mines, the classic uniform
+    /// placement if `None` or not registered.
+    pub variant: Option<String>,
+    /// Which cells count towards each other's numbers, the surrounding ones or
+    /// those a knight's move away.
+    pub adjacency: Adjacency,
+    /// Pairs of cells that touch each other's neighbors.
+    pub wormholes: Vec<(Position, Position)>,
+    /// Every number is one more or one less than the count of its neighbors.
+    pub liar: bool,
+    /// Level of the first hint after a move, asking again gives away more.
+    pub first_hint: HintLevel,
+    /// Flags on safe cells are rejected with `FlagError::NotAMine`, an assisted
+    /// mode for learning. Results of such games say they were assisted.
+    pub assisted_flags: bool,
+    /// Cells to open in treasure games, placed by the variant along with the
+    /// mines and shown to the player. See `objective::Treasures`.
+    pub treasures: Vec<Position>,
+    /// Reproducible games: without a seed `DETERMINISTIC_SEED` is used, the clock
+    /// is logical and moves are not rate limited, so saves, replays and summaries
+    /// are byte-identical on every run and platform.
+    pub deterministic: bool,
+}
+
+/// Seed of deterministic games that are not given one.
+pub const DETERMINISTIC_SEED: u64 = 0;
+
+/// When a game counts as won.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WinCondition {
+    /// Every mine is flagged and every other cell opened.
+    #[default]
+    FlagAllMines,
+    /// Every safe cell is opened, flags don't matter. The usual rule elsewhere.
+    OpenAllSafe,
+}
+
+/// The standard board sizes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Difficulty {
+    /// 9x9 with 10 mines
+    #[default]
+    Beginner,
+    /// 16x16 with 40 mines
+    Intermediate,
+    /// 16 rows and 30 columns with 99 mines
+    Expert,
+}
+
+impl Difficulty {
+    /// Rows, columns and mines of the board.
+    pub fn dimensions(self) -> (usize, usize, usize) {
+        match self {
+            Difficulty::Beginner => (9, 9, 10),
+            Difficulty::Intermediate => (16, 16, 40),
+            Difficulty::Expert => (16, 30, 99),
+        }
+    }
+}
 
 /// Generate minesweeper boards
+#[doc(hidden)]
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Args {
@@ -8,17 +91,249 @@ pub struct Args {
     #[arg(short, long, default_value=None)]
     seed: Option<u64>,
 
-    /// Number of board rows
-    #[arg(short, long, default_value = "9")]
-    rows: usize,
+    /// Board size and mines, `--rows`, `--cols` and `--mines` override it
+    #[arg(short, long, value_enum, default_value_t = Difficulty::Beginner)]
+    difficulty: Difficulty,
+
+    /// Number of board rows [default: from the difficulty]
+    #[arg(short, long, default_value=None)]
+    rows: Option<usize>,
+
+    /// Number of board cols [default: from the difficulty]
+    #[arg(short, long, default_value=None)]
+    cols: Option<usize>,
+
+    /// Number of mines [default: from the difficulty]
+    #[arg(short, long, default_value=None)]
+    mines: Option<usize>,
+
+    /// Only generate boards that can be solved without guessing
+    #[arg(long)]
+    no_guess: bool,
+
+    /// Minimum number of milliseconds between two moves
+    #[arg(long, default_value=None)]
+    min_move_interval: Option<u64>,
+
+    /// Keep every cell within this distance of the first click free of mines
+    #[arg(long, default_value = "0")]
+    safe_radius: usize,
+
+    /// When the game is won
+    #[arg(long, value_enum, default_value_t = WinCondition::FlagAllMines)]
+    win_condition: WinCondition,
+
+    /// Let the board wrap around, so cells on opposite edges are neighbors
+    #[arg(long)]
+    wrap_edges: bool,
+
+    /// Game variant, see `--variant list` for the available ones
+    #[arg(long, default_value=None)]
+    variant: Option<String>,
+
+    /// Guarantee the first click opens an area, like classic Windows minesweeper
+    #[arg(long)]
+    open_start: bool,
+
+    /// Copy the result and final board to the clipboard when the game ends
+    #[arg(long)]
+    copy: bool,
+
+    /// Assisted mode for learning: flags on safe cells are rejected
+    #[arg(long)]
+    assisted_flags: bool,
+
+    /// How much the first hint after a move gives away, asking again gives away more
+    #[arg(long, value_enum, default_value_t = HintLevel::Cell)]
+    first_hint: HintLevel,
+
+    /// Ask before opening a cell that is a guess while another cell is provably safe
+    #[arg(long)]
+    warn_guesses: bool,
+
+    /// Print a spoiler free emoji summary when the game ends
+    #[arg(long)]
+    share: bool,
+
+    /// Play the mine layout of a Minesweeper Board File (.mbf) instead of a generated one
+    #[arg(long, default_value=None)]
+    load_mbf: Option<PathBuf>,
+
+    /// Save the mine layout as a Minesweeper Board File (.mbf) when the game ends
+    #[arg(long, default_value=None)]
+    save_mbf: Option<PathBuf>,
+
+    /// Continue a game written by the `save` command
+    #[arg(long, default_value=None)]
+    load: Option<PathBuf>,
+
+    /// Play back a game written by the `save` command or a file holding its
+    /// replay code, printing the board after every move
+    #[arg(long, default_value=None)]
+    replay: Option<PathBuf>,
+
+    /// Play the moves in a file, `-` for stdin, without prompting: one open, flag
+    /// or chord command per line as typed in the game. Exits with 0 when the
+    /// game is won, 1 when it is lost, 2 when it is unfinished and 3 for an
+    /// invalid file
+    #[arg(long, default_value=None)]
+    moves: Option<PathBuf>,
+
+    /// Let the solver play, printing every move with the reason for it
+    #[arg(long)]
+    solve: bool,
+
+    /// Milliseconds to wait between the moves played back with `--replay` or
+    /// made with `--solve`
+    #[arg(long, default_value=None)]
+    delay: Option<u64>,
+
+    /// Draw the board with letters instead of emoji
+    #[arg(long)]
+    ascii: bool,
+
+    /// TOML file with the glyphs of the board, see the `render` module
+    #[cfg(feature = "themes")]
+    #[arg(long, default_value=None)]
+    glyphs: Option<PathBuf>,
+
+    /// Print the board without colors, also the default when not printing to a terminal
+    #[arg(long)]
+    no_color: bool,
 
-    /// Number of board cols
-    #[arg(short, long, default_value = "9")]
-    cols: usize,
+    /// Number the rows and columns of the printed board
+    #[arg(long)]
+    headers: bool,
 
-    /// Number of mines
-    #[arg(short, long, default_value = "10")]
-    mines: usize,
+    /// Reproducible games: a fixed seed unless one is given and a clock that
+    /// advances one second per move instead of following the wall clock
+    #[arg(long)]
+    deterministic: bool,
+
+    /// Rhai script reacting to the game, see the `scripting` module
+    #[cfg(feature = "scripting")]
+    #[arg(long, default_value=None)]
+    script: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[doc(hidden)]
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Check recorded games for superhuman timing and luck, printing the report
+    /// format of the `anticheat` module for each
+    Audit {
+        /// Saved games or files holding their replay code
+        #[arg(required = true)]
+        replays: Vec<PathBuf>,
+
+        /// Milliseconds under which a move after new cells were revealed is instant
+        #[arg(long, default_value = "10")]
+        reaction: u64,
+
+        /// Instant moves in a row that get a game flagged
+        #[arg(long, default_value = "3")]
+        chain: usize,
+
+        /// Guesses it takes before a perfect guess record is judged
+        #[arg(long, default_value = "5")]
+        min_guesses: usize,
+
+        /// A perfect guess record less likely than this gets a game flagged
+        #[arg(long, default_value = "0.01")]
+        luck: f64,
+    },
+    /// Time board generation, flood fills and the solver on the configured board
+    Bench {
+        /// Number of boards to generate
+        #[arg(short, long, default_value = "100")]
+        boards: usize,
+    },
+    /// Compare two boards, each a saved game, an MBF file or a text layout
+    Diff {
+        /// File with the first board
+        a: PathBuf,
+
+        /// File with the second board
+        b: PathBuf,
+    },
+    /// Practice a drill, or list the drills without a name
+    Drill {
+        /// Name of the drill
+        name: Option<String>,
+    },
+    /// Create a signing key for content you hand out, printing its public key
+    #[cfg(feature = "signing")]
+    Keygen {
+        /// File to write the secret key to, keep it private
+        secret: PathBuf,
+    },
+    /// Play or list the puzzles of a pack, see the `pack` module
+    #[command(subcommand)]
+    Pack(PackCommand),
+    /// Exhaustively play tiny boards and check the results against known totals
+    Selftest,
+    /// Sign a pack or other file, writing the signature next to it as <file>.sig
+    #[cfg(feature = "signing")]
+    Sign {
+        /// The file to sign
+        file: PathBuf,
+
+        /// File with the secret key written by `keygen`
+        #[arg(long)]
+        secret: PathBuf,
+    },
+    /// Check that mine layouts are uniformly random for the configured board
+    TestRandomness {
+        /// Number of layouts to generate
+        #[arg(short, long, default_value = "20000")]
+        samples: usize,
+
+        /// Column of the first click
+        #[arg(short, long, default_value = "0")]
+        x: usize,
+
+        /// Row of the first click
+        #[arg(short, long, default_value = "0")]
+        y: usize,
+    },
+    /// Check the signature <file>.sig of a file, exiting with 1 if it doesn't hold
+    #[cfg(feature = "signing")]
+    Verify {
+        /// The signed file
+        file: PathBuf,
+
+        /// Public key of the organizer, as printed by `keygen`
+        #[arg(long)]
+        key: String,
+    },
+}
+
+#[doc(hidden)]
+#[derive(Subcommand, Debug)]
+pub enum PackCommand {
+    /// Play a puzzle of the pack with the rules of the other options
+    Play {
+        /// The pack file
+        file: PathBuf,
+
+        /// Number of the puzzle in the recommended order, starting at 1
+        #[arg(short, long, default_value = "1")]
+        index: usize,
+
+        /// Public key of the organizer, the pack is only played if its
+        /// signature <file>.sig holds
+        #[cfg(feature = "signing")]
+        #[arg(long)]
+        key: Option<String>,
+    },
+    /// List the puzzles of the pack in the recommended order
+    List {
+        /// The pack file
+        file: PathBuf,
+    },
 }
 
 impl Args {
@@ -26,12 +341,128 @@ impl Args {
         self.seed
     }
     pub fn get_rows(&self) -> usize {
-        self.rows
+        self.rows.unwrap_or(self.difficulty.dimensions().0)
     }
     pub fn get_cols(&self) -> usize {
-        self.cols
+        self.cols.unwrap_or(self.difficulty.dimensions().1)
     }
     pub fn get_mines(&self) -> usize {
-        self.mines
+        self.mines.unwrap_or(self.difficulty.dimensions().2)
+    }
+    pub fn get_no_guess(&self) -> bool {
+        self.no_guess
+    }
+    pub fn get_game_config(&self) -> GameConfig {
+        let config = GameConfig {
+            min_move_interval: self.min_move_interval.map(Duration::from_millis),
+            safe_radius: if self.open_start {
+                self.safe_radius.max(1)
+            } else {
+                self.safe_radius
+            },
+            win_condition: self.win_condition,
+            wrap_edges: self.wrap_edges,
+            variant: None,
+            adjacency: Adjacency::King,
+            wormholes: Vec::new(),
+            liar: false,
+            first_hint: self.first_hint,
+            assisted_flags: self.assisted_flags,
+            treasures: Vec::new(),
+            deterministic: self.deterministic,
+        };
+        // unknown names are reported by the frontend
+        match self.variant.as_deref().map(variant::find) {
+            Some(Ok(variant)) => config.with_variant(variant),
+            _ => config,
+        }
+    }
+    pub fn get_variant(&self) -> Option<&str> {
+        self.variant.as_deref()
+    }
+    pub fn get_copy(&self) -> bool {
+        self.copy
+    }
+    pub fn get_share(&self) -> bool {
+        self.share
+    }
+    pub fn get_warn_guesses(&self) -> bool {
+        self.warn_guesses
+    }
+    pub fn get_load_mbf(&self) -> Option<&Path> {
+        self.load_mbf.as_deref()
+    }
+    pub fn get_save_mbf(&self) -> Option<&Path> {
+        self.save_mbf.as_deref()
+    }
+    pub fn get_load(&self) -> Option<&Path> {
+        self.load.as_deref()
+    }
+    pub fn get_replay(&self) -> Option<&Path> {
+        self.replay.as_deref()
+    }
+    pub fn get_moves(&self) -> Option<&Path> {
+        self.moves.as_deref()
+    }
+    pub fn get_solve(&self) -> bool {
+        self.solve
+    }
+    pub fn get_delay(&self) -> Option<Duration> {
+        self.delay.map(Duration::from_millis)
+    }
+    pub fn get_style(&self) -> Style {
+        Style {
+            color: !self.no_color && std::io::stdout().is_terminal(),
+            glyphs: if self.ascii {
+                Glyphs::ASCII
+            } else {
+                Glyphs::EMOJI
+            },
+            headers: self.headers,
+        }
+    }
+    #[cfg(feature = "themes")]
+    pub fn get_glyphs(&self) -> Option<&Path> {
+        self.glyphs.as_deref()
+    }
+    #[cfg(feature = "scripting")]
+    pub fn get_script(&self) -> Option<&Path> {
+        self.script.as_deref()
+    }
+    pub fn get_command(&self) -> Option<&Command> {
+        self.command.as_ref()
+    }
+    /// The organizer key the pack of `pack play` has to be signed with.
+    #[cfg(feature = "signing")]
+    pub fn get_pack_key(&self) -> Option<&str> {
+        match &self.command {
+            Some(Command::Pack(PackCommand::Play { key, .. })) => key.as_deref(),
+            _ => None,
+        }
+    }
+    /// The pack file and puzzle number of `pack play`, which runs the regular game.
+    pub fn get_pack_puzzle(&self) -> Option<(&Path, usize)> {
+        match &self.command {
+            Some(Command::Pack(PackCommand::Play { file, index, .. })) => Some((file, *index)),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_difficulty() {
+        let parse = |args: &[&str]| {
+            let args = Args::try_parse_from(["minesweeper"].iter().chain(args)).unwrap();
+            (args.get_rows(), args.get_cols(), args.get_mines())
+        };
+        assert_eq!(parse(&[]), (9, 9, 10));
+        assert_eq!(parse(&["--difficulty", "expert"]), (16, 30, 99));
+        assert_eq!(parse(&["-d", "intermediate", "-m", "50"]), (16, 16, 50));
+        assert_eq!(parse(&["--rows", "5"]), (5, 9, 10));
+        assert!(Args::try_parse_from(["minesweeper", "-d", "hard"]).is_err());
     }
 }
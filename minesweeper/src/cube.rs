@@ -0,0 +1,97 @@
+//! Three dimensional boards. The layers are stored below each other on a regular
+//! board, so every rule works on them unchanged; only the neighbors differ.
+
+use crate::board::{Board, CubeGrid, Position, Square};
+use crate::config::GameConfig;
+
+/// A board of `rows` x `cols` x `layers` cubes, each touching the 26 around it.
+pub type Board3D = Board<CubeGrid>;
+
+/// Cell coordinates as `(x, y, z)`, i.e. `(column, row, layer)`.
+pub type Position3D = (usize, usize, usize);
+
+impl Board3D {
+    pub fn with_layers(
+        rows: usize,
+        cols: usize,
+        layers: usize,
+        nr_mines: usize,
+        config: GameConfig,
+    ) -> Board3D {
+        Board::with_topology(CubeGrid { layers }, rows * layers, cols, nr_mines, config)
+    }
+
+    pub fn layers(&self) -> usize {
+        self.topology().layers
+    }
+
+    /// Rows of a single layer.
+    pub fn layer_rows(&self) -> usize {
+        self.topology().layer_rows(self.rows)
+    }
+
+    /// The position the moves of the board take for a cell.
+    pub fn position_3d(&self, (x, y, z): Position3D) -> Position {
+        (x, z * self.layer_rows() + y)
+    }
+
+    pub fn layer_position(&self, (x, y): Position) -> Position3D {
+        (x, y % self.layer_rows(), y / self.layer_rows())
+    }
+
+    /// `get_board_state` split into layers, indexed `[layer][row][col]`.
+    pub fn get_layer_states(&self) -> Vec<Vec<Vec<Square>>> {
+        self.get_board_state()
+            .chunks(self.layer_rows())
+            .map(<[_]>::to_vec)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{GameState, Mask};
+
+    #[test]
+    fn test_cube() {
+        let mut board = Board3D::with_layers(3, 3, 3, 1, GameConfig::default());
+        assert_eq!((board.rows, board.layers(), board.layer_rows()), (9, 3, 3));
+        let center = board.position_3d((1, 1, 1));
+        assert_eq!(center, (1, 4));
+        assert_eq!(board.layer_position(center), (1, 1, 1));
+        assert_eq!(board.iter_neighbors(center).count(), 26);
+
+        // every cell touches the center, so nothing cascades
+        board.mines = Some(Mask::from_positions(board.grid(), [center]));
+        board.init_mines(board.position_3d((0, 0, 0)), None);
+        assert_eq!(board.open_fields.len(), 1);
+        let layers = board.get_layer_states();
+        assert_eq!(layers.len(), 3);
+        assert_eq!(layers[0][0][0], Square::Opened(1));
+        assert_eq!(layers[2][2], vec![Square::NotYetOpened; 3]);
+
+        for z in 0..3 {
+            for y in 0..3 {
+                for x in 0..3 {
+                    let pos = board.position_3d((x, y, z));
+                    if pos != center && !board.open_fields.contains(pos) {
+                        board.open(pos).unwrap();
+                    }
+                }
+            }
+        }
+        assert_eq!(board.flag(center).unwrap(), GameState::Won);
+    }
+
+    #[test]
+    fn test_cube_cascade() {
+        let mut board = Board3D::with_layers(4, 4, 3, 1, GameConfig::default());
+        let mine = board.position_3d((3, 3, 2));
+        board.mines = Some(Mask::from_positions(board.grid(), [mine]));
+        board.init_mines((0, 0), None);
+        // only the seven cubes around the mine in its corner show a number
+        assert_eq!(board.open_fields.len(), 4 * 4 * 3 - 1);
+        assert_eq!(board.counts.iter().filter(|&&c| c > 0).count(), 7);
+    }
+}
@@ -0,0 +1,316 @@
+//! Practice drills: a number of short rounds training one skill, like clearing
+//! small boards fast or spotting deductions. Every round is a fresh board whose
+//! first click is already made, frontends play it and pass it to
+//! `Session::update` after every move.
+
+use std::time::Duration;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::board::{Board, GameState, Move, Position, Square};
+use crate::config::GameConfig;
+use crate::solver::Solver;
+
+/// What a round asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Goal {
+    /// Win the board without hints, within the time limit if any.
+    Clear(Option<Duration>),
+    /// Open a provably safe cell or flag a provable mine. The round ends with
+    /// the first move.
+    Deduce,
+    /// Open every cell the opening proves safe and flag every mine it proves,
+    /// within the time limit. The first click is anywhere on the board.
+    Frontier(Duration),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Drill {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub rows: usize,
+    pub cols: usize,
+    pub nr_mines: usize,
+    pub goal: Goal,
+    /// Rounds to pass to complete the drill.
+    pub rounds: usize,
+}
+
+pub const DRILLS: [Drill; 4] = [
+    Drill {
+        name: "openings",
+        description: "Clear 10 small boards in under 3 seconds each",
+        rows: 5,
+        cols: 5,
+        nr_mines: 3,
+        goal: Goal::Clear(Some(Duration::from_secs(3))),
+        rounds: 10,
+    },
+    Drill {
+        name: "patterns",
+        description: "Find a safe cell or a mine by deduction on 20 boards",
+        rows: 9,
+        cols: 9,
+        nr_mines: 10,
+        goal: Goal::Deduce,
+        rounds: 20,
+    },
+    Drill {
+        name: "no-hints",
+        description: "Win 5 intermediate boards that need no guessing, without hints",
+        rows: 16,
+        cols: 16,
+        nr_mines: 40,
+        goal: Goal::Clear(None),
+        rounds: 5,
+    },
+    Drill {
+        name: "frontier",
+        description:
+            "Resolve what the opening proves on 10 expert boards, in under 15 seconds each",
+        rows: 16,
+        cols: 30,
+        nr_mines: 99,
+        goal: Goal::Frontier(Duration::from_secs(15)),
+        rounds: 10,
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Drill> {
+    DRILLS.iter().find(|drill| drill.name == name)
+}
+
+// boards generated looking for one with a deduction before taking any
+const MAX_TRIES: usize = 100;
+
+/// Progress through a drill. Only kept for as long as it is played.
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub drill: &'static Drill,
+    pub passed: usize,
+    pub failed: usize,
+    /// Time of the fastest passed round.
+    pub best: Option<Duration>,
+    // seed of the next generated board, random if `None`
+    next_seed: Option<u64>,
+    // seed of the board being played
+    round_seed: Option<u64>,
+    // what the board proves at the start of a deduction or frontier round
+    safe: Vec<Position>,
+    mines: Vec<Position>,
+    // the round result is reported once
+    judged: bool,
+}
+
+impl Session {
+    /// A session whose boards come from `seed`, `seed + 1` and so on, or
+    /// random seeds.
+    pub fn new(drill: &'static Drill, seed: Option<u64>) -> Session {
+        Session {
+            drill,
+            passed: 0,
+            failed: 0,
+            best: None,
+            next_seed: seed,
+            round_seed: None,
+            safe: Vec::new(),
+            mines: Vec::new(),
+            judged: false,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.passed >= self.drill.rounds
+    }
+
+    /// The board of the next round, opened in the center, or anywhere for
+    /// frontier rounds.
+    pub fn next_board(&mut self) -> Board {
+        let drill = self.drill;
+        let config = GameConfig {
+            safe_radius: 1,
+            ..GameConfig::default()
+        };
+        let mut board = Board::with_config(drill.rows, drill.cols, drill.nr_mines, config.clone());
+        for _ in 0..MAX_TRIES {
+            board = Board::with_config(drill.rows, drill.cols, drill.nr_mines, config.clone());
+            let seed = self.next_seed.unwrap_or_else(rand::random);
+            self.next_seed = self.next_seed.map(|seed| seed.wrapping_add(1));
+            let start = match drill.goal {
+                Goal::Frontier(_) => {
+                    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+                    (
+                        rng.random_range(0..drill.cols),
+                        rng.random_range(0..drill.rows),
+                    )
+                }
+                Goal::Clear(_) | Goal::Deduce => (drill.cols / 2, drill.rows / 2),
+            };
+            match drill.goal {
+                Goal::Clear(_) => {
+                    if board.init_mines_no_guess(start, Some(seed)).is_err() {
+                        board.init_mines(start, Some(seed));
+                    }
+                }
+                Goal::Deduce | Goal::Frontier(_) => board.init_mines(start, Some(seed)),
+            }
+            if !board.ongoing() {
+                continue;
+            }
+            if matches!(drill.goal, Goal::Deduce | Goal::Frontier(_)) {
+                let view = board.view();
+                let solver = Solver::new(&view);
+                let closed = |&pos: &Position| view.get(pos) == Some(Square::NotYetOpened);
+                self.safe = solver.safe_cells().into_iter().filter(closed).collect();
+                self.mines = solver.mine_cells().into_iter().filter(closed).collect();
+                if self.safe.is_empty() && self.mines.is_empty() {
+                    continue;
+                }
+            }
+            break;
+        }
+        self.round_seed = board.seed();
+        self.judged = false;
+        board
+    }
+
+    /// Whether `board` is the round being played, and not one the player
+    /// started outside of the drill.
+    pub fn owns(&self, board: &Board) -> bool {
+        (board.rows, board.cols, board.nr_mines)
+            == (self.drill.rows, self.drill.cols, self.drill.nr_mines)
+            && board.seed().is_some()
+            && board.seed() == self.round_seed
+    }
+
+    /// Judges the round once it is over, `Some(true)` when it was passed. Call
+    /// after every move, and regularly for timed rounds. The result is only
+    /// reported once.
+    pub fn update(&mut self, board: &Board) -> Option<bool> {
+        if self.judged || !self.owns(board) {
+            return None;
+        }
+        let passed = match self.drill.goal {
+            Goal::Clear(limit) => match board.state {
+                GameState::Won => {
+                    limit.is_none_or(|limit| board.elapsed() <= limit)
+                        && board.hints_used.iter().all(|&n| n == 0)
+                }
+                GameState::Lost => false,
+                GameState::Init | GameState::OnGoing => return None,
+            },
+            Goal::Deduce => {
+                // the first action is the click that started the round
+                let replay = board.replay()?;
+                match replay.actions.get(1)?.mv {
+                    Move::Open(pos) => self.safe.contains(&pos),
+                    Move::Flag(pos) => self.mines.contains(&pos),
+                    Move::Chord(_) => board.last_changed().iter().all(|p| self.safe.contains(p)),
+                }
+            }
+            Goal::Frontier(limit) => {
+                let resolved = self.safe.iter().all(|&p| board.open_fields.contains(p))
+                    && self.mines.iter().all(|&p| board.flagged_fields.contains(p));
+                if board.lost() || board.elapsed() > limit {
+                    false
+                } else if resolved {
+                    true
+                } else {
+                    return None;
+                }
+            }
+        };
+        self.judged = true;
+        if passed {
+            self.passed += 1;
+            let time = board.elapsed();
+            self.best = Some(self.best.map_or(time, |best| best.min(time)));
+        } else {
+            self.failed += 1;
+        }
+        Some(passed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deduce_round() {
+        let mut session = Session::new(find("patterns").unwrap(), Some(1));
+        let mut board = session.next_board();
+        assert!(board.ongoing());
+        assert!(session.owns(&board));
+        assert_eq!(session.update(&board), None);
+
+        board.open(session.safe[0]).unwrap();
+        assert_eq!(session.update(&board), Some(true));
+        assert_eq!(session.update(&board), None);
+        assert_eq!((session.passed, session.failed), (1, 0));
+
+        // flagging a provably safe cell fails the round
+        let mut board = session.next_board();
+        board.flag(session.safe[0]).unwrap();
+        assert_eq!(session.update(&board), Some(false));
+        assert_eq!(session.failed, 1);
+        assert!(!session.is_complete());
+        assert!(!session.owns(&Board::new(9, 9, 10)));
+    }
+
+    #[test]
+    fn test_frontier_round() {
+        let mut session = Session::new(find("frontier").unwrap(), Some(1));
+        let mut board = session.next_board();
+        board.config.min_move_interval = None;
+        let (safe, mines) = (session.safe.clone(), session.mines.clone());
+        assert!(!safe.is_empty() || !mines.is_empty());
+        assert_eq!(session.update(&board), None);
+        for &pos in &mines {
+            board.flag(pos).unwrap();
+        }
+        for &pos in &safe {
+            let _ = board.open(pos);
+        }
+        assert_eq!(session.update(&board), Some(true));
+        assert!(session.best.is_some());
+
+        // starts differ between rounds
+        let starts: Vec<_> = (0..5)
+            .map(|_| {
+                let board = session.next_board();
+                board.replay().unwrap().actions[0].mv
+            })
+            .collect();
+        assert!(starts.iter().any(|&mv| mv != starts[0]));
+    }
+
+    #[test]
+    fn test_clear_round() {
+        let mut session = Session::new(find("openings").unwrap(), Some(1));
+        let mut board = session.next_board();
+        board.config.min_move_interval = None;
+        while board.ongoing() {
+            let solver = Solver::new(&board.view());
+            for pos in solver.mine_cells() {
+                if !board.flagged_fields.contains(pos) {
+                    board.flag(pos).unwrap();
+                }
+            }
+            for pos in solver.safe_cells() {
+                let _ = board.open(pos);
+            }
+        }
+        assert_eq!(board.state, GameState::Won);
+        assert_eq!(session.update(&board), Some(true));
+
+        // a hint fails the round even when it is won
+        let mut board = session.next_board();
+        assert!(board.request_hint().is_some());
+        let mine = board.mines.as_ref().unwrap().iter().next().unwrap();
+        board.open(mine).unwrap();
+        assert_eq!(session.update(&board), Some(false));
+        assert_eq!((session.passed, session.failed), (1, 1));
+    }
+}
@@ -0,0 +1,184 @@
+//! A gym-style environment around `Board` for training agents: `reset` starts a
+//! game, `step` plays a move and returns what the agent sees, its reward and
+//! whether the game is over.
+
+use crate::board::{Board, GameState, Move, Rect, Square};
+use crate::config::GameConfig;
+
+/// Planes of an `Observation`, in order.
+pub const CHANNELS: usize = 4;
+
+/// The visible board as `CHANNELS` planes of `rows` x `cols` values: closed
+/// cells, flags, opened cells and the count of opened cells divided by 8 (signed
+/// with anti-mines). The first three hold 0 or 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Observation {
+    /// Channels, rows and columns.
+    pub shape: [usize; 3],
+    /// Channel-major, then row-major.
+    pub data: Vec<f32>,
+}
+
+/// What `Env::step` pays for a move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rewards {
+    pub win: f32,
+    pub loss: f32,
+    /// For a move the board rejects, which leaves it unchanged.
+    pub invalid: f32,
+    /// For opening every safe cell, paid pro rata as they are opened.
+    pub progress: f32,
+}
+
+impl Default for Rewards {
+    fn default() -> Rewards {
+        Rewards {
+            win: 1.0,
+            loss: -1.0,
+            invalid: -0.1,
+            progress: 1.0,
+        }
+    }
+}
+
+pub struct Env {
+    pub rows: usize,
+    pub cols: usize,
+    pub nr_mines: usize,
+    pub config: GameConfig,
+    pub rewards: Rewards,
+    board: Board,
+    // seed of the next game, counting up so runs can be repeated
+    seed: u64,
+}
+
+impl Env {
+    /// An environment whose games use the seeds `seed`, `seed + 1` and so on.
+    /// Moves are not rate limited.
+    pub fn new(rows: usize, cols: usize, nr_mines: usize, config: GameConfig, seed: u64) -> Env {
+        let config = GameConfig {
+            min_move_interval: None,
+            ..config
+        };
+        Env {
+            board: Board::with_config(rows, cols, nr_mines, config.clone()),
+            rows,
+            cols,
+            nr_mines,
+            config,
+            rewards: Rewards::default(),
+            seed,
+        }
+    }
+
+    /// Starts a new game, whose mines are placed by the first opened cell.
+    pub fn reset(&mut self) -> Observation {
+        self.board = Board::with_config(self.rows, self.cols, self.nr_mines, self.config.clone());
+        self.observation()
+    }
+
+    /// Plays `action` and returns the observation after it, the reward and
+    /// whether the game is over.
+    pub fn step(&mut self, action: Move) -> (Observation, f32, bool) {
+        let opened = self.board.open_fields.len();
+        let accepted = match (self.board.state, action) {
+            (GameState::Init, Move::Open(pos)) if self.board.contains(pos) => {
+                self.board.init_mines(pos, Some(self.seed));
+                self.seed = self.seed.wrapping_add(1);
+                true
+            }
+            (GameState::Init, _) => false,
+            _ => self.board.play(action).is_ok(),
+        };
+        let safe_cells = self.rows * self.cols - self.nr_mines;
+        let reward = if !accepted {
+            self.rewards.invalid
+        } else {
+            let progress = (self.board.open_fields.len() - opened) as f32 / safe_cells as f32;
+            progress * self.rewards.progress
+                + match self.board.state {
+                    GameState::Won => self.rewards.win,
+                    GameState::Lost => self.rewards.loss,
+                    GameState::Init | GameState::OnGoing => 0.0,
+                }
+        };
+        let done = matches!(self.board.state, GameState::Won | GameState::Lost);
+        (self.observation(), reward, done)
+    }
+
+    pub fn observation(&self) -> Observation {
+        let plane = self.rows * self.cols;
+        let mut data = vec![0.0; CHANNELS * plane];
+        let all = Rect::new((0, 0), self.rows, self.cols);
+        for ((x, y), square) in self.board.iter_region(all) {
+            let i = y * self.cols + x;
+            match square {
+                Square::NotYetOpened | Square::Mine => data[i] = 1.0,
+                Square::Flag => data[plane + i] = 1.0,
+                Square::Opened(count) => {
+                    data[2 * plane + i] = 1.0;
+                    data[3 * plane + i] = count as f32 / 8.0;
+                }
+            }
+        }
+        Observation {
+            shape: [CHANNELS, self.rows, self.cols],
+            data,
+        }
+    }
+
+    /// Number of discrete actions, see `action`.
+    pub fn action_count(&self) -> usize {
+        self.rows * self.cols * 3
+    }
+
+    /// The move of a discrete action: `(y * cols + x) * 3` opens `(x, y)`, one more
+    /// flags it and two more chords it.
+    pub fn action(&self, index: usize) -> Option<Move> {
+        if index >= self.action_count() {
+            return None;
+        }
+        let pos = ((index / 3) % self.cols, index / 3 / self.cols);
+        Some(match index % 3 {
+            0 => Move::Open(pos),
+            1 => Move::Flag(pos),
+            _ => Move::Chord(pos),
+        })
+    }
+
+    /// The game being played, for inspecting it outside of the observation.
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env() {
+        let mut env = Env::new(9, 9, 10, GameConfig::default(), 1);
+        let observation = env.reset();
+        assert_eq!(observation.shape, [CHANNELS, 9, 9]);
+        assert!(observation.data[..81].iter().all(|&v| v == 1.0));
+
+        let (_, reward, done) = env.step(Move::Flag((0, 0)));
+        assert_eq!((reward, done), (env.rewards.invalid, false));
+        let (observation, reward, done) = env.step(env.action(0).unwrap());
+        assert!(!done);
+        // the first click with seed 1 at (0, 0) opens 18 cells, (2, 0) shows a 1
+        assert_eq!(reward, 18.0 / 71.0);
+        assert_eq!(observation.data[2 * 81 + 2], 1.0);
+        assert_eq!(observation.data[3 * 81 + 2], 1.0 / 8.0);
+        assert_eq!(observation.data[2], 0.0);
+
+        let (_, reward, done) = env.step(Move::Open((3, 1)));
+        assert_eq!((reward, done), (env.rewards.loss, true));
+        env.reset();
+        assert_eq!(env.board().state, GameState::Init);
+
+        assert_eq!(env.action(3 * 10 + 1), Some(Move::Flag((1, 1))));
+        assert_eq!(env.action(env.action_count()), None);
+    }
+}
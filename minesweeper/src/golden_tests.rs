@@ -0,0 +1,143 @@
+//! Golden-file regression tests: generated output is compared against the files
+//! in `testdata/`. Run with `UPDATE_GOLDEN=1` to regenerate them after an
+//! intentional behavior change.
+use std::fmt::Write;
+
+use crate::board::{Board, Move, Position};
+use crate::codec;
+use crate::config::GameConfig;
+use crate::simulate::SolverStrategy;
+use crate::solver::Solver;
+
+const CASES: [(usize, usize, usize, u64, Position); 6] = [
+    (9, 9, 10, 1, (0, 0)),
+    (9, 9, 10, 2, (4, 4)),
+    (9, 9, 10, 3, (8, 8)),
+    (16, 16, 40, 7, (8, 8)),
+    (16, 30, 99, 42, (0, 0)),
+    (16, 30, 99, 1000, (29, 15)),
+];
+
+fn check_golden(name: &str, actual: &str) {
+    let path = format!("{}/testdata/{name}", env!("CARGO_MANIFEST_DIR"));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).unwrap();
+    }
+    let expected = std::fs::read_to_string(&path).unwrap();
+    assert!(
+        actual == expected,
+        "{path} is out of date, rerun with UPDATE_GOLDEN=1 if the change is intended"
+    );
+}
+
+fn board_for((rows, cols, mines, seed, start): (usize, usize, usize, u64, Position)) -> Board {
+    let mut board = Board::new(rows, cols, mines);
+    board.init_mines(start, Some(seed));
+    board
+}
+
+#[test]
+fn test_golden_layouts() {
+    let mut out = String::from("# `*` is a mine, `.` a safe cell.\n");
+    for case in CASES {
+        let (rows, cols, mines, seed, (x, y)) = case;
+        let board = board_for(case);
+        let layout = board.mines.as_ref().unwrap();
+        writeln!(
+            out,
+            "\ncase rows={rows} cols={cols} mines={mines} seed={seed} start={x},{y}"
+        )
+        .unwrap();
+        for y in 0..rows {
+            for x in 0..cols {
+                out.push(if layout.contains((x, y)) { '*' } else { '.' });
+            }
+            out.push('\n');
+        }
+    }
+    check_golden("layouts.txt", &out);
+}
+
+#[test]
+fn test_golden_analysis() {
+    let mut out = String::from(
+        "# 3BV, openings and islands of the layouts, what the solver deduces right\n\
+         # after the first click as x,y and how far playing only deductions gets.\n",
+    );
+    let cells = |cells: Vec<Position>| {
+        cells
+            .iter()
+            .map(|(x, y)| format!("{x},{y}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    for case in CASES {
+        let (rows, cols, mines, seed, (x, y)) = case;
+        let board = board_for(case);
+        let metrics = board.metrics().unwrap();
+        let solver = Solver::new(&board.view());
+        let mut played = board.clone();
+        let mut moves = 0;
+        while played.ongoing() {
+            let solver = Solver::new(&played.view());
+            let flags = solver
+                .mine_cells()
+                .into_iter()
+                .filter(|&pos| !played.flagged_fields.contains(pos))
+                .map(Move::Flag);
+            let opens = solver.safe_cells().into_iter().map(Move::Open);
+            let deduced: Vec<Move> = flags.chain(opens).collect();
+            let before = moves;
+            for mv in deduced {
+                if played.play(mv).is_ok() {
+                    moves += 1;
+                }
+            }
+            if moves == before {
+                break;
+            }
+        }
+        writeln!(
+            out,
+            "\ncase rows={rows} cols={cols} mines={mines} seed={seed} start={x},{y}\n\
+             3bv={} openings={} islands={}\nsafe: {}\nmines: {}\n\
+             deduced: {moves} moves, {:?} with {} cells open",
+            metrics.bbbv,
+            metrics.openings,
+            metrics.islands,
+            cells(solver.safe_cells()),
+            cells(solver.mine_cells()),
+            played.state,
+            played.open_fields.len(),
+        )
+        .unwrap();
+    }
+    check_golden("analysis.txt", &out);
+}
+
+#[test]
+fn test_golden_deterministic_game() {
+    let config = GameConfig {
+        deterministic: true,
+        ..GameConfig::default()
+    };
+    let mut board = Board::with_config(16, 30, 99, config);
+    while board.ongoing() || !board.initialized() {
+        let Some((mv, _)) = SolverStrategy.decide(&board.view()) else {
+            break;
+        };
+        match (board.initialized(), mv) {
+            (false, Move::Open(pos)) => board.init_mines(pos, None),
+            _ => board.play(mv).map(|_| ()).unwrap(),
+        }
+    }
+    let replay = board.replay().unwrap();
+    let out = format!(
+        "state={:?} time={:?} moves={}\nreplay={}\n",
+        board.state,
+        board.elapsed(),
+        replay.actions.len(),
+        codec::to_share_code(&codec::encode_replay(&replay))
+    );
+    check_golden("deterministic.txt", &out);
+}
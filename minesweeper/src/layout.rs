@@ -0,0 +1,282 @@
+//! A plain text board format for tests, puzzles and bug reports, one line per row:
+//!
+//! ```text
+//! 01*.
+//! 012F
+//! 001x
+//! ```
+//!
+//! `.` is an unopened safe cell, `*` an unopened mine, `F` a flagged mine, `x` a
+//! flag on a safe cell and digits are opened cells with their mine count.
+//!
+//! Lines like `wormhole 0,0 3,2` after the rows join two cells by a wormhole.
+
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::board::{Adjacency, Board, Mask, Position};
+use crate::config::GameConfig;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LayoutError {
+    Empty,
+    /// The row has another length than the first one.
+    RaggedRow(usize),
+    InvalidChar(Position, char),
+    /// An opened cell shows another count than its neighboring mines.
+    WrongCount(Position),
+    /// Every cell is a mine.
+    NoSafeCell,
+    /// A wormhole line with cells that are malformed, outside the board or
+    /// already part of a wormhole.
+    InvalidWormhole(String),
+}
+
+impl Board {
+    /// A board with the mines, opened cells and flags of a text layout. Without
+    /// opened cells or flags the first click is still to come, otherwise the game
+    /// goes on from the given position. The board has no seed, so no replay.
+    pub fn from_layout_str(layout: &str) -> Result<Board, LayoutError> {
+        Board::from_layout_with_config(layout, GameConfig::default())
+    }
+
+    /// A layout played by the rules of `config`, its opened cells counted by the
+    /// neighbors `config` gives them. Wormholes of the layout join those of `config`.
+    pub fn from_layout_with_config(layout: &str, config: GameConfig) -> Result<Board, LayoutError> {
+        let (wormhole_lines, lines): (Vec<&str>, Vec<&str>) = layout
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .partition(|line| line.starts_with("wormhole "));
+        let cols = lines.first().ok_or(LayoutError::Empty)?.chars().count();
+        let mut mines = HashSet::new();
+        let mut opened = Vec::new();
+        let mut flagged = HashSet::new();
+        for (y, line) in lines.iter().enumerate() {
+            if line.chars().count() != cols {
+                return Err(LayoutError::RaggedRow(y));
+            }
+            for (x, c) in line.chars().enumerate() {
+                let pos = (x, y);
+                match c {
+                    '.' => {}
+                    '*' => {
+                        mines.insert(pos);
+                    }
+                    'F' => {
+                        mines.insert(pos);
+                        flagged.insert(pos);
+                    }
+                    'x' => {
+                        flagged.insert(pos);
+                    }
+                    '0'..='9' => opened.push((pos, c as u8 - b'0')),
+                    _ => return Err(LayoutError::InvalidChar(pos, c)),
+                }
+            }
+        }
+        let rows = lines.len();
+        if mines.len() == rows * cols {
+            return Err(LayoutError::NoSafeCell);
+        }
+        let mut board = Board::with_config(rows, cols, mines.len(), config);
+        for line in wormhole_lines {
+            let pair = parse_wormhole(line)
+                .filter(|&(a, b)| a != b && board.contains(a) && board.contains(b))
+                .filter(|&(a, b)| {
+                    !board
+                        .config
+                        .wormholes
+                        .iter()
+                        .any(|&(c, d)| [c, d].contains(&a) || [c, d].contains(&b))
+                })
+                .ok_or_else(|| LayoutError::InvalidWormhole(line.to_string()))?;
+            board.config.wormholes.push(pair);
+        }
+        for &(pos, count) in opened.iter() {
+            if board
+                .iter_neighbors(pos)
+                .filter(|n| mines.contains(n))
+                .count()
+                != count as usize
+            {
+                return Err(LayoutError::WrongCount(pos));
+            }
+        }
+        let opened = opened.into_iter().map(|(pos, _)| pos);
+        board.install_position(
+            Mask::from_positions(board.grid(), mines),
+            Mask::from_positions(board.grid(), opened),
+            Mask::from_positions(board.grid(), flagged),
+        );
+        Ok(board)
+    }
+
+    /// The board as a text layout, `None` while the mines are not placed, with
+    /// anti-mines or counts above 9, which the format has no characters for, with
+    /// counts other than of the surrounding cells or with lying numbers. Mines are
+    /// included, so this spoils an ongoing game.
+    pub fn to_layout_string(&self) -> Option<String> {
+        let mines = self.mines.as_ref()?;
+        if !self.anti_mines.is_empty()
+            || self.config.adjacency != Adjacency::King
+            || self.config.liar
+            || self.counts.iter().any(|&count| count > 9)
+        {
+            return None;
+        }
+        let mut layout = String::with_capacity(self.rows * (self.cols + 1));
+        for y in 0..self.rows {
+            for x in 0..self.cols {
+                let pos = (x, y);
+                let c = if self.open_fields.contains(pos) && !mines.contains(pos) {
+                    (b'0' + self.count(pos) as u8) as char
+                } else {
+                    match (mines.contains(pos), self.flagged_fields.contains(pos)) {
+                        (true, true) => 'F',
+                        (true, false) => '*',
+                        (false, true) => 'x',
+                        (false, false) => '.',
+                    }
+                };
+                layout.push(c);
+            }
+            layout.push('\n');
+        }
+        for &((ax, ay), (bx, by)) in self.config.wormholes.iter() {
+            layout.push_str(&format!("wormhole {ax},{ay} {bx},{by}\n"));
+        }
+        Some(layout)
+    }
+}
+
+fn parse_wormhole(line: &str) -> Option<(Position, Position)> {
+    let cell = |text: &str| {
+        let (x, y) = text.split_once(',')?;
+        Some((x.parse().ok()?, y.parse().ok()?))
+    };
+    match line.split_whitespace().collect::<Vec<_>>()[..] {
+        ["wormhole", a, b] => Some((cell(a)?, cell(b)?)),
+        _ => None,
+    }
+}
+
+impl FromStr for Board {
+    type Err = LayoutError;
+
+    fn from_str(layout: &str) -> Result<Board, LayoutError> {
+        Board::from_layout_str(layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{GameState, Square};
+
+    #[test]
+    fn test_layout_round_trip() {
+        let mut board = Board::new(9, 9, 10);
+        assert_eq!(board.to_layout_string(), None);
+        board.init_mines((0, 0), Some(1));
+        board.flag((3, 1)).unwrap();
+        board.flag((5, 0)).unwrap();
+        let layout = board.to_layout_string().unwrap();
+        assert_eq!(layout.lines().next(), Some("001..x..*"));
+        assert_eq!(layout.lines().nth(1), Some("001F..*.."));
+
+        let loaded: Board = layout.parse().unwrap();
+        assert!(loaded.ongoing());
+        assert_eq!(loaded.get_board_state(), board.get_board_state());
+        assert_eq!(loaded.to_layout_string(), Some(layout));
+    }
+
+    #[test]
+    fn test_wrapped_layout() {
+        let layout = "1...\n....\n....\n...*\n";
+        assert_eq!(
+            Board::from_layout_str(layout).err(),
+            Some(LayoutError::WrongCount((0, 0)))
+        );
+        let wrapped = GameConfig {
+            wrap_edges: true,
+            ..GameConfig::default()
+        };
+        let mut board = Board::from_layout_with_config(layout, wrapped).unwrap();
+        assert!(board.config.wrap_edges);
+        board.open((3, 0)).unwrap();
+        assert_eq!(board.get_board_state()[0][3], Square::Opened(1));
+    }
+
+    #[test]
+    fn test_layout_position() {
+        let mut board = Board::from_layout_str(
+            "
+            1*1
+            111
+            ...
+            ",
+        )
+        .unwrap();
+        assert!(board.ongoing());
+        assert_eq!(board.nr_mines, 1);
+        board.open((1, 2)).unwrap();
+        assert_eq!(board.flag((1, 0)).unwrap(), GameState::Won);
+
+        let mut fresh = Board::from_layout_str("..\n.*").unwrap();
+        assert!(!fresh.initialized());
+        fresh.init_mines((0, 0), None);
+        assert_eq!(fresh.get_board_state()[0][0], Square::Opened(1));
+    }
+
+    #[test]
+    fn test_layout_wormholes() {
+        let layout = "*1..1\nwormhole 1,0 4,0\n";
+        let mut board = Board::from_layout_str(layout).unwrap();
+        assert_eq!(board.config.wormholes, [((1, 0), (4, 0))]);
+        assert_eq!(board.to_layout_string().as_deref(), Some(layout));
+        // the zero cascades through the wormhole
+        board.open((3, 0)).unwrap();
+        assert_eq!(board.get_board_state()[0][2], Square::Opened(0));
+
+        assert_eq!(
+            Board::from_layout_str("*1..1").err(),
+            Some(LayoutError::WrongCount((4, 0)))
+        );
+        for line in ["wormhole 1,0 1,0", "wormhole 1,0 5,0", "wormhole 1,0"] {
+            assert_eq!(
+                Board::from_layout_str(&format!("*1...\n{line}")).err(),
+                Some(LayoutError::InvalidWormhole(line.to_string()))
+            );
+        }
+        let reused = "*1...\nwormhole 1,0 3,0\nwormhole 2,0 3,0";
+        assert_eq!(
+            Board::from_layout_str(reused).err(),
+            Some(LayoutError::InvalidWormhole("wormhole 2,0 3,0".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_layout_errors() {
+        assert_eq!(
+            Board::from_layout_str(" \n").err(),
+            Some(LayoutError::Empty)
+        );
+        assert_eq!(
+            Board::from_layout_str("..\n...").err(),
+            Some(LayoutError::RaggedRow(1))
+        );
+        assert_eq!(
+            Board::from_layout_str(".?").err(),
+            Some(LayoutError::InvalidChar((1, 0), '?'))
+        );
+        assert_eq!(
+            Board::from_layout_str("2*").err(),
+            Some(LayoutError::WrongCount((0, 0)))
+        );
+        assert_eq!(
+            Board::from_layout_str("*F").err(),
+            Some(LayoutError::NoSafeCell)
+        );
+    }
+}
@@ -1,2 +1,56 @@
+//! Minesweeper engine shared by the terminal game, the GUI and the wasm build.
+//!
+//! The types in `prelude` are the stable API. The other public modules are
+//! usable but may change with the engine; the hidden ones serve the frontends of
+//! this repository, the tests and the fuzzers and are not part of the API.
+
+pub mod adaptive;
+pub mod anticheat;
+#[doc(hidden)]
+pub mod async_game;
+#[doc(hidden)]
+pub mod bench;
 pub mod board;
+#[doc(hidden)]
+pub mod chunked;
+pub mod clock;
+pub mod codec;
+#[doc(hidden)]
+pub mod command;
 pub mod config;
+#[doc(hidden)]
+pub mod cube;
+pub mod drill;
+#[doc(hidden)]
+pub mod env;
+pub mod layout;
+pub mod mbf;
+pub mod metrics;
+pub mod objective;
+pub mod pack;
+#[doc(hidden)]
+pub mod perft;
+pub mod prelude;
+pub mod race;
+#[doc(hidden)]
+pub mod randomness;
+pub mod render;
+pub mod replay;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod share;
+#[cfg(feature = "signing")]
+pub mod signing;
+pub mod simulate;
+pub mod solver;
+pub mod sync;
+pub mod thumbnail;
+#[doc(hidden)]
+pub mod user_error;
+pub mod variant;
+pub mod view;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(test)]
+mod golden_tests;
@@ -1,117 +1,1082 @@
-use std::io;
+use std::io::{self, IsTerminal};
+#[cfg(feature = "signing")]
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use clap::Parser;
+use minesweeper::anticheat::{self, Sensitivity};
+use minesweeper::bench;
 use minesweeper::board::*;
-use minesweeper::config::Args;
-use regex::Regex;
+use minesweeper::codec;
+use minesweeper::command::{self, GameCommand};
+use minesweeper::config::{Args, Command, PackCommand};
+use minesweeper::drill::{self, Session};
+use minesweeper::pack::Pack;
+use minesweeper::perft;
+use minesweeper::prelude::UserFacingError;
+use minesweeper::randomness::{self, Uniformity};
+#[cfg(feature = "themes")]
+use minesweeper::render::Glyphs;
+use minesweeper::render::Style;
+use minesweeper::replay::Replay;
+#[cfg(feature = "scripting")]
+use minesweeper::scripting::{Event, Script};
+use minesweeper::share;
+#[cfg(feature = "signing")]
+use minesweeper::signing::{Signature, SigningKey};
+use minesweeper::simulate::{Reason, SolverStrategy};
+use minesweeper::variant;
 
 fn main() {
     let args = Args::parse();
+    if let Some(command) = args.get_command() {
+        match command {
+            Command::Audit {
+                replays,
+                reaction,
+                chain,
+                min_guesses,
+                luck,
+            } => audit(
+                replays,
+                &Sensitivity {
+                    reaction: Duration::from_millis(*reaction),
+                    chain: *chain,
+                    min_guesses: *min_guesses,
+                    luck: *luck,
+                },
+            ),
+            Command::Bench { boards } => bench(&args, *boards),
+            Command::Diff { a, b } => diff(&args, a, b),
+            Command::Drill { name } => practice(&args, name.as_deref()),
+            #[cfg(feature = "signing")]
+            Command::Keygen { secret } => keygen(secret),
+            Command::Pack(PackCommand::List { file }) => list_pack(&args, file),
+            // the puzzle is played like any other board, see `new_board`
+            Command::Pack(PackCommand::Play { .. }) => {}
+            Command::Selftest => selftest(),
+            #[cfg(feature = "signing")]
+            Command::Sign { file, secret } => sign(file, secret),
+            Command::TestRandomness { samples, x, y } => test_randomness(&args, (*x, *y), *samples),
+            #[cfg(feature = "signing")]
+            Command::Verify { file, key } => verify(file, key),
+        }
+        if args.get_pack_puzzle().is_none() {
+            return;
+        }
+    }
+    if let Some(path) = args.get_replay() {
+        play_back(&args, path);
+        return;
+    }
+    if let Some(name) = args.get_variant() {
+        if let Err(e) = variant::find(name) {
+            if name != "list" {
+                println!("{}", UserFacingError::from(e));
+            }
+            for variant in variant::variants() {
+                println!("{:<12}{}", variant.name(), variant.description());
+            }
+            std::process::exit(if name == "list" { 0 } else { 1 });
+        }
+    }
     if let Some(seed) = args.get_seed() {
         println!("Seed: {seed}");
     }
+    if args.get_solve() {
+        solve(&args);
+        return;
+    }
+    if let Some(path) = args.get_moves() {
+        play_moves(&args, path);
+    }
+    let mut seed = args.get_seed();
+    #[allow(unused_mut)]
+    let mut style = style(&args);
+    let mut board = new_board(&args);
+    if let Some(path) = args.get_load() {
+        match load_game(path) {
+            Ok(loaded) => {
+                board = loaded;
+                seed = board.seed();
+                println!("Loaded {}: \n{}", path.display(), style.render(&board));
+            }
+            Err(message) => {
+                println!("{message}");
+                std::process::exit(1);
+            }
+        }
+    }
+    #[cfg(feature = "scripting")]
+    let mut script = args.get_script().map(load_script);
     println!(
         "Rows: {}, Cols: {}, Mines: {}",
-        args.get_rows(),
-        args.get_cols(),
-        args.get_mines()
+        board.rows, board.cols, board.nr_mines
     );
-    let re_open = Regex::new(r"\(?(?<x>\d+)(,|\s+)(?<y>\d+)\)?").unwrap();
-    let re_flag = Regex::new(r"(flag|f)\s*\(?(?<x>\d+)(,|\s+)(?<y>\d+)\)?").unwrap();
-    let mut board = Board::new(args.get_rows(), args.get_cols(), args.get_mines());
-    while board.ongoing() || !board.initialized() {
-        println!("Enter coordinate to open (int,int): ");
+    println!("Goal: {}", board.objective().description());
+    while board.ongoing() || !board.initialized() || take_back_loss(&mut board, &style) {
+        // theme authors see their glyph changes on the next board printed
+        #[cfg(all(feature = "themes", debug_assertions))]
+        if let Some(path) = args.get_glyphs() {
+            match read_glyphs(path) {
+                Ok(glyphs) => style.glyphs = glyphs,
+                Err(message) => println!("{message}"),
+            }
+        }
+        println!("Enter coordinate to open (int,int), flag (int,int), chord (int,int), restart [same], hint, undo, seed [int], save <path> or load <path>: ");
 
-        let mut coord = String::new();
-        io::stdin()
-            .read_line(&mut coord)
+        let mut line = String::new();
+        let read = io::stdin()
+            .read_line(&mut line)
             .expect("Failed to read line");
-        coord = coord.to_lowercase();
-
-        let caps_flag = re_flag.captures(&coord);
-        match caps_flag {
-            Some(c) => {
-                let (x, y) = (c.name("x").unwrap().as_str(), c.name("y").unwrap().as_str());
-                let (x, y) = (x.trim().parse::<usize>(), y.trim().parse::<usize>());
-                if x.is_err() || y.is_err() {
-                    println!("Could not parse coordinates to usize, try again.");
-                    continue;
+        if read == 0 {
+            println!("End of input, quitting.");
+            return;
+        }
+
+        #[cfg(feature = "scripting")]
+        let was_initialized = board.initialized();
+        match line.parse::<GameCommand>() {
+            Err(e) => {
+                println!("{}", UserFacingError::from(e));
+                continue;
+            }
+            Ok(GameCommand::Restart { same }) => {
+                if same {
+                    board.restart();
                 } else {
-                    let flag_res = board.flag((x.unwrap(), y.unwrap()));
-                    if let Err(e) = flag_res {
-                        match e {
-                            FlagError::AlreadyOpen => {
-                                println!("This field is already open, try again.")
-                            }
-                            FlagError::OutOfBounds => {
-                                println!("That coordinate set is out of bounds, try again")
-                            }
-                            FlagError::AlreadyWon => {
-                                panic!("This game is already won.")
-                            }
-                            FlagError::MinesNotInit => {
-                                panic!("Mines have not been initialized.")
-                            }
-                            FlagError::AlreadyLost => panic!("Game is already lost."),
-                        }
+                    // the next seed, a new layout that can still be played again
+                    if seed.is_some() || board.config.deterministic {
+                        seed = board.seed().or(seed).map(|seed| seed.wrapping_add(1));
+                    }
+                    if let Some(seed) = seed {
+                        println!("Seed: {seed}");
+                    }
+                    board = new_board(&args);
+                }
+                println!("Restarted, current board: \n{}", style.render(&board));
+                continue;
+            }
+            Ok(GameCommand::Hint) => {
+                println!("{}", command::hint_message(board.request_hint().as_ref()));
+                continue;
+            }
+            Ok(GameCommand::Undo) => {
+                match board.undo() {
+                    Ok(()) => println!(
+                        "Took back the last move, current board: \n{}",
+                        style.render(&board)
+                    ),
+                    Err(e) => println!("{}", UserFacingError::from(e)),
+                }
+                continue;
+            }
+            Ok(GameCommand::Seed(None)) => {
+                match board.seed() {
+                    Some(seed) => println!("Seed: {seed}"),
+                    None => println!("The mines are placed at the first click."),
+                }
+                continue;
+            }
+            Ok(GameCommand::Seed(Some(new_seed))) => {
+                seed = Some(new_seed);
+                board = new_board(&args);
+                println!("New board with seed {new_seed}: \n{}", style.render(&board));
+                continue;
+            }
+            Ok(GameCommand::Save(path)) => {
+                match board.replay() {
+                    Some(replay) => match std::fs::write(&path, codec::encode_replay(&replay)) {
+                        Ok(()) => println!("Saved the game, continue it with: load {path}"),
+                        Err(e) => println!("Could not write {path}: {e}"),
+                    },
+                    None => println!("Only games started from a seed can be saved."),
+                }
+                continue;
+            }
+            Ok(GameCommand::Load(path)) => {
+                match load_game(Path::new(&path)) {
+                    Ok(loaded) => {
+                        board = loaded;
+                        seed = board.seed();
+                        println!("Loaded {path}, current board: \n{}", style.render(&board));
                     }
+                    Err(message) => println!("{message}"),
                 }
+                continue;
             }
-            None => {
-                let caps_open = re_open.captures(&coord);
-                match caps_open {
-                    None => {
-                        println!("Invalid coordinate entered, try again.");
+            Ok(GameCommand::Flag((x, y))) => {
+                if let Err(e) = board.flag((x, y)) {
+                    println!("{}", UserFacingError::from(e));
+                }
+            }
+            Ok(GameCommand::Chord((x, y))) => {
+                if let Err(e) = board.chord((x, y)) {
+                    println!("{}", UserFacingError::from(e));
+                }
+            }
+            Ok(GameCommand::Open((x, y))) => match board.initialized() {
+                false => {
+                    if let Err(e) = first_click(&args, &mut board, (x, y), seed) {
+                        println!("{}", UserFacingError::from(e));
+                    }
+                }
+                true => {
+                    if args.get_warn_guesses()
+                        && board.safe_alternative((x, y)).is_some()
+                        && !confirm(&format!(
+                            "({x},{y}) is a guess while another cell is provably safe. Open anyway? [y/N]"
+                        ))
+                    {
                         continue;
                     }
-                    Some(c) => {
-                        let (x, y) = (c.name("x").unwrap().as_str(), c.name("y").unwrap().as_str());
-                        let (x, y) = (x.trim().parse::<usize>(), y.trim().parse::<usize>());
-                        if x.is_err() || y.is_err() {
-                            println!("Could not parse coordinates to usize, try again.");
-                            continue;
-                        } else {
-                            let (x, y) = (x.unwrap(), y.unwrap());
-                            match board.initialized() {
-                                false => board.init_mines((x, y), args.get_seed()),
-                                true => {
-                                    let open_res = board.open((x, y));
-                                    if let Err(e) = open_res {
-                                        match e {
-                                            OpenError::AlreadyOpen => {
-                                                println!("This field is already open, try again.")
-                                            }
-                                            OpenError::AlreadyFlagged => {
-                                                println!(
-                                                    "This field is already flagged, try again."
-                                                )
-                                            }
-                                            OpenError::OutOfBounds => {
-                                                println!("That coordinate set is out of bounds, try again")
-                                            }
-                                            OpenError::AlreadyWon => {
-                                                panic!("This game is already won.")
-                                            }
-                                            OpenError::MinesNotInit => {
-                                                panic!("Mines have not been initialized.")
-                                            }
-                                            OpenError::AlreadyLost => {
-                                                panic!("Game is already lost.")
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
+                    if let Err(e) = board.open((x, y)) {
+                        println!("{}", UserFacingError::from(e));
                     }
                 }
-            }
+            },
         }
-        println!("Current board: \n{board}");
+        #[cfg(feature = "scripting")]
+        if let Some(script) = script.as_mut().filter(|_| board.initialized()) {
+            let event = if was_initialized {
+                Event::Turn
+            } else {
+                Event::Start
+            };
+            run_script(script, event, &mut board);
+        }
+        println!("Current board: \n{}", style.render(&board));
+    }
+    #[cfg(feature = "scripting")]
+    if let Some(script) = script.as_mut() {
+        run_script(script, Event::End, &mut board);
     }
+    let time = board.elapsed().as_secs_f64();
     if board.lost() {
-        println!("You lost!")
+        println!("You lost! Time: {time:.3}s")
     } else {
-        println!("Congratulations, you won!")
+        println!("Congratulations, you won! Time: {time:.3}s")
+    }
+    if let (Some(metrics), Some(score)) = (board.metrics(), board.score()) {
+        println!("3BV: {}, 3BV/s: {score:.3}", metrics.bbbv);
+    }
+    if let Some(phases) = board.phase_splits() {
+        println!(
+            "Phases: first opening {:.3}s, mid game {:.3}s, endgame {:.3}s",
+            phases.first_opening.as_secs_f64(),
+            phases.mid_game.as_secs_f64(),
+            phases.endgame.as_secs_f64()
+        );
+    }
+    if board.config.assisted_flags {
+        println!("Assisted: {} wrong flags rejected", board.flags_rejected);
+    }
+    if board.hints_used.iter().any(|&n| n > 0) {
+        let [number, region, cell] = board.hints_used;
+        println!("Hints: {number} number, {region} region, {cell} cell");
+    }
+    if board.undos > 0 {
+        println!("Undos: {}", board.undos);
+    }
+    if let Some(path) = args.get_save_mbf() {
+        match board.to_mbf().map(|bytes| std::fs::write(path, bytes)) {
+            Ok(Ok(())) => println!("Saved the board to {}.", path.display()),
+            Ok(Err(e)) => println!("Could not write {}: {e}", path.display()),
+            Err(e) => println!("This board cannot be saved: {}", UserFacingError::from(e)),
+        }
+    }
+    if args.get_share() {
+        if let Some(grid) = share::emoji_grid(&board) {
+            println!("{grid}");
+        }
+        if let Some(code) = share::replay_code(&board) {
+            println!("Replay code: {code}");
+        }
+    }
+    if args.get_copy() {
+        if let Some(summary) = share::result_summary(&board) {
+            let snapshot = share::markdown_snapshot(&board.view());
+            print!("{}", share::osc52_copy(&format!("{summary}\n{snapshot}")));
+            println!("Copied the result to the clipboard.");
+        }
+    }
+}
+
+#[cfg(feature = "scripting")]
+fn load_script(path: &std::path::Path) -> Script {
+    let loaded = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read {}: {e}", path.display()))
+        .and_then(|source| {
+            Script::new(&source).map_err(|e| format!("Invalid script {}: {e:?}", path.display()))
+        });
+    match loaded {
+        Ok(script) => script,
+        Err(message) => {
+            println!("{message}");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "scripting")]
+fn run_script(script: &mut Script, event: Event, board: &mut Board) {
+    match script.dispatch(event, board) {
+        Ok(played) => {
+            for (mv, result) in played {
+                match result {
+                    Ok(_) => println!("Script played {mv:?}."),
+                    Err(e) => println!("Script move {mv:?} was rejected: {e:?}"),
+                }
+            }
+        }
+        Err(e) => println!("Script error: {e:?}"),
+    }
+}
+
+/// The style of the printed boards, with the glyph file if one is given.
+fn style(args: &Args) -> Style {
+    #[allow(unused_mut)]
+    let mut style = args.get_style();
+    #[cfg(feature = "themes")]
+    if let Some(path) = args.get_glyphs() {
+        match read_glyphs(path) {
+            Ok(glyphs) => style.glyphs = glyphs,
+            Err(message) => {
+                println!("{message}");
+                std::process::exit(1);
+            }
+        }
+    }
+    style
+}
+
+#[cfg(feature = "themes")]
+fn read_glyphs(path: &std::path::Path) -> Result<Glyphs, String> {
+    std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not read {}: {e}", path.display()))
+        .and_then(|source| {
+            Glyphs::from_toml(&source)
+                .map_err(|e| format!("Invalid glyphs {}: {e}", path.display()))
+        })
+}
+
+/// Offers to take back the move that lost the game, typos happen. Only a player
+/// at a terminal is asked, piped input ends with the game.
+fn take_back_loss(board: &mut Board, style: &Style) -> bool {
+    let undone = board.lost()
+        && io::stdin().is_terminal()
+        && confirm("You hit a mine. Undo the last move? [y/N]")
+        && board.undo().is_ok();
+    if undone {
+        println!("Current board: \n{}", style.render(board));
+    }
+    undone
+}
+
+/// Asks a yes or no question, anything but yes is no.
+fn confirm(question: &str) -> bool {
+    println!("{question}");
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).is_ok()
+        && matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Reads a game written by the `save` command.
+fn load_game(path: &Path) -> Result<Board, String> {
+    let replay = read_replay(path)?;
+    Board::from_saved(&replay).map_err(|e| {
+        format!(
+            "Invalid saved game {}: {}",
+            path.display(),
+            UserFacingError::from(e)
+        )
+    })
+}
+
+/// Reads a saved game, an MBF file (by its `.mbf` extension) or a text layout.
+/// MBF files and layouts get the rules of `args`, saved games keep their own.
+fn load_any(args: &Args, path: &Path) -> Result<Board, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    if path.extension().is_some_and(|ext| ext == "mbf") {
+        Board::from_mbf_with_config(&bytes, args.get_game_config()).map_err(|e| {
+            format!(
+                "Invalid MBF file {}: {}",
+                path.display(),
+                UserFacingError::from(e)
+            )
+        })
+    } else if let Ok(replay) = read_replay(path) {
+        Board::from_saved(&replay).map_err(|e| {
+            format!(
+                "Invalid saved game {}: {}",
+                path.display(),
+                UserFacingError::from(e)
+            )
+        })
+    } else if let Ok(layout) = std::str::from_utf8(&bytes) {
+        Board::from_layout_with_config(layout, args.get_game_config()).map_err(|e| {
+            format!(
+                "Invalid layout {}: {}",
+                path.display(),
+                UserFacingError::from(e)
+            )
+        })
+    } else {
+        load_game(path)
+    }
+}
+
+/// Reads a pack, checking its signature first if `pack play` was given a key.
+#[cfg_attr(not(feature = "signing"), allow(unused_variables))]
+fn read_pack(args: &Args, path: &Path) -> Result<Pack, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    #[cfg(feature = "signing")]
+    if let Some(key) = args.get_pack_key() {
+        check_signature(path, &bytes, key)?;
+    }
+    Pack::parse(&String::from_utf8_lossy(&bytes)).map_err(|e| {
+        format!(
+            "Invalid pack {}: {}",
+            path.display(),
+            UserFacingError::from(e)
+        )
+    })
+}
+
+fn list_pack(args: &Args, path: &Path) {
+    let pack = match read_pack(args, path) {
+        Ok(pack) => pack,
+        Err(message) => {
+            println!("{message}");
+            std::process::exit(1);
+        }
+    };
+    println!("{}", pack.name);
+    if let Some(author) = &pack.author {
+        println!("by {author}");
+    }
+    if let Some(description) = &pack.description {
+        println!("{description}");
+    }
+    for (i, puzzle) in pack.puzzles.iter().enumerate() {
+        let board = puzzle.board().expect("checked when the pack was read");
+        println!(
+            "{:>3}. {} ({}x{}, {} mines)",
+            i + 1,
+            puzzle.title,
+            board.cols,
+            board.rows,
+            board.nr_mines
+        );
+    }
+}
+
+/// The board of puzzle `index` of the pack, counted from 1, with the rules of `args`.
+fn pack_puzzle(args: &Args, path: &Path, index: usize) -> Board {
+    let pack = read_pack(args, path).unwrap_or_else(|message| {
+        println!("{message}");
+        std::process::exit(1);
+    });
+    let Some(puzzle) = index.checked_sub(1).and_then(|i| pack.puzzles.get(i)) else {
+        println!(
+            "{} has puzzles 1 to {}, not {index}",
+            pack.name,
+            pack.puzzles.len()
+        );
+        std::process::exit(1);
+    };
+    println!(
+        "{}, puzzle {index} of {}: {}",
+        pack.name,
+        pack.puzzles.len(),
+        puzzle.title
+    );
+    puzzle
+        .board_with(&args.get_game_config())
+        .expect("checked when the pack was read")
+}
+
+/// The file holding the signature of `path`, `path` with `.sig` appended.
+#[cfg(feature = "signing")]
+fn signature_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".sig");
+    PathBuf::from(name)
+}
+
+#[cfg(feature = "signing")]
+fn check_signature(path: &Path, content: &[u8], key: &str) -> Result<(), String> {
+    let sig_path = signature_path(path);
+    let text = std::fs::read_to_string(&sig_path)
+        .map_err(|e| format!("Could not read {}: {e}", sig_path.display()))?;
+    Signature::parse(&text)
+        .and_then(|signature| signature.verify(content, key))
+        .map_err(|e| {
+            format!(
+                "Signature of {} rejected: {}",
+                path.display(),
+                UserFacingError::from(e)
+            )
+        })
+}
+
+#[cfg(feature = "signing")]
+fn keygen(path: &Path) {
+    let key = SigningKey::generate();
+    // never overwrite a key content was already signed with
+    let written = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)
+        .and_then(|mut file| writeln!(file, "{}", key.to_code()));
+    if let Err(e) = written {
+        println!("Could not write {}: {e}", path.display());
+        std::process::exit(1);
+    }
+    println!("Public key: {}", key.public_key());
+}
+
+#[cfg(feature = "signing")]
+fn sign(path: &Path, secret: &Path) {
+    let signed = std::fs::read_to_string(secret)
+        .map_err(|e| format!("Could not read {}: {e}", secret.display()))
+        .and_then(|code| {
+            SigningKey::from_code(&code).map_err(|e| {
+                format!(
+                    "Invalid key {}: {}",
+                    secret.display(),
+                    UserFacingError::from(e)
+                )
+            })
+        })
+        .and_then(|key| {
+            let content = std::fs::read(path)
+                .map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+            let sig_path = signature_path(path);
+            std::fs::write(&sig_path, key.sign(&content).to_signature_string())
+                .map_err(|e| format!("Could not write {}: {e}", sig_path.display()))?;
+            Ok(sig_path)
+        });
+    match signed {
+        Ok(sig_path) => println!("Signed {} into {}", path.display(), sig_path.display()),
+        Err(message) => {
+            println!("{message}");
+            std::process::exit(1);
+        }
+    }
+}
+
+#[cfg(feature = "signing")]
+fn verify(path: &Path, key: &str) {
+    let checked = std::fs::read(path)
+        .map_err(|e| format!("Could not read {}: {e}", path.display()))
+        .and_then(|content| check_signature(path, &content, key));
+    match checked {
+        Ok(()) => println!("The signature of {} holds", path.display()),
+        Err(message) => {
+            println!("{message}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Reads a file written by the `save` command, or holding a replay code.
+fn read_replay(path: &Path) -> Result<Replay, String> {
+    let bytes =
+        std::fs::read(path).map_err(|e| format!("Could not read {}: {e}", path.display()))?;
+    let from_code = std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|code| codec::from_share_code(code).ok())
+        .and_then(|bytes| codec::decode_replay(&bytes).ok());
+    if let Some(replay) = from_code {
+        return Ok(replay);
+    }
+    codec::decode_replay(&bytes).map_err(|e| {
+        format!(
+            "Invalid saved game {}: {}",
+            path.display(),
+            UserFacingError::from(e)
+        )
+    })
+}
+
+/// Prints the anticheat report of every replay, exiting with 1 if one is
+/// flagged and 3 if one can't be read.
+fn audit(paths: &[PathBuf], sensitivity: &Sensitivity) {
+    let mut code = 0;
+    for path in paths {
+        println!("{}", path.display());
+        let report = read_replay(path).and_then(|replay| {
+            anticheat::analyze(&replay, sensitivity).map_err(|e| {
+                format!(
+                    "Invalid replay {}: {}",
+                    path.display(),
+                    UserFacingError::from(e)
+                )
+            })
+        });
+        match report {
+            Ok(report) => {
+                println!("{}", report.summary());
+                if report.flagged() {
+                    code = code.max(1);
+                }
+            }
+            Err(message) => {
+                println!("{message}");
+                code = 3;
+            }
+        }
+    }
+    std::process::exit(code);
+}
+
+/// Prints the board after every move of a recorded game, waiting `delay`
+/// between the moves.
+fn play_back(args: &Args, path: &Path) {
+    let replay = match read_replay(path) {
+        Ok(replay) => replay,
+        Err(message) => {
+            println!("{message}");
+            std::process::exit(1);
+        }
+    };
+    let style = style(args);
+    println!(
+        "Rows: {}, Cols: {}, Mines: {}, Seed: {}",
+        replay.rows, replay.cols, replay.nr_mines, replay.seed
+    );
+    let total = replay.actions.len();
+    for (i, (action, board)) in replay.actions.iter().zip(replay.steps()).enumerate() {
+        if i > 0 {
+            if let Some(delay) = args.get_delay() {
+                std::thread::sleep(delay);
+            }
+        }
+        let board = match board {
+            Ok(board) => board,
+            Err(e) => {
+                println!("{}", UserFacingError::from(e));
+                std::process::exit(1);
+            }
+        };
+        println!(
+            "Move {}/{total} at {:.3}s: {:?}\n{}",
+            i + 1,
+            action.elapsed.as_secs_f64(),
+            action.mv,
+            style.render(&board)
+        );
+        if !board.ongoing() {
+            let result = if board.lost() { "lost" } else { "won" };
+            println!(
+                "The game was {result} after {:.3}s.",
+                action.elapsed.as_secs_f64()
+            );
+        }
+    }
+}
+
+/// Places the mines around the first click, which has to be on the board.
+fn first_click(
+    args: &Args,
+    board: &mut Board,
+    pos: Position,
+    seed: Option<u64>,
+) -> Result<(), OpenError> {
+    if !board.contains(pos) {
+        return Err(OpenError::OutOfBounds);
+    }
+    if !args.get_no_guess() && !board.guess_free() {
+        board.init_mines(pos, seed)
+    } else if generate_no_guess(board, pos, seed).is_err() {
+        println!("No guess-free board found, generating a regular one.");
+        board.init_mines(pos, seed)
+    }
+    if !board.config.treasures.is_empty() {
+        println!("Treasures: {:?}", board.config.treasures);
+    }
+    Ok(())
+}
+
+/// No-guess generation that reports the attempts per second on stderr once it
+/// takes longer than a second.
+fn generate_no_guess(
+    board: &mut Board,
+    pos: Position,
+    seed: Option<u64>,
+) -> Result<(), GenerationError> {
+    let started = Instant::now();
+    let mut reported = Duration::ZERO;
+    let result = board.init_mines_no_guess_with(pos, seed, |progress| {
+        let elapsed = started.elapsed();
+        if elapsed >= reported + Duration::from_secs(1) {
+            reported = elapsed;
+            let rate = progress.attempts as f64 / elapsed.as_secs_f64();
+            eprint!(
+                "\rGenerating: {} attempts, {rate:.0}/s, {:.0}% of the limit",
+                progress.attempts,
+                progress.fraction() * 100.0
+            );
+        }
+        true
+    });
+    if reported > Duration::ZERO {
+        eprintln!();
+    }
+    result
+}
+
+/// Plays a move file and exits with the result, see `--moves`.
+fn play_moves(args: &Args, path: &Path) -> ! {
+    let source = match path.to_str() {
+        Some("-") => io::read_to_string(io::stdin()),
+        _ => std::fs::read_to_string(path),
+    };
+    let moves = match source.map(|source| command::parse_moves(&source)) {
+        Ok(Ok(moves)) => moves,
+        Ok(Err((line, e))) => {
+            println!("Line {line}: {}", UserFacingError::from(e));
+            std::process::exit(3);
+        }
+        Err(e) => {
+            println!("Could not read {}: {e}", path.display());
+            std::process::exit(3);
+        }
+    };
+    let style = style(args);
+    let mut board = match args.get_load() {
+        Some(saved) => load_game(saved).unwrap_or_else(|message| {
+            println!("{message}");
+            std::process::exit(3);
+        }),
+        None => new_board(args),
+    };
+    board.config.min_move_interval = None;
+    for (line, mv) in moves {
+        if !board.ongoing() && board.initialized() {
+            println!("The game ended before line {line}, ignoring the remaining moves.");
+            break;
+        }
+        match (board.initialized(), mv) {
+            (false, Move::Open(pos)) => {
+                if let Err(e) = first_click(args, &mut board, pos, args.get_seed()) {
+                    println!("Line {line}: {}", UserFacingError::from(e));
+                }
+            }
+            (false, _) => println!("Line {line}: {}", UserFacingError::new("mines_not_placed")),
+            (true, _) => {
+                if let Err(e) = board.play(mv) {
+                    println!("Line {line}: {}", UserFacingError::from(e));
+                }
+            }
+        }
+    }
+    print!("{}", style.render(&board));
+    let time = board.elapsed().as_secs_f64();
+    let (result, code) = match board.state {
+        GameState::Won => ("won", 0),
+        GameState::Lost => ("lost", 1),
+        GameState::Init | GameState::OnGoing => ("unfinished", 2),
+    };
+    println!("Result: {result}, time: {time:.3}s");
+    if let Some(seed) = board.seed() {
+        println!("Seed: {seed}");
+    }
+    std::process::exit(code);
+}
+
+fn solve(args: &Args) {
+    let style = style(args);
+    let mut board = new_board(args);
+    board.config.min_move_interval = None;
+    println!(
+        "Rows: {}, Cols: {}, Mines: {}",
+        board.rows, board.cols, board.nr_mines
+    );
+    let (mut deductions, mut guesses) = (0, 0);
+    while board.ongoing() || !board.initialized() {
+        let Some((mv, reason)) = SolverStrategy.decide(&board.view()) else {
+            println!("The solver found no move.");
+            break;
+        };
+        if board.initialized() {
+            if let Some(delay) = args.get_delay() {
+                std::thread::sleep(delay);
+            }
+        }
+        let (Move::Open((x, y)) | Move::Flag((x, y)) | Move::Chord((x, y))) = mv;
+        let description = match reason {
+            Reason::FirstClick => format!("Opening ({x},{y}) to start"),
+            Reason::Mine => format!("({x},{y}) is a mine, flagging it"),
+            Reason::Safe => format!("({x},{y}) is safe, opening it"),
+            Reason::Guess(p) => format!(
+                "Nothing is certain, guessing ({x},{y}) with a {:.1}% chance of a mine",
+                p * 100.0
+            ),
+        };
+        match reason {
+            Reason::FirstClick => {}
+            Reason::Mine | Reason::Safe => deductions += 1,
+            Reason::Guess(_) => guesses += 1,
+        }
+        if board.initialized() {
+            if let Err(e) = board.play(mv) {
+                println!("{}", UserFacingError::from(e));
+                break;
+            }
+        } else if let Err(e) = first_click(args, &mut board, (x, y), args.get_seed()) {
+            println!("{}", UserFacingError::from(e));
+            break;
+        }
+        println!("{description}:\n{}", style.render(&board));
+    }
+    let time = board.elapsed().as_secs_f64();
+    match board.state {
+        GameState::Won => println!("The solver won! Time: {time:.3}s"),
+        GameState::Lost => println!("The solver lost. Time: {time:.3}s"),
+        GameState::Init | GameState::OnGoing => println!("The solver got stuck."),
+    }
+    println!("Deductions: {deductions}, guesses: {guesses}");
+    if let Some(metrics) = board.metrics() {
+        println!("3BV: {}", metrics.bbbv);
+    }
+}
+
+/// A board with a generated layout, or the layout of `--load-mbf` if given.
+fn new_board(args: &Args) -> Board {
+    if let Some((path, index)) = args.get_pack_puzzle() {
+        return pack_puzzle(args, path, index);
+    }
+    let Some(path) = args.get_load_mbf() else {
+        return Board::with_config(
+            args.get_rows(),
+            args.get_cols(),
+            args.get_mines(),
+            args.get_game_config(),
+        );
+    };
+    let loaded = std::fs::read(path)
+        .map_err(|e| format!("Could not read {}: {e}", path.display()))
+        .and_then(|bytes| {
+            Board::from_mbf_with_config(&bytes, args.get_game_config()).map_err(|e| {
+                format!(
+                    "Invalid MBF file {}: {}",
+                    path.display(),
+                    UserFacingError::from(e)
+                )
+            })
+        });
+    match loaded {
+        Ok(board) => board,
+        Err(message) => {
+            println!("{message}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn bench(args: &Args, boards: usize) {
+    let (rows, cols, mines) = (args.get_rows(), args.get_cols(), args.get_mines());
+    println!("{boards} boards of {rows}x{cols} with {mines} mines:");
+    let timings = bench::run(
+        rows,
+        cols,
+        mines,
+        &args.get_game_config(),
+        boards,
+        args.get_seed().unwrap_or(0),
+    );
+    let report = |name: &str, total: Duration| {
+        println!(
+            "{name:<12}{:>10.3}ms total {:>10.3}ms per board",
+            total.as_secs_f64() * 1000.0,
+            timings.average(total).as_secs_f64() * 1000.0
+        );
+    };
+    report("Generation", timings.generation);
+    report("Flood fill", timings.flood_fill);
+    report("Solver", timings.solver);
+    println!(
+        "Flood fills opened {} cells, {:.0} per board",
+        timings.flood_cells,
+        timings.flood_cells as f64 / boards.max(1) as f64
+    );
+}
+
+fn test_randomness(args: &Args, start: Position, samples: usize) {
+    let board = Board::with_config(
+        args.get_rows(),
+        args.get_cols(),
+        args.get_mines(),
+        args.get_game_config(),
+    );
+    let report = |name: &str, result: &Uniformity| {
+        println!(
+            "{name}: chi-squared {:.1} with {} degrees of freedom, z = {:.2}, {}",
+            result.chi_squared,
+            result.degrees_of_freedom,
+            result.z_score,
+            if result.passes() { "ok" } else { "FAILED" }
+        );
+        result.passes()
+    };
+    let frequencies = randomness::cell_frequencies(&board, start, samples);
+    println!("Mine frequency per cell over {samples} layouts:");
+    for row in frequencies.iter() {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|&count| format!("{:5.1}%", 100.0 * count as f64 / samples as f64))
+            .collect();
+        println!("{}", cells.join(" "));
+    }
+    let mut passed = match randomness::cell_uniformity(&frequencies, &board, start) {
+        Some(result) => report("Cell frequencies", &result),
+        None => {
+            println!("Cell frequencies: FAILED, a mine was placed in the safe area");
+            false
+        }
+    };
+    // every layout should be expected at least five times for the test to hold
+    match randomness::layout_count(&board, start) {
+        Some(layouts) if layouts * 5 <= samples as u128 => {
+            let result = randomness::layout_uniformity(&board, start, samples);
+            passed &= report("Layouts", &result);
+        }
+        _ => {
+            println!("Layouts: skipped, there are too many possible layouts for {samples} samples")
+        }
+    }
+    if !passed {
+        std::process::exit(1);
+    }
+}
+
+fn diff(args: &Args, path_a: &Path, path_b: &Path) {
+    let (a, b) = match (load_any(args, path_a), load_any(args, path_b)) {
+        (Ok(a), Ok(b)) => (a, b),
+        (Err(message), _) | (_, Err(message)) => {
+            println!("{message}");
+            std::process::exit(1);
+        }
+    };
+    let (name_a, name_b) = (path_a.display(), path_b.display());
+    let Ok(diff) = a.diff(&b) else {
+        println!(
+            "{name_a} is {}x{} but {name_b} is {}x{}, only boards of the same size can be compared.",
+            a.cols, a.rows, b.cols, b.rows
+        );
+        std::process::exit(1);
+    };
+    if diff.is_empty() {
+        println!("{name_a} and {name_b} hold identical boards.");
+        return;
+    }
+    println!("Board in {name_a}: \n{a:?}");
+    println!("Board in {name_b}: \n{b:?}");
+    println!("Mines only in {name_a}: {:?}", diff.mines_removed);
+    println!("Mines only in {name_b}: {:?}", diff.mines_added);
+    println!("Differing cells:");
+    for (pos, sa, sb) in diff.cells {
+        println!("  {pos:?}: {sa:?} -> {sb:?}");
+    }
+}
+
+/// Plays the rounds of a drill until it is complete or the input ends.
+fn practice(args: &Args, name: Option<&str>) {
+    let Some(drill) = name.and_then(drill::find) else {
+        if let Some(name) = name {
+            println!("Unknown drill: {name}");
+        }
+        for drill in drill::DRILLS {
+            println!("{:<12}{}", drill.name, drill.description);
+        }
+        std::process::exit(if name.is_some() { 1 } else { 0 });
+    };
+    let style = style(args);
+    let mut session = Session::new(drill, args.get_seed());
+    let mut board = session.next_board();
+    println!("{}\nRound 1: \n{}", drill.description, style.render(&board));
+    while !session.is_complete() {
+        println!("Enter coordinate to open (int,int), flag (int,int), chord (int,int) or hint: ");
+        let mut line = String::new();
+        let read = io::stdin()
+            .read_line(&mut line)
+            .expect("Failed to read line");
+        if read == 0 {
+            println!("End of input, quitting.");
+            break;
+        }
+        let result = match line.parse::<GameCommand>() {
+            Ok(GameCommand::Open(pos)) => board.open(pos).map_err(UserFacingError::from),
+            Ok(GameCommand::Flag(pos)) => board.flag(pos).map_err(UserFacingError::from),
+            Ok(GameCommand::Chord(pos)) => board.chord(pos).map_err(UserFacingError::from),
+            Ok(GameCommand::Hint) => {
+                println!("{}", command::hint_message(board.request_hint().as_ref()));
+                continue;
+            }
+            Ok(_) => {
+                println!("Not available in drills, try again.");
+                continue;
+            }
+            Err(e) => Err(e.into()),
+        };
+        if let Err(e) = result {
+            println!("{e}");
+            continue;
+        }
+        println!("Current board: \n{}", style.render(&board));
+        if let Some(passed) = session.update(&board) {
+            let time = board.elapsed().as_secs_f64();
+            println!(
+                "Round {}, {:.3}s: {}/{} passed, {} failed",
+                if passed { "passed" } else { "failed" },
+                time,
+                session.passed,
+                drill.rounds,
+                session.failed
+            );
+            if !session.is_complete() {
+                board = session.next_board();
+                println!(
+                    "Round {}: \n{}",
+                    session.passed + session.failed + 1,
+                    style.render(&board)
+                );
+            }
+        }
+    }
+    if session.is_complete() {
+        println!("Drill complete, {} rounds failed.", session.failed);
+    }
+    if let Some(best) = session.best {
+        println!("Fastest round: {:.3}s", best.as_secs_f64());
+    }
+}
+
+fn selftest() {
+    let mut failed = false;
+    for case in perft::CASES {
+        print!(
+            "{}x{}, {} mines, {:?}, depth {}: ",
+            case.rows, case.cols, case.nr_mines, case.win_condition, case.depth
+        );
+        match case.run() {
+            Ok(totals) if totals == case.expected => println!("ok {totals:?}"),
+            Ok(totals) => {
+                failed = true;
+                println!("FAILED, expected {:?}, got {totals:?}", case.expected);
+            }
+            Err(e) => {
+                failed = true;
+                println!("FAILED, {e:?}");
+            }
+        }
+    }
+    if failed {
+        std::process::exit(1);
     }
 }
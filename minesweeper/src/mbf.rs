@@ -0,0 +1,149 @@
+//! The Minesweeper Board File format used by Minesweeper Arbiter and other
+//! tools: width and height as one byte each, the mine count as a big endian
+//! `u16`, then one `(x, y)` byte pair per mine.
+
+use crate::board::{Board, Grid, Mask, Position};
+use crate::config::GameConfig;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MbfError {
+    UnexpectedEnd,
+    TrailingBytes,
+    /// A zero width or height, or no room for a safe cell.
+    InvalidSize,
+    MineOutOfBounds(Position),
+    DuplicateMine(Position),
+    /// Only boards up to 255x255 with at most 65535 mines can be written.
+    TooLarge,
+    /// The mines are placed on the first click, there is no layout to write yet.
+    MinesNotInit,
+    /// The format has no anti-mines.
+    AntiMines,
+}
+
+impl Board {
+    /// The mine layout as an MBF file.
+    pub fn to_mbf(&self) -> Result<Vec<u8>, MbfError> {
+        let mines = self.mines.as_ref().ok_or(MbfError::MinesNotInit)?;
+        if !self.anti_mines.is_empty() {
+            return Err(MbfError::AntiMines);
+        }
+        let (Ok(cols), Ok(rows), Ok(count)) = (
+            u8::try_from(self.cols),
+            u8::try_from(self.rows),
+            u16::try_from(mines.len()),
+        ) else {
+            return Err(MbfError::TooLarge);
+        };
+        let mut bytes = vec![cols, rows];
+        bytes.extend(count.to_be_bytes());
+        for (x, y) in mines.iter() {
+            // positions are within the board, so they fit as well
+            bytes.extend([x as u8, y as u8]);
+        }
+        Ok(bytes)
+    }
+
+    /// A new game on the layout of an MBF file. The first click can be anywhere,
+    /// including on a mine; the board has no seed, so no replay.
+    pub fn from_mbf(bytes: &[u8]) -> Result<Board, MbfError> {
+        Board::from_mbf_with_config(bytes, GameConfig::default())
+    }
+
+    /// The layout of an MBF file played by the rules of `config`.
+    pub fn from_mbf_with_config(bytes: &[u8], config: GameConfig) -> Result<Board, MbfError> {
+        let [cols, rows, high, low, rest @ ..] = bytes else {
+            return Err(MbfError::UnexpectedEnd);
+        };
+        let (cols, rows) = (*cols as usize, *rows as usize);
+        let count = u16::from_be_bytes([*high, *low]) as usize;
+        if cols == 0 || rows == 0 || count >= rows * cols {
+            return Err(MbfError::InvalidSize);
+        }
+        if rest.len() < count * 2 {
+            return Err(MbfError::UnexpectedEnd);
+        }
+        if rest.len() > count * 2 {
+            return Err(MbfError::TrailingBytes);
+        }
+        let mut mines = Mask::new(Grid::new(rows, cols));
+        for pair in rest.chunks_exact(2) {
+            let pos = (pair[0] as usize, pair[1] as usize);
+            if pos.0 >= cols || pos.1 >= rows {
+                return Err(MbfError::MineOutOfBounds(pos));
+            }
+            if !mines.insert(pos) {
+                return Err(MbfError::DuplicateMine(pos));
+            }
+        }
+        let mut board = Board::with_config(rows, cols, count, config);
+        board.mines = Some(mines);
+        Ok(board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mbf_round_trip() {
+        let mut board = Board::new(9, 16, 10);
+        assert_eq!(board.to_mbf(), Err(MbfError::MinesNotInit));
+        board.init_mines((0, 0), Some(1));
+        let bytes = board.to_mbf().unwrap();
+        assert_eq!(bytes[..4], [16, 9, 0, 10]);
+        assert_eq!(bytes.len(), 4 + 2 * 10);
+
+        let mut loaded = Board::from_mbf(&bytes).unwrap();
+        assert!(!loaded.initialized());
+        assert_eq!(loaded.to_mbf(), Ok(bytes.clone()));
+        loaded.init_mines((0, 0), Some(2));
+        assert_eq!(loaded.get_board_state(), board.get_board_state());
+        assert_eq!(loaded.seed(), None);
+        assert_eq!(loaded.to_mbf(), Ok(bytes));
+    }
+
+    #[test]
+    fn test_mbf_first_click_on_mine() {
+        let mut board = Board::from_mbf(&[3, 2, 0, 1, 2, 1]).unwrap();
+        board.init_mines((2, 1), None);
+        assert!(board.lost());
+        assert_eq!(board.reveal_mines(), Some(vec![(2, 1)]));
+    }
+
+    #[test]
+    fn test_mbf_errors() {
+        assert_eq!(
+            Board::from_mbf(&[3, 3, 0]).err(),
+            Some(MbfError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Board::from_mbf(&[3, 3, 0, 1, 0]).err(),
+            Some(MbfError::UnexpectedEnd)
+        );
+        assert_eq!(
+            Board::from_mbf(&[3, 3, 0, 1, 0, 0, 0]).err(),
+            Some(MbfError::TrailingBytes)
+        );
+        assert_eq!(
+            Board::from_mbf(&[0, 3, 0, 0]).err(),
+            Some(MbfError::InvalidSize)
+        );
+        assert_eq!(
+            Board::from_mbf(&[1, 1, 0, 1, 0, 0]).err(),
+            Some(MbfError::InvalidSize)
+        );
+        assert_eq!(
+            Board::from_mbf(&[3, 3, 0, 1, 3, 0]).err(),
+            Some(MbfError::MineOutOfBounds((3, 0)))
+        );
+        assert_eq!(
+            Board::from_mbf(&[3, 3, 0, 2, 1, 1, 1, 1]).err(),
+            Some(MbfError::DuplicateMine((1, 1)))
+        );
+        let mut wide = Board::new(9, 300, 10);
+        wide.init_mines((0, 0), Some(1));
+        assert_eq!(wide.to_mbf(), Err(MbfError::TooLarge));
+    }
+}
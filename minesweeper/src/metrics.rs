@@ -0,0 +1,291 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use minesweeper_solver::grid::{Grid, Mask};
+
+use crate::board::Position;
+
+/// Difficulty measures of a mine layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metrics {
+    /// Bechtel's Board Benchmark Value: the minimum number of clicks needed to
+    /// open every safe cell without flagging.
+    pub bbbv: usize,
+    /// Connected areas of zero cells, each cleared by a single click.
+    pub openings: usize,
+    /// Connected groups of numbered cells that border no opening.
+    pub islands: usize,
+}
+
+impl Metrics {
+    pub fn new(rows: usize, cols: usize, mines: &HashSet<Position>) -> Metrics {
+        Metrics::with_grid(Grid::new(rows, cols), mines)
+    }
+
+    /// Metrics on `grid`, which may wrap around its edges.
+    pub fn with_grid(grid: Grid, mines: &HashSet<Position>) -> Metrics {
+        Metrics::with_neighbors(grid, mines, |pos| grid.neighbors(pos).collect())
+    }
+
+    /// Metrics on a board of the size of `grid` whose cells touch as `neighbors`
+    /// says, for example through wormholes.
+    pub fn with_neighbors(
+        grid: Grid,
+        mines: &HashSet<Position>,
+        neighbors: impl Fn(Position) -> Vec<Position>,
+    ) -> Metrics {
+        let clicks = Clicks::with_neighbors(grid, mines, &neighbors);
+        let isolated = Mask::from_positions(grid, clicks.isolated.iter().copied());
+        Metrics {
+            bbbv: clicks.bbbv(),
+            openings: clicks.openings(),
+            islands: components(&isolated, &neighbors).len(),
+        }
+    }
+}
+
+/// A quick difficulty estimate of a started game from the mine counts alone,
+/// without flood fills or the solver. No-guess generation runs it on every
+/// candidate layout to skip the solver on the hopeless ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    /// Share of the cells that hold a mine.
+    pub density: f64,
+    /// Cells open so far, right after the first click the size of its opening.
+    pub opened: usize,
+    /// Share of the safe cells without a mine around them. The larger it is,
+    /// the larger the openings a click can expect.
+    pub zero_share: f64,
+    /// Lower and upper bound of the 3BV: the numbers no opening reveals plus
+    /// at least one and at most every zero cell.
+    pub bbbv_bounds: (usize, usize),
+}
+
+impl Estimate {
+    /// The first click revealed only its own number while the layout needs more
+    /// than one click, which almost never plays out without a guess.
+    pub fn needs_guess(&self) -> bool {
+        self.opened == 1 && self.bbbv_bounds.0 > 1
+    }
+}
+
+/// The clicks counted by 3BV: one per opening and one per numbered cell that
+/// no opening reveals.
+#[derive(Debug, Clone)]
+pub struct Clicks {
+    /// Zero cells of each opening.
+    openings: Vec<Vec<Position>>,
+    isolated: Vec<Position>,
+}
+
+impl Clicks {
+    pub fn with_neighbors(
+        grid: Grid,
+        mines: &HashSet<Position>,
+        neighbors: impl Fn(Position) -> Vec<Position>,
+    ) -> Clicks {
+        let count = |pos: Position| neighbors(pos).iter().filter(|n| mines.contains(n)).count();
+        let safe: Vec<Position> = grid.positions().filter(|p| !mines.contains(p)).collect();
+        let zeros = Mask::from_positions(grid, safe.iter().copied().filter(|&p| count(p) == 0));
+        // numbered cells that are not opened by the cascade of any opening
+        let isolated = safe
+            .iter()
+            .copied()
+            .filter(|&p| count(p) > 0)
+            .filter(|&p| !neighbors(p).iter().any(|&n| zeros.contains(n)))
+            .collect();
+        Clicks {
+            openings: components(&zeros, &neighbors),
+            isolated,
+        }
+    }
+
+    pub fn bbbv(&self) -> usize {
+        self.openings.len() + self.isolated.len()
+    }
+
+    pub fn openings(&self) -> usize {
+        self.openings.len()
+    }
+
+    /// The openings and the 3BV cleared once the cells in `opened` are open.
+    pub fn progress(&self, opened: impl Fn(Position) -> bool) -> (usize, usize) {
+        let openings = self
+            .openings
+            .iter()
+            .filter(|cells| cells.iter().any(|&pos| opened(pos)))
+            .count();
+        let isolated = self.isolated.iter().filter(|&&pos| opened(pos)).count();
+        (openings, openings + isolated)
+    }
+}
+
+/// Share of the 3BV cleared at which the endgame starts.
+pub const ENDGAME_PROGRESS: f64 = 0.75;
+
+/// Time spent in each phase of a game. A phase the game never got to is zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PhaseSplits {
+    /// From the start until the first opening is cleared.
+    pub first_opening: Duration,
+    /// From the first opening until `ENDGAME_PROGRESS` of the 3BV is cleared.
+    pub mid_game: Duration,
+    /// The rest of the game.
+    pub endgame: Duration,
+}
+
+impl PhaseSplits {
+    /// Splits a game by the elapsed time of every move, the openings and the 3BV
+    /// cleared after it, on a layout with `bbbv` 3BV. Layouts without an opening
+    /// start in the mid game.
+    pub fn new(moves: &[(Duration, usize, usize)], bbbv: usize, has_openings: bool) -> PhaseSplits {
+        let end = moves
+            .last()
+            .map_or(Duration::ZERO, |&(elapsed, ..)| elapsed);
+        let first_opening = match has_openings {
+            true => moves
+                .iter()
+                .find(|&&(_, openings, _)| openings > 0)
+                .map_or(end, |&(elapsed, ..)| elapsed),
+            false => Duration::ZERO,
+        };
+        let endgame_start = moves
+            .iter()
+            .find(|&&(_, _, cleared)| cleared as f64 >= bbbv as f64 * ENDGAME_PROGRESS)
+            .map_or(end, |&(elapsed, ..)| elapsed)
+            .max(first_opening);
+        PhaseSplits {
+            first_opening,
+            mid_game: endgame_start - first_opening,
+            endgame: end - endgame_start,
+        }
+    }
+}
+
+/// Connected components of `cells`.
+fn components(cells: &Mask, neighbors: &impl Fn(Position) -> Vec<Position>) -> Vec<Vec<Position>> {
+    let mut seen = Mask::new(cells.grid());
+    let mut found = Vec::new();
+    for start in cells.iter() {
+        if seen.contains(start) {
+            continue;
+        }
+        seen.insert(start);
+        let mut component = vec![start];
+        let mut stack = vec![start];
+        while let Some(pos) = stack.pop() {
+            for n in neighbors(pos) {
+                if cells.contains(n) && !seen.contains(n) {
+                    seen.insert(n);
+                    component.push(n);
+                    stack.push(n);
+                }
+            }
+        }
+        found.push(component);
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    #[test]
+    fn test_single_opening() {
+        let mines = HashSet::from([(2, 2)]);
+        let metrics = Metrics::new(3, 3, &mines);
+        assert_eq!(
+            metrics,
+            Metrics {
+                bbbv: 1,
+                openings: 1,
+                islands: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_islands() {
+        let mines = HashSet::from([(1, 0)]);
+        let metrics = Metrics::new(1, 3, &mines);
+        assert_eq!(
+            metrics,
+            Metrics {
+                bbbv: 2,
+                openings: 0,
+                islands: 2
+            }
+        );
+    }
+
+    #[test]
+    fn test_board_metrics() {
+        assert_eq!(Board::new(9, 9, 10).metrics(), None);
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        let metrics = board.metrics().unwrap();
+        assert!(metrics.openings >= 1);
+        assert!(metrics.bbbv >= metrics.openings + metrics.islands);
+    }
+
+    #[test]
+    fn test_estimate() {
+        let estimate = |opened, bbbv_bounds| Estimate {
+            density: 0.2,
+            opened,
+            zero_share: 0.5,
+            bbbv_bounds,
+        };
+        assert!(estimate(1, (5, 12)).needs_guess());
+        assert!(!estimate(14, (5, 12)).needs_guess());
+        // a first click that is the only click
+        assert!(!estimate(1, (1, 1)).needs_guess());
+    }
+
+    #[test]
+    fn test_clicks_progress() {
+        // one opening and the isolated numbers at (0,2) and (2,2)
+        //   0 0 2 *
+        //   1 1 4 *
+        //   1 * 3 *
+        let mines = HashSet::from([(3, 0), (3, 1), (1, 2), (3, 2)]);
+        let grid = Grid::new(3, 4);
+        let clicks = Clicks::with_neighbors(grid, &mines, |pos| grid.neighbors(pos).collect());
+        assert_eq!(clicks.bbbv(), Metrics::new(3, 4, &mines).bbbv);
+        assert_eq!(clicks.progress(|_| false), (0, 0));
+        assert_eq!(clicks.progress(|pos| pos == (1, 0)), (1, 1));
+        assert_eq!(clicks.progress(|pos| pos == (2, 2)), (0, 1));
+        assert_eq!(clicks.progress(|_| true), (1, clicks.bbbv()));
+    }
+
+    #[test]
+    fn test_phase_splits() {
+        let s = Duration::from_secs;
+        // (elapsed, openings, 3BV cleared) of a game on a layout with 8 3BV
+        let moves = [
+            (s(0), 0, 1),
+            (s(3), 1, 2),
+            (s(10), 2, 5),
+            (s(12), 3, 6),
+            (s(20), 3, 8),
+        ];
+        let phases = PhaseSplits::new(&moves, 8, true);
+        assert_eq!(
+            phases,
+            PhaseSplits {
+                first_opening: s(3),
+                mid_game: s(9),
+                endgame: s(8),
+            }
+        );
+        let no_openings = PhaseSplits::new(&moves, 8, false);
+        assert_eq!(no_openings.first_opening, s(0));
+        assert_eq!(no_openings.mid_game, s(12));
+        // a game lost before the endgame never leaves the mid game
+        let lost = PhaseSplits::new(&moves[..3], 8, true);
+        assert_eq!((lost.mid_game, lost.endgame), (s(7), s(0)));
+    }
+}
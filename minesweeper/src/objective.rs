@@ -0,0 +1,145 @@
+//! Win objectives, what a game has to reach to be won. The classic rules are
+//! the `WinCondition` of the config; a variant can pick another objective with
+//! `Variant::objective`, so modes define their own completion criteria without
+//! touching the engine.
+
+use crate::board::{Mask, Position};
+use crate::config::WinCondition;
+#[cfg(test)]
+use crate::{board::GameState, config::GameConfig};
+
+/// What the player achieved so far, checked after every move.
+#[derive(Debug, Clone, Copy)]
+pub struct Progress<'a> {
+    pub rows: usize,
+    pub cols: usize,
+    pub nr_mines: usize,
+    pub mines: &'a Mask,
+    pub opened: &'a Mask,
+    pub flagged: &'a Mask,
+    pub treasures: &'a [Position],
+}
+
+impl Progress<'_> {
+    pub fn safe_cells(&self) -> usize {
+        self.rows * self.cols - self.nr_mines
+    }
+}
+
+pub trait Objective: Send + Sync {
+    fn description(&self) -> String;
+
+    /// Whether `progress` wins the game. Only asked while no mine was opened.
+    fn reached(&self, progress: &Progress<'_>) -> bool;
+}
+
+impl Objective for WinCondition {
+    fn description(&self) -> String {
+        match self {
+            WinCondition::FlagAllMines => "Flag every mine and open every other cell",
+            WinCondition::OpenAllSafe => "Open every safe cell",
+        }
+        .to_string()
+    }
+
+    fn reached(&self, progress: &Progress<'_>) -> bool {
+        let all_safe_open = progress.opened.len() == progress.safe_cells();
+        match self {
+            WinCondition::OpenAllSafe => all_safe_open,
+            WinCondition::FlagAllMines => all_safe_open && progress.flagged == progress.mines,
+        }
+    }
+}
+
+/// Opening this many percent of the safe cells, flags don't matter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OpenPercent(pub u8);
+
+impl Objective for OpenPercent {
+    fn description(&self) -> String {
+        format!("Open {}% of the safe cells", self.0)
+    }
+
+    fn reached(&self, progress: &Progress<'_>) -> bool {
+        100 * progress.opened.len() >= usize::from(self.0.min(100)) * progress.safe_cells()
+    }
+}
+
+/// Opening every treasure cell, see `GameConfig::treasures`. Flags and the
+/// other cells don't matter, without treasures every safe cell has to be opened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Treasures;
+
+impl Objective for Treasures {
+    fn description(&self) -> String {
+        "Open every treasure cell".to_string()
+    }
+
+    fn reached(&self, progress: &Progress<'_>) -> bool {
+        // a board without treasures is won the classic way
+        if progress.treasures.is_empty() {
+            return progress.opened.len() == progress.safe_cells();
+        }
+        progress
+            .treasures
+            .iter()
+            .all(|&pos| progress.opened.contains(pos))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+    use crate::variant::{self, Variant};
+
+    /// Won halfway through, a variant defined outside the engine.
+    struct Halfway;
+
+    impl Variant for Halfway {
+        fn name(&self) -> &'static str {
+            "halfway"
+        }
+
+        fn description(&self) -> &'static str {
+            "Open half of the safe cells"
+        }
+
+        fn objective(&self) -> Option<&dyn Objective> {
+            Some(&OpenPercent(50))
+        }
+    }
+
+    #[test]
+    fn test_open_percent() {
+        variant::register(Halfway).unwrap();
+        let config = GameConfig::default().with_variant(variant::find("halfway").unwrap());
+        let mut board = Board::with_config(9, 9, 10, config);
+        assert_eq!(
+            board.objective().description(),
+            "Open 50% of the safe cells"
+        );
+        // the first click opens 18 of the 71 safe cells
+        board.init_mines((0, 0), Some(1));
+        assert!(board.ongoing());
+        board.open((3, 0)).unwrap();
+        board.open((5, 0)).unwrap();
+        board.open((6, 0)).unwrap();
+        board.open((7, 0)).unwrap();
+        let opened = board.open_fields.len();
+        assert_eq!(board.state == GameState::Won, 2 * opened >= 71);
+        assert_eq!(crate::perft::check(&board), Ok(()));
+    }
+
+    #[test]
+    fn test_win_conditions() {
+        let mut board = Board::new(2, 2, 1);
+        board.init_mines((0, 0), Some(1));
+        let progress = board.progress().unwrap();
+        assert!(!WinCondition::OpenAllSafe.reached(&progress));
+        assert!(!Treasures.reached(&progress));
+        assert!(OpenPercent(0).reached(&progress));
+        assert!(OpenPercent(33).reached(&progress));
+        assert!(!OpenPercent(34).reached(&progress));
+    }
+}
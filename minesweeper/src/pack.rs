@@ -0,0 +1,203 @@
+//! Puzzle packs: fixed layouts in the order they are meant to be played, with a
+//! name and an author, so players can share their puzzles as one file. A pack is
+//! text, header lines first and then every puzzle as a `puzzle` line with its
+//! title followed by its rows in the format of the `layout` module:
+//!
+//! ```text
+//! pack First steps
+//! author jb
+//! description Small openings to warm up with, easiest first
+//!
+//! puzzle Corner
+//! ...*
+//! ....
+//! *...
+//!
+//! puzzle Half open
+//! 001*
+//! 0011
+//! 11..
+//! *...
+//! ```
+//!
+//! `pack` is required, `author` and `description` are optional. Lines starting
+//! with `#` are comments.
+
+use crate::board::Board;
+use crate::config::GameConfig;
+use crate::layout::LayoutError;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum PackError {
+    /// There is no `pack` line.
+    MissingName,
+    /// The line with this index is neither a header nor part of a puzzle.
+    UnknownLine(usize),
+    NoPuzzles,
+    /// The puzzle with this index has an invalid layout.
+    InvalidPuzzle(usize, LayoutError),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub title: String,
+    /// The rows in the format of the `layout` module.
+    pub layout: String,
+}
+
+impl Puzzle {
+    pub fn board(&self) -> Result<Board, LayoutError> {
+        Board::from_layout_str(&self.layout)
+    }
+
+    /// The board played by the rules of the player in `config`: its win
+    /// condition, hints, assisted flags, rate limit and clock. How the cells
+    /// count stays as the layout has it, other neighbors would break its numbers.
+    pub fn board_with(&self, config: &GameConfig) -> Result<Board, LayoutError> {
+        let mut board = self.board()?;
+        board.config = GameConfig {
+            win_condition: config.win_condition,
+            first_hint: config.first_hint,
+            assisted_flags: config.assisted_flags,
+            min_move_interval: config.min_move_interval,
+            deterministic: config.deterministic,
+            ..board.config
+        };
+        Ok(board)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pack {
+    pub name: String,
+    pub author: Option<String>,
+    pub description: Option<String>,
+    /// In the recommended order.
+    pub puzzles: Vec<Puzzle>,
+}
+
+impl Pack {
+    /// Reads a pack, checking that every puzzle is a valid layout.
+    pub fn parse(text: &str) -> Result<Pack, PackError> {
+        let mut name = None;
+        let mut author = None;
+        let mut description = None;
+        let mut puzzles: Vec<Puzzle> = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+            let rest = rest.trim().to_string();
+            match (keyword, puzzles.last_mut()) {
+                ("puzzle", _) => puzzles.push(Puzzle {
+                    title: rest,
+                    layout: String::new(),
+                }),
+                ("pack", None) => name = Some(rest),
+                ("author", None) => author = Some(rest),
+                ("description", None) => description = Some(rest),
+                (_, Some(puzzle)) => {
+                    puzzle.layout.push_str(line);
+                    puzzle.layout.push('\n');
+                }
+                (_, None) => return Err(PackError::UnknownLine(i)),
+            }
+        }
+        let name = name.ok_or(PackError::MissingName)?;
+        if puzzles.is_empty() {
+            return Err(PackError::NoPuzzles);
+        }
+        for (i, puzzle) in puzzles.iter().enumerate() {
+            puzzle.board().map_err(|e| PackError::InvalidPuzzle(i, e))?;
+        }
+        Ok(Pack {
+            name,
+            author,
+            description,
+            puzzles,
+        })
+    }
+
+    /// The pack in the format of the module documentation.
+    pub fn to_pack_string(&self) -> String {
+        let mut text = format!("pack {}\n", self.name);
+        if let Some(author) = &self.author {
+            text.push_str(&format!("author {author}\n"));
+        }
+        if let Some(description) = &self.description {
+            text.push_str(&format!("description {description}\n"));
+        }
+        for puzzle in &self.puzzles {
+            text.push_str(&format!("\npuzzle {}\n{}", puzzle.title, puzzle.layout));
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::GameState;
+    use crate::config::WinCondition;
+
+    const PACK: &str = "\
+pack First steps
+author jb
+# the easy ones first
+
+puzzle Corner
+...*
+....
+*...
+
+puzzle Half open
+001*
+0011
+11..
+*...
+";
+
+    #[test]
+    fn test_parse_pack() {
+        let pack = Pack::parse(PACK).unwrap();
+        assert_eq!(pack.name, "First steps");
+        assert_eq!(pack.author.as_deref(), Some("jb"));
+        assert_eq!(pack.description, None);
+        let titles: Vec<_> = pack.puzzles.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["Corner", "Half open"]);
+
+        let corner = pack.puzzles[0].board().unwrap();
+        assert_eq!((corner.rows, corner.cols, corner.nr_mines), (3, 4, 2));
+        assert!(!corner.initialized());
+        let half_open = pack.puzzles[1].board().unwrap();
+        assert_eq!(half_open.state, GameState::OnGoing);
+        assert_eq!(half_open.open_fields.len(), 9);
+
+        let rules = GameConfig {
+            win_condition: WinCondition::OpenAllSafe,
+            wrap_edges: true,
+            ..GameConfig::default()
+        };
+        let played = pack.puzzles[1].board_with(&rules).unwrap();
+        assert_eq!(played.config.win_condition, WinCondition::OpenAllSafe);
+        assert!(!played.config.wrap_edges);
+
+        assert_eq!(Pack::parse(&pack.to_pack_string()), Ok(pack));
+    }
+
+    #[test]
+    fn test_invalid_packs() {
+        assert_eq!(Pack::parse("puzzle A\n..*\n"), Err(PackError::MissingName));
+        assert_eq!(Pack::parse("pack A\n"), Err(PackError::NoPuzzles));
+        assert_eq!(Pack::parse("pack A\n..*\n"), Err(PackError::UnknownLine(1)));
+        assert_eq!(
+            Pack::parse("pack A\npuzzle B\n..*\npuzzle C\n..*\n.?.\n"),
+            Err(PackError::InvalidPuzzle(
+                1,
+                LayoutError::InvalidChar((1, 1), '?')
+            ))
+        );
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashSet;
+
+use crate::board::{Board, GameState, Mask, Move, MoveError, Position};
+use crate::config::{GameConfig, WinCondition};
+use crate::variant;
+
+/// Totals of an exhaustive search, compared against known values to catch
+/// changes in the rules.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Perft {
+    /// Mine layout and first click combinations searched.
+    pub games: u64,
+    /// Positions reached by a move after the first click.
+    pub nodes: u64,
+    pub won: u64,
+    pub lost: u64,
+}
+
+/// A broken rule found in a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    OpenAndFlagged(Position),
+    MineOpened(Position),
+    WrongCount(Position),
+    /// A zero cell whose neighbor was not opened by the cascade.
+    ClosedNextToZero(Position),
+    WrongState(GameState),
+    NothingChanged,
+}
+
+#[derive(Debug)]
+pub enum PerftError {
+    /// A move from `legal_moves` was not accepted.
+    Rejected { moves: Vec<Move>, error: MoveError },
+    Invariant {
+        moves: Vec<Move>,
+        violation: Violation,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PerftCase {
+    pub rows: usize,
+    pub cols: usize,
+    pub nr_mines: usize,
+    pub win_condition: WinCondition,
+    /// Moves played after the first click.
+    pub depth: usize,
+    pub expected: Perft,
+}
+
+/// Cases run by the `selftest` command.
+pub const CASES: &[PerftCase] = &[
+    PerftCase {
+        rows: 3,
+        cols: 3,
+        nr_mines: 1,
+        win_condition: WinCondition::FlagAllMines,
+        depth: 3,
+        expected: Perft {
+            games: 72,
+            nodes: 94704,
+            won: 1304,
+            lost: 11156,
+        },
+    },
+    PerftCase {
+        rows: 3,
+        cols: 3,
+        nr_mines: 2,
+        win_condition: WinCondition::OpenAllSafe,
+        depth: 3,
+        expected: Perft {
+            games: 252,
+            nodes: 499556,
+            won: 4764,
+            lost: 77664,
+        },
+    },
+    PerftCase {
+        rows: 4,
+        cols: 4,
+        nr_mines: 2,
+        win_condition: WinCondition::FlagAllMines,
+        depth: 2,
+        expected: Perft {
+            games: 1680,
+            nodes: 804076,
+            won: 192,
+            lost: 72656,
+        },
+    },
+];
+
+impl PerftCase {
+    pub fn run(&self) -> Result<Perft, PerftError> {
+        let config = GameConfig {
+            win_condition: self.win_condition,
+            ..GameConfig::default()
+        };
+        perft(
+            &Board::with_config(self.rows, self.cols, self.nr_mines, config),
+            self.depth,
+        )
+    }
+}
+
+/// Plays every sequence of up to `depth` legal moves after every possible first
+/// click on every mine layout, checking the rules in each position reached.
+/// Only the size, mine count and config of `board` are used.
+pub fn perft(board: &Board, depth: usize) -> Result<Perft, PerftError> {
+    let mut totals = Perft::default();
+    let cells: Vec<Position> = (0..board.rows)
+        .flat_map(|y| (0..board.cols).map(move |x| (x, y)))
+        .collect();
+    for &start in cells.iter() {
+        let safe = board.safe_area(start);
+        let candidates: Vec<Position> = cells
+            .iter()
+            .copied()
+            .filter(|p| !safe.contains(p))
+            .collect();
+        for mines in combinations(&candidates, board.nr_mines) {
+            let mut game =
+                Board::with_config(board.rows, board.cols, board.nr_mines, board.config.clone());
+            game.install_mines(Mask::from_positions(game.grid(), mines), start, None);
+            let mut moves = vec![Move::Open(start)];
+            check(&game).map_err(|violation| PerftError::Invariant {
+                moves: moves.clone(),
+                violation,
+            })?;
+            totals.games += 1;
+            search(&game, depth, &mut moves, &mut totals)?;
+        }
+    }
+    Ok(totals)
+}
+
+fn search(
+    board: &Board,
+    depth: usize,
+    moves: &mut Vec<Move>,
+    totals: &mut Perft,
+) -> Result<(), PerftError> {
+    if depth == 0 {
+        return Ok(());
+    }
+    for mv in board.legal_moves() {
+        let mut next = board.clone();
+        moves.push(mv);
+        if let Err(error) = next.play(mv) {
+            return Err(PerftError::Rejected {
+                moves: moves.clone(),
+                error,
+            });
+        }
+        let checked = if next.last_changed().is_empty() {
+            Err(Violation::NothingChanged)
+        } else {
+            check(&next)
+        };
+        checked.map_err(|violation| PerftError::Invariant {
+            moves: moves.clone(),
+            violation,
+        })?;
+        totals.nodes += 1;
+        match next.state {
+            GameState::Won => totals.won += 1,
+            GameState::Lost => totals.lost += 1,
+            GameState::Init | GameState::OnGoing => search(&next, depth - 1, moves, totals)?,
+        }
+        moves.pop();
+    }
+    Ok(())
+}
+
+/// Checks the rules that hold in every position of an initialized game.
+pub fn check(board: &Board) -> Result<(), Violation> {
+    let Some(mines) = board.mines.as_ref() else {
+        return Err(Violation::WrongState(board.state));
+    };
+    for &pos in board.opened_cells().iter() {
+        if board.flagged_fields.contains(pos) {
+            return Err(Violation::OpenAndFlagged(pos));
+        }
+        if mines.contains(pos) {
+            return Err(Violation::MineOpened(pos));
+        }
+        let count: i8 = board
+            .iter_neighbors(pos)
+            .filter(|&n| mines.contains(n))
+            .map(|n| if board.anti_mines.contains(n) { -1 } else { 1 })
+            .sum();
+        if board.count(pos) != count {
+            return Err(Violation::WrongCount(pos));
+        }
+    }
+    // flags can be removed next to old openings, so only check the last cascade
+    for &pos in board.last_changed().iter() {
+        let calm = !board.iter_neighbors(pos).any(|n| mines.contains(n));
+        if board.open_fields.contains(pos) && calm {
+            if let Some(closed) = board
+                .iter_neighbors(pos)
+                .find(|&n| !board.open_fields.contains(n) && !board.flagged_fields.contains(n))
+            {
+                return Err(Violation::ClosedNextToZero(closed));
+            }
+        }
+    }
+    let cells = board.rows * board.cols;
+    let all_safe_open = board.open_fields.len() + board.nr_mines == cells;
+    let objective = match board.config.variant.as_deref().map(variant::find) {
+        Some(Ok(variant)) => variant.objective(),
+        _ => None,
+    };
+    // objectives of variants are trusted, the classic rules are checked here
+    let won = match (objective, board.progress()) {
+        (Some(objective), Some(progress)) => objective.reached(&progress),
+        _ => match board.config.win_condition {
+            WinCondition::OpenAllSafe => all_safe_open,
+            WinCondition::FlagAllMines => all_safe_open && board.flagged_fields == *mines,
+        },
+    };
+    let hit_mine = board.last_changed().iter().any(|&p| mines.contains(p));
+    let consistent = match board.state {
+        GameState::Init => false,
+        GameState::OnGoing => !won,
+        GameState::Won => won && mines.is_subset(&board.flagged_fields),
+        GameState::Lost => hit_mine,
+    };
+    if !consistent {
+        return Err(Violation::WrongState(board.state));
+    }
+    Ok(())
+}
+
+/// Every way to choose `k` of `items`, in lexicographic order.
+fn combinations(items: &[Position], k: usize) -> Vec<HashSet<Position>> {
+    if k == 0 {
+        return vec![HashSet::new()];
+    }
+    let mut result = Vec::new();
+    for (i, &first) in items.iter().enumerate() {
+        for mut rest in combinations(&items[i + 1..], k - 1) {
+            rest.insert(first);
+            result.push(rest);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_smallest_case() {
+        // the larger cases take a while in debug builds, `selftest` runs them all
+        for case in CASES.iter().filter(|c| c.expected.nodes < 100_000) {
+            assert_eq!(case.run().unwrap(), case.expected, "{case:?}");
+        }
+    }
+
+    #[test]
+    fn test_check_finds_violations() {
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        assert_eq!(check(&board), Ok(()));
+
+        let mut opened_mine = board.clone();
+        opened_mine.open_fields.insert((3, 1));
+        assert_eq!(check(&opened_mine), Err(Violation::MineOpened((3, 1))));
+
+        let mut flagged = board.clone();
+        flagged.flagged_fields.insert((0, 0));
+        assert_eq!(check(&flagged), Err(Violation::OpenAndFlagged((0, 0))));
+
+        let mut closed = board.clone();
+        closed.open_fields.remove((0, 1));
+        assert_eq!(check(&closed), Err(Violation::ClosedNextToZero((0, 1))));
+    }
+}
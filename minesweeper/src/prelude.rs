@@ -0,0 +1,25 @@
+//! The supported surface of the engine for frontends embedding it:
+//!
+//! ```
+//! use minesweeper::prelude::*;
+//!
+//! let mut board = Board::with_config(9, 9, 10, GameConfig::default());
+//! board.init_mines((0, 0), Some(1));
+//! let view: BoardView = board.view();
+//! assert!(Solver::new(&view).mine_cells().contains(&(3, 1)));
+//! ```
+//!
+//! `Board` is the game: it holds the rules, the clock and the history. Clients
+//! that must not see the mines get a `BoardView`, the events of a move come from
+//! `Board::last_events`.
+
+pub use crate::board::{
+    Board, BoardEvent, ChordError, FlagError, GameState, Hint, HintLevel, Move, MoveError,
+    OpenError, Position, Rect, Square, UndoError,
+};
+pub use crate::config::{GameConfig, WinCondition};
+pub use crate::replay::{Replay, ReplayError};
+pub use crate::solver::{mine_probabilities, Solver};
+pub use crate::user_error::UserFacingError;
+pub use crate::variant::{Variant, VariantError};
+pub use crate::view::{BoardView, Constraint};
@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+/// Authoritative timing of one player in a networked race.
+///
+/// Both sides measure time from the player's first click: the client from the
+/// click itself, the server from the moment that click arrived. Every later move
+/// carries the client's elapsed time, and the server accepts
+///
+/// ```text
+/// accepted = clamp(reported, received - max_compensation, received)
+/// ```
+///
+/// raised to the previously accepted time if needed so times never go backwards.
+/// A constant connection delay shifts both clocks equally and cancels out, so
+/// slow and fast connections get the same result. Messages are never early, so
+/// the server's receive time bounds the claim from above, and `max_compensation`
+/// caps how much time a client can claim back for delays that grew during the game.
+#[derive(Debug, Clone)]
+pub struct RaceTimer {
+    max_compensation: Duration,
+    handicap: Handicap,
+    accepted: Vec<Duration>,
+}
+
+/// What the host of a race gives a player to even out skill levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Handicap {
+    #[default]
+    None,
+    /// Taken off the player's time in the ranking.
+    Time(Duration),
+    /// The player races on a board of this size instead of the host's, and the
+    /// time is scaled to the host's board by the ratio of their safe cells.
+    Board {
+        rows: usize,
+        cols: usize,
+        mines: usize,
+    },
+}
+
+impl RaceTimer {
+    pub fn new(max_compensation: Duration) -> RaceTimer {
+        RaceTimer::with_handicap(max_compensation, Handicap::None)
+    }
+
+    pub fn with_handicap(max_compensation: Duration, handicap: Handicap) -> RaceTimer {
+        RaceTimer {
+            max_compensation,
+            handicap,
+            accepted: Vec::new(),
+        }
+    }
+
+    pub fn handicap(&self) -> Handicap {
+        self.handicap
+    }
+
+    /// Records a move the client made at `reported` that reached the server at
+    /// `received`, both measured from the first click, and returns the time
+    /// that counts.
+    pub fn record(&mut self, reported: Duration, received: Duration) -> Duration {
+        let earliest = received.saturating_sub(self.max_compensation);
+        let previous = self.accepted.last().copied().unwrap_or(Duration::ZERO);
+        let accepted = reported.clamp(earliest, received).max(previous);
+        self.accepted.push(accepted);
+        accepted
+    }
+
+    /// Accepted times of every recorded move.
+    pub fn splits(&self) -> &[Duration] {
+        &self.accepted
+    }
+
+    /// Accepted time of the last move, the result once the game is over.
+    pub fn result(&self) -> Option<Duration> {
+        self.accepted.last().copied()
+    }
+
+    /// The result with the handicap applied, comparable between players of a
+    /// race on a `rows` x `cols` board with `mines` mines.
+    pub fn ranked_result(&self, (rows, cols, mines): (usize, usize, usize)) -> Option<Duration> {
+        let result = self.result()?;
+        Some(match self.handicap {
+            Handicap::None => result,
+            Handicap::Time(bonus) => result.saturating_sub(bonus),
+            Handicap::Board {
+                rows: own_rows,
+                cols: own_cols,
+                mines: own_mines,
+            } => {
+                let own_safe = (own_rows * own_cols).saturating_sub(own_mines).max(1);
+                let safe = (rows * cols).saturating_sub(mines);
+                result.mul_f64(safe as f64 / own_safe as f64)
+            }
+        })
+    }
+}
+
+/// Orders the players of a race on a `rows` x `cols` board with `mines` mines
+/// by their handicapped results, fastest first. Players without a result yet
+/// come last in their given order.
+pub fn ranking<P>(
+    players: impl IntoIterator<Item = (P, RaceTimer)>,
+    board: (usize, usize, usize),
+) -> Vec<(P, Option<Duration>)> {
+    let mut ranked: Vec<_> = players
+        .into_iter()
+        .map(|(player, timer)| (player, timer.ranked_result(board)))
+        .collect();
+    ranked.sort_by_key(|(_, result)| (result.is_none(), *result));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    /// Plays the moves at `times` on a client whose messages take `delay(i)`
+    /// to arrive, returning the server side result.
+    fn race(times: &[u64], first_delay: u64, delay: impl Fn(usize) -> u64) -> Duration {
+        let mut timer = RaceTimer::new(ms(500));
+        for (i, &t) in times.iter().enumerate() {
+            // the server clock started when the first click arrived
+            let received = (t + delay(i)).saturating_sub(first_delay);
+            timer.record(ms(t), ms(received));
+        }
+        timer.result().unwrap()
+    }
+
+    #[test]
+    fn test_constant_latency_cancels_out() {
+        let moves = [1_200, 2_500, 4_100, 9_800];
+        let local = race(&moves, 0, |_| 0);
+        let remote = race(&moves, 350, |_| 350);
+        assert_eq!(local, ms(9_800));
+        assert_eq!(remote, local);
+    }
+
+    #[test]
+    fn test_jitter_is_compensated() {
+        let moves = [1_200, 2_500, 4_100, 9_800];
+        let jittery = race(&moves, 100, |i| [100, 400, 150, 300][i]);
+        assert_eq!(jittery, ms(9_800));
+    }
+
+    #[test]
+    fn test_claims_are_bounded() {
+        let mut timer = RaceTimer::new(ms(500));
+        // a client claiming an impossibly fast move gets at most the compensation
+        assert_eq!(timer.record(ms(100), ms(3_000)), ms(2_500));
+        // a claim later than the server saw the move is capped at arrival
+        assert_eq!(timer.record(ms(9_000), ms(4_000)), ms(4_000));
+        // times never go backwards
+        assert_eq!(timer.record(ms(3_900), ms(4_100)), ms(4_000));
+        assert_eq!(timer.splits(), &[ms(2_500), ms(4_000), ms(4_000)]);
+    }
+
+    #[test]
+    fn test_handicaps_in_ranking() {
+        let expert = (16, 30, 99);
+        let finished = |handicap, millis| {
+            let mut timer = RaceTimer::with_handicap(ms(500), handicap);
+            timer.record(ms(millis), ms(millis));
+            timer
+        };
+        let players = [
+            ("host", finished(Handicap::None, 90_000)),
+            ("unfinished", RaceTimer::new(ms(500))),
+            ("head start", finished(Handicap::Time(ms(20_000)), 100_000)),
+            // 71 safe cells against 381 on expert
+            (
+                "beginner board",
+                finished(
+                    Handicap::Board {
+                        rows: 9,
+                        cols: 9,
+                        mines: 10,
+                    },
+                    20_000,
+                ),
+            ),
+        ];
+        let ranking = ranking(players, expert);
+        let names: Vec<_> = ranking.iter().map(|(name, _)| *name).collect();
+        assert_eq!(
+            names,
+            ["head start", "host", "beginner board", "unfinished"]
+        );
+        assert_eq!(ranking[0].1, Some(ms(80_000)));
+        assert_eq!(ranking[2].1.unwrap().as_millis(), 107_323);
+        assert_eq!(ranking[3].1, None);
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use crate::board::{Board, Position};
+
+/// Deviations beyond this many standard deviations fail a test, a fair generator
+/// does so in about one of 2000 runs.
+pub const MAX_Z_SCORE: f64 = 3.29;
+
+/// Result of a chi-squared test against a uniform distribution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Uniformity {
+    pub chi_squared: f64,
+    pub degrees_of_freedom: usize,
+    /// The statistic as a standard normal score (Wilson-Hilferty), large when the
+    /// observations are less uniform than chance allows.
+    pub z_score: f64,
+}
+
+impl Uniformity {
+    /// Tests observed counts of categories that are all expected `expected` times,
+    /// `unseen` more categories were never observed.
+    pub fn new(
+        observed: impl IntoIterator<Item = usize>,
+        unseen: usize,
+        expected: f64,
+    ) -> Uniformity {
+        Uniformity::with_variance(observed, unseen, expected, expected)
+    }
+
+    /// Like `new`, for counts whose variance is not their expected value.
+    fn with_variance(
+        observed: impl IntoIterator<Item = usize>,
+        unseen: usize,
+        expected: f64,
+        variance: f64,
+    ) -> Uniformity {
+        let mut chi_squared = unseen as f64 * expected * expected / variance;
+        let mut categories = unseen;
+        for count in observed {
+            chi_squared += (count as f64 - expected).powi(2) / variance;
+            categories += 1;
+        }
+        let k = categories.saturating_sub(1).max(1) as f64;
+        let z_score =
+            ((chi_squared / k).cbrt() - (1.0 - 2.0 / (9.0 * k))) / (2.0 / (9.0 * k)).sqrt();
+        Uniformity {
+            chi_squared,
+            degrees_of_freedom: categories.saturating_sub(1),
+            z_score,
+        }
+    }
+
+    pub fn passes(&self) -> bool {
+        self.z_score < MAX_Z_SCORE
+    }
+}
+
+/// Mine layouts generated for the first click `start`, with the size and config
+/// of `board` and seeds `0..samples`.
+fn layouts(
+    board: &Board,
+    start: Position,
+    samples: usize,
+) -> impl Iterator<Item = Vec<Position>> + '_ {
+    (0..samples as u64).map(move |seed| {
+        let mut game =
+            Board::with_config(board.rows, board.cols, board.nr_mines, board.config.clone());
+        game.init_mines(start, Some(seed));
+        game.mines.unwrap().iter().collect()
+    })
+}
+
+/// How often each cell held a mine, indexed `[row][col]`.
+pub fn cell_frequencies(board: &Board, start: Position, samples: usize) -> Vec<Vec<usize>> {
+    let mut counts = vec![vec![0; board.cols]; board.rows];
+    for mines in layouts(board, start, samples) {
+        for (x, y) in mines {
+            counts[y][x] += 1;
+        }
+    }
+    counts
+}
+
+/// Every cell outside the safe area should hold a mine equally often. `None` if
+/// a mine was placed inside the safe area.
+pub fn cell_uniformity(
+    frequencies: &[Vec<usize>],
+    board: &Board,
+    start: Position,
+) -> Option<Uniformity> {
+    let safe = board.safe_area(start);
+    if safe.iter().any(|&(x, y)| frequencies[y][x] > 0) {
+        return None;
+    }
+    let total: usize = frequencies.iter().flatten().sum();
+    let eligible = board.rows * board.cols - safe.len();
+    let observed = (0..board.rows)
+        .flat_map(|y| (0..board.cols).map(move |x| (x, y)))
+        .filter(|pos| !safe.contains(pos))
+        .map(|(x, y)| frequencies[y][x]);
+    // every layout holds exactly `nr_mines` mines, so a cell's count is binomial
+    let expected = total as f64 / eligible as f64;
+    let p = board.nr_mines as f64 / eligible as f64;
+    Some(Uniformity::with_variance(
+        observed,
+        0,
+        expected,
+        expected * (1.0 - p),
+    ))
+}
+
+/// Number of possible layouts, every one of them should be generated equally often.
+/// `None` if there are too many to count.
+pub fn layout_count(board: &Board, start: Position) -> Option<u128> {
+    let eligible = (board.rows * board.cols - board.safe_area(start).len()) as u128;
+    (0..board.nr_mines as u128).try_fold(1u128, |count, i| {
+        Some(count.checked_mul(eligible - i)? / (i + 1))
+    })
+}
+
+/// Tests the joint distribution of the mines, which per cell frequencies can miss.
+/// Only meaningful on tiny boards, where every layout is expected several times.
+pub fn layout_uniformity(board: &Board, start: Position, samples: usize) -> Uniformity {
+    let mut seen: HashMap<Vec<Position>, usize> = HashMap::new();
+    for mines in layouts(board, start, samples) {
+        *seen.entry(mines).or_default() += 1;
+    }
+    let possible = layout_count(board, start).unwrap_or(u128::MAX) as usize;
+    Uniformity::new(
+        seen.values().copied(),
+        possible - seen.len(),
+        samples as f64 / possible as f64,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+
+    #[test]
+    fn test_uniformity_statistic() {
+        assert!(Uniformity::new([100, 98, 103, 99], 0, 100.0).passes());
+        let biased = Uniformity::new([150, 50, 100, 100], 0, 100.0);
+        assert_eq!(biased.chi_squared, 50.0);
+        assert_eq!(biased.degrees_of_freedom, 3);
+        assert!(!biased.passes());
+        // categories that never came up count against uniformity
+        assert!(!Uniformity::new([200, 200], 2, 100.0).passes());
+    }
+
+    #[test]
+    fn test_generated_layouts() {
+        let board = Board::new(4, 4, 3);
+        let frequencies = cell_frequencies(&board, (0, 0), 2000);
+        assert_eq!(frequencies[0][0], 0);
+        assert!(cell_uniformity(&frequencies, &board, (0, 0))
+            .unwrap()
+            .passes());
+
+        let config = GameConfig {
+            safe_radius: 1,
+            ..GameConfig::default()
+        };
+        let board = Board::with_config(3, 3, 2, config);
+        assert_eq!(layout_count(&board, (0, 0)), Some(10));
+        assert_eq!(layout_count(&Board::new(16, 30, 99), (0, 0)), None);
+        assert!(layout_uniformity(&board, (0, 0), 1000).passes());
+    }
+}
@@ -0,0 +1,230 @@
+//! Text rendering of boards for terminals, with a set of glyphs for the cells
+//! and optional ANSI colors. `Display` for `Board` is the default style.
+//!
+//! Glyph sets other than the built-in ones are read from TOML files with the
+//! `themes` feature:
+//!
+//! ```toml
+//! closed = " . "
+//! flag = " F "
+//! new_flag = "[F]"
+//! mine = " * "
+//! hit_mine = "[*]"
+//! ```
+
+use std::borrow::Cow;
+
+use crate::board::{Board, GameState, Position, Topology};
+
+/// Text of the cells that don't show a number, each three columns wide to line
+/// up with the numbers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Glyphs {
+    pub closed: Cow<'static, str>,
+    pub flag: Cow<'static, str>,
+    /// A flag placed by the last move.
+    pub new_flag: Cow<'static, str>,
+    pub mine: Cow<'static, str>,
+    /// The mine that lost the game.
+    pub hit_mine: Cow<'static, str>,
+}
+
+impl Glyphs {
+    pub const EMOJI: Glyphs = Glyphs {
+        closed: Cow::Borrowed("🟪 "),
+        flag: Cow::Borrowed("🚩 "),
+        new_flag: Cow::Borrowed("⛳ "),
+        mine: Cow::Borrowed("💣 "),
+        hit_mine: Cow::Borrowed("💥 "),
+    };
+
+    /// For terminals without emoji support.
+    pub const ASCII: Glyphs = Glyphs {
+        closed: Cow::Borrowed(" # "),
+        flag: Cow::Borrowed(" F "),
+        new_flag: Cow::Borrowed("[F]"),
+        mine: Cow::Borrowed(" * "),
+        hit_mine: Cow::Borrowed("[*]"),
+    };
+}
+
+impl Default for Glyphs {
+    fn default() -> Glyphs {
+        Glyphs::EMOJI
+    }
+}
+
+#[cfg(feature = "themes")]
+impl Glyphs {
+    pub fn from_toml(source: &str) -> Result<Glyphs, toml::de::Error> {
+        toml::from_str(source)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Style {
+    /// Color the numbers by count, flags red and mines bold.
+    pub color: bool,
+    pub glyphs: Glyphs,
+    /// Print the column indices across the top and the row indices down the
+    /// side, the `x` and `y` of the coordinates to type.
+    pub headers: bool,
+}
+
+// ANSI codes of the numbers, anti-mine counts below zero are purple
+fn count_color(count: i8) -> &'static str {
+    match count {
+        ..0 => "95",
+        0 => "2",
+        1 => "94",
+        2 => "32",
+        3 => "91",
+        4 => "34",
+        5 => "31",
+        6 => "36",
+        7 => "35",
+        _ => "90",
+    }
+}
+
+impl Style {
+    /// One line per row. Cells changed by the last move are marked: `[n]` for
+    /// opened cells, `new_flag` for flags and `hit_mine` for the mine that was hit.
+    pub fn render<T: Topology>(&self, board: &Board<T>) -> String {
+        let mut out = String::new();
+        let margin = board.rows.saturating_sub(1).to_string().len();
+        if self.headers {
+            out.push_str(&" ".repeat(margin + 1));
+            for x in 0..board.cols {
+                out.push_str(&format!("{x:>2} "));
+            }
+            out.push('\n');
+        }
+        for y in 0..board.rows {
+            if self.headers {
+                out.push_str(&format!("{y:>margin$} "));
+            }
+            for x in 0..board.cols {
+                out.push_str(&self.cell(board, (x, y)));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn cell<T: Topology>(&self, board: &Board<T>, pos: Position) -> String {
+        let glyphs = &self.glyphs;
+        let changed = board.last_changed().contains(&pos);
+        let over = matches!(board.state, GameState::Lost | GameState::Won);
+        let mine = over
+            && board
+                .mines
+                .as_ref()
+                .is_some_and(|mines| mines.contains(pos));
+        let (text, color) = if !board.open_fields.contains(pos) {
+            if board.flagged_fields.contains(pos) {
+                let text = if changed {
+                    &glyphs.new_flag
+                } else {
+                    &glyphs.flag
+                };
+                (text.to_string(), Some("31"))
+            } else if mine {
+                let text = if changed {
+                    &glyphs.hit_mine
+                } else {
+                    &glyphs.mine
+                };
+                (text.to_string(), Some("1"))
+            } else {
+                (glyphs.closed.to_string(), None)
+            }
+        } else if mine {
+            (glyphs.mine.to_string(), Some("1"))
+        } else {
+            let count = board.shown_count(pos);
+            let text = if changed {
+                format!("[{count}]")
+            } else {
+                format!("{count:>2} ")
+            };
+            (text, Some(count_color(count)))
+        };
+        match color {
+            Some(code) if self.color => format!("\x1b[{code}m{text}\x1b[0m"),
+            _ => text,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_styles() {
+        let mut board = Board::new(9, 9, 10);
+        assert_eq!(board.to_string().lines().next(), Some(&*"🟪 ".repeat(9)));
+        board.init_mines((0, 0), Some(1));
+        board.flag((3, 1)).unwrap();
+
+        let ascii = Style {
+            color: false,
+            glyphs: Glyphs::ASCII,
+            headers: false,
+        }
+        .render(&board);
+        assert!(ascii.is_ascii());
+        assert_eq!(ascii.lines().next(), Some(" 0  0  1  #  #  #  #  #  # "));
+        assert!(ascii.lines().nth(1).unwrap().contains("[F]"));
+
+        let colored = Style {
+            color: true,
+            glyphs: Glyphs::EMOJI,
+            headers: false,
+        }
+        .render(&board);
+        assert!(colored.contains("\x1b[94m 1 \x1b[0m"));
+        assert!(colored.contains("\x1b[31m⛳ \x1b[0m"));
+
+        board.open((6, 1)).unwrap();
+        let ascii = Style {
+            color: true,
+            glyphs: Glyphs::ASCII,
+            headers: false,
+        }
+        .render(&board);
+        assert!(ascii.contains("\x1b[1m[*]\x1b[0m"));
+        assert!(ascii.contains("\x1b[31m F \x1b[0m"));
+    }
+
+    #[test]
+    fn test_headers() {
+        let mut board = Board::new(11, 3, 1);
+        board.init_mines((0, 0), Some(1));
+        let style = Style {
+            color: false,
+            glyphs: Glyphs::ASCII,
+            headers: true,
+        };
+        let rendered = style.render(&board);
+        let lines: Vec<_> = rendered.lines().collect();
+        assert_eq!(lines.len(), 12);
+        assert_eq!(lines[0], "    0  1  2 ");
+        assert!(lines[1].starts_with(" 0 "));
+        assert!(lines[11].starts_with("10 "));
+        assert!(lines.iter().all(|line| line.len() == lines[0].len()));
+    }
+
+    #[cfg(feature = "themes")]
+    #[test]
+    fn test_glyph_file() {
+        let source =
+            "closed = ' . '\nflag = ' F '\nnew_flag = '[F]'\nmine = ' * '\nhit_mine = '[*]'";
+        let glyphs = Glyphs::from_toml(source).unwrap();
+        assert_eq!(glyphs.closed, " . ");
+        assert_eq!(glyphs.hit_mine, Glyphs::ASCII.hit_mine);
+        assert!(Glyphs::from_toml("closed = ' . '").is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use crate::board::{Board, GameState, Move, MoveError, OpenError};
+use crate::config::GameConfig;
+
+/// A move together with the game time at which it was made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReplayAction {
+    pub mv: Move,
+    pub elapsed: Duration,
+}
+
+/// Ordered log of the moves of a game, the first action is always the first click.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Replay {
+    pub rows: usize,
+    pub cols: usize,
+    pub nr_mines: usize,
+    pub seed: u64,
+    pub config: GameConfig,
+    pub actions: Vec<ReplayAction>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The replay does not start with opening a cell.
+    MissingFirstClick,
+    /// The action at this index was rejected by the board.
+    InvalidMove(usize, MoveError),
+}
+
+impl Replay {
+    /// Rebuilds the board after the first `steps` actions.
+    pub fn board_at(&self, steps: usize) -> Result<Board, ReplayError> {
+        // moves are re-applied instantly, so timing rules can't apply
+        let mut config = self.config.clone();
+        config.min_move_interval = None;
+        let mut board = Board::with_config(self.rows, self.cols, self.nr_mines, config);
+        let mut actions = self.actions.iter().take(steps);
+        match actions.next() {
+            Some(ReplayAction {
+                mv: Move::Open(start),
+                ..
+            }) if !board.contains(*start) => {
+                return Err(ReplayError::InvalidMove(
+                    0,
+                    MoveError::Open(OpenError::OutOfBounds),
+                ))
+            }
+            Some(ReplayAction {
+                mv: Move::Open(start),
+                ..
+            }) => board.init_mines(*start, Some(self.seed)),
+            Some(_) => return Err(ReplayError::MissingFirstClick),
+            None => return Ok(board),
+        }
+        for (i, action) in actions.enumerate() {
+            board
+                .play(action.mv)
+                .map_err(|e| ReplayError::InvalidMove(i + 1, e))?;
+        }
+        board.config = self.config.clone();
+        Ok(board)
+    }
+
+    /// Rebuilds the final board of the game.
+    pub fn play(&self) -> Result<Board, ReplayError> {
+        self.board_at(self.actions.len())
+    }
+
+    /// The board after every action, starting with the first click.
+    pub fn steps(&self) -> impl Iterator<Item = Result<Board, ReplayError>> + '_ {
+        (1..=self.actions.len()).map(|steps| self.board_at(steps))
+    }
+
+    pub fn outcome(&self) -> Result<GameState, ReplayError> {
+        Ok(self.play()?.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn played_game() -> Board {
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), None);
+        let moves: Vec<Move> = board.legal_moves().into_iter().step_by(7).collect();
+        for mv in moves {
+            if board.play(mv).is_err() || !board.ongoing() {
+                break;
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn test_replay_reproduces_game() {
+        let board = played_game();
+        let replay = board.replay().unwrap();
+        assert_eq!(replay.actions[0].mv, Move::Open((0, 0)));
+        assert!(replay
+            .actions
+            .windows(2)
+            .all(|w| w[0].elapsed <= w[1].elapsed));
+
+        let replayed = replay.play().unwrap();
+        assert!(board.diff(&replayed).unwrap().is_empty());
+        assert_eq!(replayed.state, board.state);
+        assert_eq!(replay.steps().count(), replay.actions.len());
+        assert!(Board::new(9, 9, 10).replay().is_none());
+    }
+
+    #[test]
+    fn test_replay_rejects_invalid_log() {
+        let mut replay = played_game().replay().unwrap();
+        replay.actions.insert(0, replay.actions[1]);
+        replay.actions[0].mv = Move::Flag((1, 1));
+        assert!(matches!(replay.play(), Err(ReplayError::MissingFirstClick)));
+
+        let mut replay = played_game().replay().unwrap();
+        replay.actions.push(replay.actions[0]);
+        assert!(matches!(
+            replay.board_at(replay.actions.len()),
+            Err(ReplayError::InvalidMove(_, _))
+        ));
+
+        let mut replay = played_game().replay().unwrap();
+        replay.actions[0].mv = Move::Open((9, 0));
+        assert!(matches!(
+            replay.play(),
+            Err(ReplayError::InvalidMove(
+                0,
+                MoveError::Open(OpenError::OutOfBounds)
+            ))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_replay_serde() {
+        let replay = played_game().replay().unwrap();
+        let json = serde_json::to_string(&replay).unwrap();
+        let restored: Replay = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, replay);
+    }
+}
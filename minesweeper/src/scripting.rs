@@ -0,0 +1,256 @@
+//! User scripts in [Rhai](https://rhai.rs) that react to board events and make
+//! moves, for custom assists, bots or accessibility macros.
+//!
+//! A script subscribes to an event by defining its handler, which gets the view
+//! of the board:
+//!
+//! ```rhai
+//! fn on_turn(view) {
+//!     if view.cell(0, 0) == "closed" {
+//!         open(0, 0);
+//!     }
+//! }
+//! ```
+//!
+//! Scripts only see the `BoardView`, never the mines, and can only queue moves
+//! with `open(x, y)`, `flag(x, y)` and `chord(x, y)`. They cannot import modules
+//! or use `eval`, and are stopped when they run too long.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, ParseError, Scope, AST};
+
+use crate::board::{Board, GameState, Move, MoveError, Position, Square};
+use crate::view::BoardView;
+
+// limits keeping a runaway script from hanging the game
+const MAX_OPERATIONS: u64 = 1_000_000;
+const MAX_CALL_LEVELS: usize = 32;
+const MAX_SIZE: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// The mines were placed by the first click, handled by `on_start(view)`.
+    Start,
+    /// The player made a move, handled by `on_turn(view)`.
+    Turn,
+    /// The game was won or lost, handled by `on_end(view)`.
+    End,
+}
+
+impl Event {
+    fn handler(self) -> &'static str {
+        match self {
+            Event::Start => "on_start",
+            Event::Turn => "on_turn",
+            Event::End => "on_end",
+        }
+    }
+}
+
+/// A move queued by a script, with the board's answer to it.
+pub type ScriptedMove = (Move, Result<GameState, MoveError>);
+
+#[derive(Debug)]
+pub enum ScriptError {
+    Parse(ParseError),
+    Runtime(Box<EvalAltResult>),
+}
+
+pub struct Script {
+    engine: Engine,
+    ast: AST,
+    scope: Scope<'static>,
+    // moves queued by the running handler
+    moves: Rc<RefCell<Vec<Move>>>,
+}
+
+impl Script {
+    pub fn new(source: &str) -> Result<Script, ScriptError> {
+        let moves = Rc::new(RefCell::new(Vec::new()));
+        let engine = sandboxed_engine(&moves);
+        let ast = engine.compile(source).map_err(ScriptError::Parse)?;
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(ScriptError::Runtime)?;
+        Ok(Script {
+            engine,
+            ast,
+            scope,
+            moves,
+        })
+    }
+
+    /// Whether the script subscribed to `event`.
+    pub fn handles(&self, event: Event) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == event.handler() && f.params.len() == 1)
+    }
+
+    /// Runs the handler of `event` and plays the moves it queued, in order.
+    pub fn dispatch(
+        &mut self,
+        event: Event,
+        board: &mut Board,
+    ) -> Result<Vec<ScriptedMove>, ScriptError> {
+        if !self.handles(event) {
+            return Ok(Vec::new());
+        }
+        self.moves.borrow_mut().clear();
+        let options = CallFnOptions::new().eval_ast(false);
+        self.engine
+            .call_fn_with_options::<Dynamic>(
+                options,
+                &mut self.scope,
+                &self.ast,
+                event.handler(),
+                (board.view(),),
+            )
+            .map(drop)
+            .map_err(ScriptError::Runtime)?;
+        let moves = std::mem::take(&mut *self.moves.borrow_mut());
+        Ok(moves.into_iter().map(|mv| (mv, board.play(mv))).collect())
+    }
+}
+
+fn sandboxed_engine(moves: &Rc<RefCell<Vec<Move>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(MAX_CALL_LEVELS)
+        .set_max_string_size(MAX_SIZE)
+        .set_max_array_size(MAX_SIZE)
+        .set_max_map_size(MAX_SIZE)
+        .set_module_resolver(DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+
+    engine
+        .register_type_with_name::<BoardView>("BoardView")
+        .register_get("rows", |view: &mut BoardView| view.rows as i64)
+        .register_get("cols", |view: &mut BoardView| view.cols as i64)
+        .register_get("nr_mines", |view: &mut BoardView| view.nr_mines as i64)
+        .register_get("state", |view: &mut BoardView| format!("{:?}", view.state))
+        .register_fn(
+            "cell",
+            |view: &mut BoardView, x: i64, y: i64| match position(x, y)
+                .ok()
+                .and_then(|pos| view.get(pos))
+            {
+                Some(Square::Opened(count)) => Dynamic::from(count as i64),
+                Some(Square::NotYetOpened) => Dynamic::from("closed"),
+                Some(Square::Flag) => Dynamic::from("flag"),
+                Some(Square::Mine) => Dynamic::from("mine"),
+                None => Dynamic::UNIT,
+            },
+        )
+        .register_fn("neighbors", |view: &mut BoardView, x: i64, y: i64| {
+            let Ok(pos) = position(x, y) else {
+                return Array::new();
+            };
+            view.neighbors(pos)
+                .map(|(x, y)| {
+                    let pair: Array = vec![(x as i64).into(), (y as i64).into()];
+                    pair.into()
+                })
+                .collect::<Array>()
+        });
+
+    for (name, action) in [
+        ("open", Move::Open as fn(Position) -> Move),
+        ("flag", Move::Flag),
+        ("chord", Move::Chord),
+    ] {
+        let moves = moves.clone();
+        engine.register_fn(name, move |x: i64, y: i64| {
+            moves.borrow_mut().push(action(position(x, y)?));
+            Ok::<_, Box<EvalAltResult>>(())
+        });
+    }
+    engine
+}
+
+fn position(x: i64, y: i64) -> Result<Position, Box<EvalAltResult>> {
+    match (usize::try_from(x), usize::try_from(y)) {
+        (Ok(x), Ok(y)) => Ok((x, y)),
+        _ => Err(format!("invalid position ({x}, {y})").into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started_board() -> Board {
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        board
+    }
+
+    #[test]
+    fn test_script_moves() {
+        let mut script = Script::new(
+            r#"
+            fn on_turn(view) {
+                if view.cell(3, 0) == "closed" && view.cell(2, 0) == 1 {
+                    open(3, 0);
+                }
+                flag(3, 1);
+                open(0, 0);
+            }
+            "#,
+        )
+        .unwrap();
+        assert!(script.handles(Event::Turn));
+        assert!(!script.handles(Event::End));
+
+        let mut board = started_board();
+        let played = script.dispatch(Event::Turn, &mut board).unwrap();
+        assert_eq!(played.len(), 3);
+        assert!(played[0].1.is_ok());
+        assert_eq!(played[1].0, Move::Flag((3, 1)));
+        assert!(played[2].1.is_err());
+        assert!(board.flagged_fields.contains((3, 1)));
+        assert!(script.dispatch(Event::End, &mut board).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_script_sees_view() {
+        let mut script = Script::new(
+            r#"
+            fn on_start(view) {
+                if view.rows == 9 && view.state == "OnGoing" && view.cell(9, 0) == () {
+                    for n in view.neighbors(0, 0) {
+                        open(n[0], n[1]);
+                    }
+                }
+            }
+            "#,
+        )
+        .unwrap();
+        let played = script.dispatch(Event::Start, &mut started_board()).unwrap();
+        assert_eq!(played.len(), 3);
+    }
+
+    #[test]
+    fn test_sandbox() {
+        assert!(matches!(
+            Script::new("fn on_turn(view) {"),
+            Err(ScriptError::Parse(_))
+        ));
+        assert!(Script::new(r#"eval("open(1, 1)")"#).is_err());
+        assert!(Script::new(r#"import "std" as fs;"#).is_err());
+
+        let mut board = started_board();
+        let mut endless = Script::new("fn on_turn(view) { loop { } }").unwrap();
+        assert!(matches!(
+            endless.dispatch(Event::Turn, &mut board),
+            Err(ScriptError::Runtime(_))
+        ));
+        let mut negative = Script::new("fn on_turn(view) { open(-1, 0); }").unwrap();
+        assert!(negative.dispatch(Event::Turn, &mut board).is_err());
+    }
+}
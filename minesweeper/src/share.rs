@@ -0,0 +1,231 @@
+use std::fmt::Write;
+
+use std::collections::HashSet;
+
+use crate::board::{Board, GameState, Move, Square};
+use crate::codec;
+use crate::view::BoardView;
+
+/// The visible board as a fenced Markdown block: `.` unopened, `F` flag,
+/// `*` mine and digits for opened cells. With anti-mines or wormholes every cell
+/// is two characters wide to fit negative and larger counts.
+pub fn markdown_snapshot(view: &BoardView) -> String {
+    let width = if view.nr_anti_mines > 0 || !view.wormholes.is_empty() {
+        2
+    } else {
+        1
+    };
+    let mut out = String::from("```\n");
+    for row in view.squares.iter() {
+        for square in row {
+            let cell = match square {
+                Square::NotYetOpened => ".".to_string(),
+                Square::Flag => "F".to_string(),
+                Square::Mine => "*".to_string(),
+                Square::Opened(count) => count.to_string(),
+            };
+            write!(out, "{cell:>width$}").unwrap();
+        }
+        out.push('\n');
+    }
+    out.push_str("```\n");
+    out
+}
+
+/// One line summary of a finished game, `None` while it is still being played.
+/// Games with `assisted_flags` are marked as assisted.
+pub fn result_summary(board: &Board) -> Option<String> {
+    let outcome = match board.state {
+        GameState::Won => "won",
+        GameState::Lost => "lost",
+        GameState::Init | GameState::OnGoing => return None,
+    };
+    Some(format!(
+        "Minesweeper {}x{} with {} mines: {outcome} in {:.3}s{}",
+        board.cols,
+        board.rows,
+        board.nr_mines,
+        board.clock.elapsed().as_secs_f64(),
+        if board.config.assisted_flags || board.undos > 0 {
+            " (assisted)"
+        } else {
+            ""
+        }
+    ))
+}
+
+/// Compact code that replays the whole game, `None` before the first click.
+/// Read it back with `codec::from_share_code` and `codec::decode_replay`.
+pub fn replay_code(board: &Board) -> Option<String> {
+    Some(codec::to_share_code(&codec::encode_replay(
+        &board.replay()?,
+    )))
+}
+
+/// Name of the classic difficulty matching the board size, if any.
+pub fn difficulty_name(rows: usize, cols: usize, nr_mines: usize) -> Option<&'static str> {
+    match (rows, cols, nr_mines) {
+        (9, 9, 10) => Some("Beginner"),
+        (16, 16, 40) => Some("Intermediate"),
+        (16, 30, 99) | (30, 16, 99) => Some("Expert"),
+        _ => None,
+    }
+}
+
+/// Spoiler free summary of a finished game for social sharing: the difficulty,
+/// time and an emoji picture at most `EMOJI_GRID_WIDTH` wide, where each emoji
+/// covers a block of cells. 🟩 all safe cells opened, 🟨 some, ⬜ none and 💥
+/// where the game was lost.
+pub fn emoji_grid(board: &Board) -> Option<String> {
+    const EMOJI_GRID_WIDTH: usize = 10;
+    let mines: HashSet<_> = board.reveal_mines()?.into_iter().collect();
+    // boards without a seed have no replay, they are drawn without the explosion
+    let last_action = board.replay().and_then(|r| r.actions.last().copied());
+    let explosion = match (board.state, last_action) {
+        // a losing chord exploded next to its position, which is close enough at this scale
+        (GameState::Lost, Some(action)) => match action.mv {
+            Move::Open(pos) | Move::Chord(pos) => Some(pos),
+            Move::Flag(_) => None,
+        },
+        _ => None,
+    };
+    let block = board.cols.div_ceil(EMOJI_GRID_WIDTH).max(1);
+
+    let mut out = match difficulty_name(board.rows, board.cols, board.nr_mines) {
+        Some(name) => format!("Minesweeper {name}"),
+        None => format!(
+            "Minesweeper {}x{}/{}",
+            board.cols, board.rows, board.nr_mines
+        ),
+    };
+    let outcome = if board.state == GameState::Won {
+        "🏆"
+    } else {
+        "💣"
+    };
+    writeln!(
+        out,
+        " {outcome} {:.3}s",
+        board.clock.elapsed().as_secs_f64()
+    )
+    .unwrap();
+    for by in (0..board.rows).step_by(block) {
+        for bx in (0..board.cols).step_by(block) {
+            let cells = (by..(by + block).min(board.rows))
+                .flat_map(|y| (bx..(bx + block).min(board.cols)).map(move |x| (x, y)));
+            let (mut safe, mut opened, mut exploded) = (0, 0, false);
+            for pos in cells {
+                exploded |= explosion.is_some_and(|e| e == pos);
+                if !mines.contains(&pos) {
+                    safe += 1;
+                    if board.open_fields.contains(pos) {
+                        opened += 1;
+                    }
+                }
+            }
+            out.push_str(if exploded {
+                "💥"
+            } else if opened == safe && (safe > 0 || board.state == GameState::Won) {
+                // blocks of only mines must not stand out on a lost board
+                "🟩"
+            } else if opened > 0 {
+                "🟨"
+            } else {
+                "⬜"
+            });
+        }
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// Terminal escape sequence (OSC 52) asking the terminal to put `text` on the clipboard.
+pub fn osc52_copy(text: &str) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in text.as_bytes().chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    format!("\x1b]52;c;{encoded}\x07")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_and_summary() {
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        board.flag((3, 1)).unwrap();
+        let snapshot = markdown_snapshot(&board.view());
+        let lines: Vec<&str> = snapshot.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert_eq!(lines[1], "001......");
+        assert_eq!(lines[2], "001F.....");
+        assert!(result_summary(&board).is_none());
+
+        board.open((4, 3)).unwrap();
+        let summary = result_summary(&board).unwrap();
+        assert!(summary.starts_with("Minesweeper 9x9 with 10 mines: lost in"));
+    }
+
+    #[test]
+    fn test_emoji_grid() {
+        let mut board = Board::new(16, 30, 99);
+        board.init_mines((0, 0), Some(1));
+        assert!(emoji_grid(&board).is_none());
+        let mine = board.legal_moves().into_iter().find_map(|mv| match mv {
+            Move::Open(pos) if board.mines.as_ref().unwrap().contains(pos) => Some(pos),
+            _ => None,
+        });
+        board.open(mine.unwrap()).unwrap();
+
+        let grid = emoji_grid(&board).unwrap();
+        let lines: Vec<&str> = grid.lines().collect();
+        assert!(lines[0].starts_with("Minesweeper Expert 💣"));
+        // 3x3 blocks: 10 emoji wide and 6 high
+        assert_eq!(lines.len(), 7);
+        assert!(lines[1..].iter().all(|l| l.chars().count() == 10));
+        assert_eq!(grid.matches('💥').count(), 1);
+
+        // a layout without a seed still gets its picture
+        let mut board = Board::with_mines(9, 9, &[(8, 8)]).unwrap();
+        board.open((0, 0)).unwrap();
+        board.flag((8, 8)).unwrap();
+        let grid = emoji_grid(&board).unwrap();
+        assert!(grid.starts_with("Minesweeper 9x9/1 🏆"));
+        assert_eq!(grid.matches('🟩').count(), 81);
+    }
+
+    #[test]
+    fn test_replay_code() {
+        let mut board = Board::new(9, 9, 10);
+        assert!(replay_code(&board).is_none());
+        board.init_mines((0, 0), Some(1));
+        board.flag((3, 1)).unwrap();
+        let code = replay_code(&board).unwrap();
+        let bytes = codec::from_share_code(&code).unwrap();
+        let replay = codec::decode_replay(&bytes).unwrap();
+        assert!(replay.play().unwrap().diff(&board).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_osc52_base64() {
+        assert_eq!(osc52_copy("Man"), "\x1b]52;c;TWFu\x07");
+        assert_eq!(osc52_copy("Ma"), "\x1b]52;c;TWE=\x07");
+        assert_eq!(osc52_copy("M"), "\x1b]52;c;TQ==\x07");
+    }
+}
@@ -0,0 +1,183 @@
+//! Signatures for content organizers hand out, such as puzzle packs or the files
+//! of a tournament, so players and servers can check that a file is the one the
+//! organizer published before they accept results played on it. The signature
+//! is a text file kept next to the content:
+//!
+//! ```text
+//! sha256 <hash of the content>
+//! key <public key of the signer>
+//! signature <ed25519 signature of the content>
+//! ```
+//!
+//! Hashes, keys and signatures are share codes, see `codec`. Checking a
+//! signature takes the key the organizer published; the key line only tells
+//! which key signed.
+
+use ed25519_dalek::{Signer, Verifier};
+use sha2::{Digest, Sha256};
+
+use crate::codec;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SignatureError {
+    /// A signature file or key that can't be read.
+    Malformed,
+    /// The content changed since it was signed.
+    HashMismatch,
+    /// The content was signed with another key than the trusted one.
+    OtherKey,
+    /// The signature doesn't match the content.
+    Invalid,
+}
+
+/// SHA-256 of the content.
+pub fn content_hash(content: &[u8]) -> [u8; 32] {
+    Sha256::digest(content).into()
+}
+
+/// The secret key of an organizer.
+pub struct SigningKey(ed25519_dalek::SigningKey);
+
+impl SigningKey {
+    pub fn generate() -> SigningKey {
+        SigningKey(ed25519_dalek::SigningKey::from_bytes(&rand::random()))
+    }
+
+    pub fn from_code(code: &str) -> Result<SigningKey, SignatureError> {
+        Ok(SigningKey(ed25519_dalek::SigningKey::from_bytes(&decode(
+            code,
+        )?)))
+    }
+
+    /// The secret key as a share code, to be kept private.
+    pub fn to_code(&self) -> String {
+        codec::to_share_code(self.0.as_bytes())
+    }
+
+    /// The public key as a share code, for the organizer to publish.
+    pub fn public_key(&self) -> String {
+        codec::to_share_code(self.0.verifying_key().as_bytes())
+    }
+
+    pub fn sign(&self, content: &[u8]) -> Signature {
+        Signature {
+            hash: content_hash(content),
+            key: self.0.verifying_key().to_bytes(),
+            signature: self.0.sign(content).to_bytes(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub hash: [u8; 32],
+    pub key: [u8; 32],
+    pub signature: [u8; 64],
+}
+
+impl Signature {
+    /// Reads a signature in the format of the module documentation.
+    pub fn parse(text: &str) -> Result<Signature, SignatureError> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        let mut field = |name: &str| {
+            lines
+                .next()
+                .and_then(|line| line.strip_prefix(name))
+                .and_then(|value| value.strip_prefix(' '))
+                .ok_or(SignatureError::Malformed)
+        };
+        let hash = decode(field("sha256")?)?;
+        let key = decode(field("key")?)?;
+        let signature = decode(field("signature")?)?;
+        if lines.next().is_some() {
+            return Err(SignatureError::Malformed);
+        }
+        Ok(Signature {
+            hash,
+            key,
+            signature,
+        })
+    }
+
+    pub fn to_signature_string(&self) -> String {
+        format!(
+            "sha256 {}\nkey {}\nsignature {}\n",
+            codec::to_share_code(&self.hash),
+            codec::to_share_code(&self.key),
+            codec::to_share_code(&self.signature)
+        )
+    }
+
+    /// Checks that `content` is what the holder of `public_key`, a share code,
+    /// signed.
+    pub fn verify(&self, content: &[u8], public_key: &str) -> Result<(), SignatureError> {
+        let trusted = ed25519_dalek::VerifyingKey::from_bytes(&decode(public_key)?)
+            .map_err(|_| SignatureError::Malformed)?;
+        if trusted.to_bytes() != self.key {
+            return Err(SignatureError::OtherKey);
+        }
+        if content_hash(content) != self.hash {
+            return Err(SignatureError::HashMismatch);
+        }
+        trusted
+            .verify(
+                content,
+                &ed25519_dalek::Signature::from_bytes(&self.signature),
+            )
+            .map_err(|_| SignatureError::Invalid)
+    }
+}
+
+fn decode<const N: usize>(code: &str) -> Result<[u8; N], SignatureError> {
+    codec::from_share_code(code)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(SignatureError::Malformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let organizer = SigningKey::generate();
+        let restored = SigningKey::from_code(&organizer.to_code()).unwrap();
+        assert_eq!(restored.public_key(), organizer.public_key());
+
+        let content = b"pack First steps\n\npuzzle A\n..*\n";
+        let signature = organizer.sign(content);
+        let parsed = Signature::parse(&signature.to_signature_string()).unwrap();
+        assert_eq!(parsed, signature);
+        assert_eq!(parsed.verify(content, &organizer.public_key()), Ok(()));
+
+        assert_eq!(
+            parsed.verify(
+                b"pack First steps\n\npuzzle A\n.*.\n",
+                &organizer.public_key()
+            ),
+            Err(SignatureError::HashMismatch)
+        );
+        let stranger = SigningKey::generate();
+        assert_eq!(
+            parsed.verify(content, &stranger.public_key()),
+            Err(SignatureError::OtherKey)
+        );
+        let forged = Signature {
+            signature: stranger.sign(content).signature,
+            ..parsed.clone()
+        };
+        assert_eq!(
+            forged.verify(content, &organizer.public_key()),
+            Err(SignatureError::Invalid)
+        );
+        assert_eq!(
+            parsed.verify(content, "not a key"),
+            Err(SignatureError::Malformed)
+        );
+        assert_eq!(
+            Signature::parse("sha256 AAAA\n"),
+            Err(SignatureError::Malformed)
+        );
+    }
+}
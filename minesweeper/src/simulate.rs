@@ -0,0 +1,277 @@
+//! Plays many games headlessly to benchmark bot strategies, without writing a
+//! game loop for each of them.
+
+use std::time::Duration;
+
+use crate::board::{row_major, Board, GameState, Move, Square};
+use crate::config::GameConfig;
+use crate::solver::{bounded_mine_probabilities, Solver};
+use crate::view::BoardView;
+
+/// A bot choosing moves from what the player sees.
+pub trait Strategy {
+    /// Called before every game, for strategies that keep state between moves.
+    fn new_game(&mut self) {}
+
+    /// The next move, the first one has to open a cell. `None` gives up the game.
+    fn next_move(&mut self, view: &BoardView) -> Option<Move>;
+}
+
+/// The games `run_games` plays.
+#[derive(Debug, Clone)]
+pub struct SimulationConfig {
+    pub rows: usize,
+    pub cols: usize,
+    pub nr_mines: usize,
+    pub game: GameConfig,
+    /// Only play boards that can be solved without guessing.
+    pub no_guess: bool,
+    /// Game `i` uses the seed `seed + i`, so a run can be repeated.
+    pub seed: u64,
+}
+
+/// Results of `run_games`.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SimulationStats {
+    pub games: usize,
+    pub won: usize,
+    pub lost: usize,
+    /// Games given up by the strategy or still going after `MAX_MOVES`.
+    pub unfinished: usize,
+    /// Moves the board did not accept.
+    pub rejected_moves: usize,
+    /// Average playing time, which is the time the strategy took to think.
+    pub average_time: Duration,
+    /// Average 3BV of the boards, over the games that got past the first click.
+    pub average_bbbv: f64,
+}
+
+impl SimulationStats {
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            self.won as f64 / self.games as f64
+        }
+    }
+}
+
+/// Moves after which a game is counted as unfinished.
+pub const MAX_MOVES: usize = 100_000;
+
+/// Plays `n` games of `config` with `strategy`. Moves are not rate limited.
+pub fn run_games(
+    mut strategy: impl Strategy,
+    n: usize,
+    config: &SimulationConfig,
+) -> SimulationStats {
+    let mut stats = SimulationStats {
+        games: n,
+        ..SimulationStats::default()
+    };
+    let mut total_time = Duration::ZERO;
+    let (mut total_bbbv, mut started) = (0, 0);
+    for i in 0..n {
+        let mut board = Board::with_config(
+            config.rows,
+            config.cols,
+            config.nr_mines,
+            config.game.clone(),
+        );
+        board.config.min_move_interval = None;
+        let seed = config.seed.wrapping_add(i as u64);
+        strategy.new_game();
+        for _ in 0..MAX_MOVES {
+            if matches!(board.state, GameState::Won | GameState::Lost) {
+                break;
+            }
+            let Some(mv) = strategy.next_move(&board.view()) else {
+                break;
+            };
+            let accepted = match (board.state, mv) {
+                (GameState::Init, Move::Open(pos)) if board.contains(pos) => {
+                    if !(config.no_guess || board.guess_free())
+                        || board.init_mines_no_guess(pos, Some(seed)).is_err()
+                    {
+                        board.init_mines(pos, Some(seed));
+                    }
+                    true
+                }
+                (GameState::Init, _) => false,
+                _ => board.play(mv).is_ok(),
+            };
+            if !accepted {
+                stats.rejected_moves += 1;
+            }
+        }
+        match board.state {
+            GameState::Won => stats.won += 1,
+            GameState::Lost => stats.lost += 1,
+            GameState::Init | GameState::OnGoing => stats.unfinished += 1,
+        }
+        total_time += board.elapsed();
+        if let Some(metrics) = board.metrics() {
+            total_bbbv += metrics.bbbv;
+            started += 1;
+        }
+    }
+    if n > 0 {
+        stats.average_time = total_time / n as u32;
+    }
+    if started > 0 {
+        stats.average_bbbv = total_bbbv as f64 / started as f64;
+    }
+    stats
+}
+
+/// Flags the mines and opens the safe cells the solver finds, otherwise opens the
+/// cell least likely to be a mine, or one away from the numbers when there are too
+/// many placements to weigh. Starts in the center.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SolverStrategy;
+
+/// Why `SolverStrategy` picked a move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reason {
+    FirstClick,
+    /// The cell is provably a mine.
+    Mine,
+    /// The cell is provably safe.
+    Safe,
+    /// No cell is provably safe, the cell is a mine with this probability.
+    Guess(f64),
+}
+
+// search steps of the enumeration per move in the variants that need it, large
+// liar boards can have far too many placements to go through
+const SEARCH_BUDGET: usize = 200_000;
+
+impl SolverStrategy {
+    /// The next move together with the reason for it.
+    pub fn decide(&self, view: &BoardView) -> Option<(Move, Reason)> {
+        if view.state == GameState::Init {
+            let center = (view.cols / 2, view.rows / 2);
+            return Some((Move::Open(center), Reason::FirstClick));
+        }
+        let closed = |pos| view.get(pos) == Some(Square::NotYetOpened);
+        let mut budget = SEARCH_BUDGET;
+        let Some(solver) = Solver::bounded(view, &mut budget) else {
+            return blind_guess(view);
+        };
+        if let Some(pos) = solver.mine_cells().into_iter().find(|&p| closed(p)) {
+            return Some((Move::Flag(pos), Reason::Mine));
+        }
+        if let Some(pos) = solver.safe_cells().into_iter().find(|&p| closed(p)) {
+            return Some((Move::Open(pos), Reason::Safe));
+        }
+        let Some(probabilities) = bounded_mine_probabilities(view, &mut budget) else {
+            return blind_guess(view);
+        };
+        let pos = row_major(probabilities.keys().copied().filter(|&p| closed(p)))
+            .into_iter()
+            .min_by(|a, b| probabilities[a].total_cmp(&probabilities[b]))?;
+        // only mines are left, which still have to be flagged to win
+        Some(if probabilities[&pos] >= 1.0 {
+            (Move::Flag(pos), Reason::Mine)
+        } else {
+            (Move::Open(pos), Reason::Guess(probabilities[&pos]))
+        })
+    }
+}
+
+/// Opens a closed cell away from the numbers, preferring the top left, at the odds
+/// of the overall mine density.
+fn blind_guess(view: &BoardView) -> Option<(Move, Reason)> {
+    let hidden = |pos| matches!(view.get(pos), Some(Square::NotYetOpened | Square::Flag));
+    let cells: Vec<_> = (0..view.rows)
+        .flat_map(|y| (0..view.cols).map(move |x| (x, y)))
+        .filter(|&pos| hidden(pos))
+        .collect();
+    let p = view.nr_mines as f64 / cells.len().max(1) as f64;
+    let pos = cells
+        .iter()
+        .copied()
+        .filter(|&pos| view.get(pos) == Some(Square::NotYetOpened))
+        .min_by_key(|&pos| view.neighbors(pos).any(|n| !hidden(n)))?;
+    Some((Move::Open(pos), Reason::Guess(p)))
+}
+
+impl Strategy for SolverStrategy {
+    fn next_move(&mut self, view: &BoardView) -> Option<Move> {
+        self.decide(view).map(|(mv, _)| mv)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn beginner(seed: u64) -> SimulationConfig {
+        SimulationConfig {
+            rows: 9,
+            cols: 9,
+            nr_mines: 10,
+            game: GameConfig::default(),
+            no_guess: false,
+            seed,
+        }
+    }
+
+    // tries to flag before the first click, then gives up
+    #[derive(Default)]
+    struct GiveUp {
+        moves: usize,
+    }
+
+    impl Strategy for GiveUp {
+        fn new_game(&mut self) {
+            self.moves = 0;
+        }
+
+        fn next_move(&mut self, _: &BoardView) -> Option<Move> {
+            self.moves += 1;
+            (self.moves == 1).then_some(Move::Flag((0, 0)))
+        }
+    }
+
+    #[test]
+    fn test_run_games() {
+        let stats = run_games(SolverStrategy, 20, &beginner(1));
+        assert_eq!(stats.games, 20);
+        assert_eq!(stats.won + stats.lost + stats.unfinished, 20);
+        assert_eq!(stats.unfinished, 0);
+        assert_eq!(stats.rejected_moves, 0);
+        assert!(stats.win_rate() > 0.5);
+        assert!(stats.average_bbbv > 0.0);
+        let again = run_games(SolverStrategy, 20, &beginner(1));
+        assert_eq!(
+            (again.won, again.average_bbbv),
+            (stats.won, stats.average_bbbv)
+        );
+
+        let mut no_guess = beginner(1);
+        no_guess.no_guess = true;
+        assert_eq!(run_games(SolverStrategy, 5, &no_guess).won, 5);
+    }
+
+    #[test]
+    fn test_reasons() {
+        let mut board = Board::new(9, 9, 10);
+        assert_eq!(
+            SolverStrategy.decide(&board.view()),
+            Some((Move::Open((4, 4)), Reason::FirstClick))
+        );
+        board.init_mines((0, 0), Some(1));
+        let (mv, reason) = SolverStrategy.decide(&board.view()).unwrap();
+        assert!(matches!(mv, Move::Flag(_)));
+        assert_eq!(reason, Reason::Mine);
+    }
+
+    #[test]
+    fn test_unfinished_games() {
+        let stats = run_games(GiveUp::default(), 3, &beginner(1));
+        assert_eq!((stats.unfinished, stats.rejected_moves), (3, 3));
+        assert_eq!(stats.average_bbbv, 0.0);
+        assert_eq!(stats.win_rate(), 0.0);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::board::{Board, GameState};
+
+pub use minesweeper_solver::solver::{bounded_mine_probabilities, mine_probabilities, Solver};
+
+// search steps a single deduction may take in the variants that enumerate
+// placements, beyond them the board counts as needing a guess
+const DEDUCTION_BUDGET: usize = 20_000;
+
+/// Plays the board using only deductions and reports whether that wins the game.
+/// Deductions in the variants that enumerate placements take their search steps
+/// from `budget`, and the board counts as needing a guess when it runs out.
+pub(crate) fn solvable_without_guessing(board: &Board, budget: &mut usize) -> bool {
+    let mut board = board.clone();
+    board.config.min_move_interval = None;
+    while board.ongoing() {
+        let limit = DEDUCTION_BUDGET.min(*budget);
+        let mut steps = limit;
+        let solver = Solver::bounded(&board.view(), &mut steps);
+        *budget -= limit - steps;
+        let Some(solver) = solver else {
+            return false;
+        };
+        let mut progress = false;
+        for pos in solver.mine_cells() {
+            if !board.flagged_fields.contains(pos) {
+                progress |= board.flag(pos).is_ok();
+            }
+        }
+        for pos in solver.safe_cells() {
+            if board.flagged_fields.contains(pos) {
+                let _ = board.flag(pos);
+            }
+            progress |= board.open(pos).is_ok();
+        }
+        if !progress {
+            return false;
+        }
+    }
+    board.state == GameState::Won
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_single_cell_rule() {
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        let solver = Solver::new(&board.view());
+        // (2, 0), (2, 1) and (2, 2) all show a 1 with (3, 1) as the only shared neighbor
+        assert!(solver.mine_cells().contains(&(3, 1)));
+        for pos in solver.safe_cells() {
+            assert!(!board.mines.as_ref().unwrap().contains(pos));
+        }
+        for pos in solver.mine_cells() {
+            assert!(board.mines.as_ref().unwrap().contains(pos));
+        }
+    }
+
+    #[test]
+    fn test_probabilities_match_solver() {
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        let probabilities = board.mine_probabilities();
+        let solver = Solver::new(&board.view());
+        for pos in solver.safe_cells() {
+            assert_eq!(probabilities[&pos], 0.0);
+        }
+        for pos in solver.mine_cells() {
+            assert_eq!(probabilities[&pos], 1.0);
+        }
+        let unopened = 81 - board.opened_cells().len();
+        assert_eq!(probabilities.len(), unopened);
+        let total: f64 = probabilities.values().sum();
+        assert!((total - 10.0).abs() < 1e-9);
+        assert!(probabilities.values().all(|p| (0.0..=1.0).contains(p)));
+    }
+
+    #[test]
+    fn test_flags_are_not_trusted() {
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        board.flag((3, 0)).unwrap();
+        let solver = Solver::new(&board.view());
+        assert!(solver.safe_cells().contains(&(3, 0)));
+        assert!(!solver.mine_cells().contains(&(3, 0)));
+    }
+}
@@ -0,0 +1,312 @@
+use crate::board::{Board, GameState, Position, Square};
+use crate::view::BoardView;
+
+/// Full state for a client that joins or lost track of the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    pub seq: u64,
+    pub view: BoardView,
+}
+
+/// The cells that changed since the update before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Update {
+    pub seq: u64,
+    pub state: GameState,
+    pub cells: Vec<(Position, Square)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SyncMessage {
+    Snapshot(Snapshot),
+    Update(Update),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SyncError {
+    /// Updates were missed, the client needs a new snapshot.
+    Gap { expected: u64, received: u64 },
+    /// An update arrived before any snapshot.
+    NoSnapshot,
+    /// An update changes a cell outside the board.
+    OutOfBounds(Position),
+}
+
+/// Server side of the protocol: turns board changes into numbered updates, so
+/// spectators receive only the cells a move touched.
+#[derive(Debug, Clone)]
+pub struct SyncServer {
+    seq: u64,
+    view: BoardView,
+    // moves and undos of the board the view was taken from
+    moves: usize,
+    undos: usize,
+}
+
+impl SyncServer {
+    pub fn new(board: &Board) -> SyncServer {
+        SyncServer {
+            seq: 0,
+            view: board.view(),
+            moves: board.nr_moves(),
+            undos: board.undos,
+        }
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            seq: self.seq,
+            view: self.view.clone(),
+        }
+    }
+
+    /// The message bringing clients up to date with `board` after a move, `None`
+    /// if nothing visible changed. Only the cells of the last move are looked at,
+    /// so call it after every move; after more moves or a move taken back the
+    /// update comes from `resync`.
+    pub fn update(&mut self, board: &Board) -> Option<SyncMessage> {
+        if board.nr_moves() != self.moves + 1 || board.undos != self.undos {
+            return self.resync(board);
+        }
+        self.moves = board.nr_moves();
+        // anti-mines are placed with the mines on the first click
+        if board.anti_mines.len() != self.view.nr_anti_mines {
+            return Some(self.send_snapshot(board.view()));
+        }
+        let mut changed = board.last_changed().to_vec();
+        if board.lost() && self.view.state != GameState::Lost {
+            changed.extend(board.reveal_mines().unwrap_or_default());
+        }
+        changed.sort_by_key(|&(x, y)| (y, x));
+        changed.dedup();
+        let cells = changed
+            .into_iter()
+            .map(|pos| (pos, board.square(pos)))
+            .filter(|&((x, y), square)| self.view.squares[y][x] != square)
+            .collect();
+        self.send(board.state, cells)
+    }
+
+    /// Compares the whole board with what clients have, for a board restarted,
+    /// swapped for another or changed by more than one move since the last
+    /// update. A board with other dimensions, mine count or rules is sent as a
+    /// snapshot.
+    pub fn resync(&mut self, board: &Board) -> Option<SyncMessage> {
+        let view = board.view();
+        self.moves = board.nr_moves();
+        self.undos = board.undos;
+        let rules = |view: &BoardView| {
+            (
+                view.rows,
+                view.cols,
+                view.nr_mines,
+                view.wrap_edges,
+                view.nr_anti_mines,
+                view.adjacency,
+                view.liar,
+            )
+        };
+        if rules(&view) != rules(&self.view) || view.wormholes != self.view.wormholes {
+            return Some(self.send_snapshot(view));
+        }
+        let mut cells = Vec::new();
+        for (y, (old, new)) in self
+            .view
+            .squares
+            .iter()
+            .zip(view.squares.iter())
+            .enumerate()
+        {
+            for (x, (a, b)) in old.iter().zip(new.iter()).enumerate() {
+                if a != b {
+                    cells.push(((x, y), *b));
+                }
+            }
+        }
+        self.send(view.state, cells)
+    }
+
+    fn send_snapshot(&mut self, view: BoardView) -> SyncMessage {
+        self.seq += 1;
+        self.view = view;
+        SyncMessage::Snapshot(self.snapshot())
+    }
+
+    fn send(&mut self, state: GameState, cells: Vec<(Position, Square)>) -> Option<SyncMessage> {
+        if cells.is_empty() && state == self.view.state {
+            return None;
+        }
+        for &((x, y), square) in cells.iter() {
+            self.view.squares[y][x] = square;
+        }
+        self.view.state = state;
+        self.seq += 1;
+        Some(SyncMessage::Update(Update {
+            seq: self.seq,
+            state,
+            cells,
+        }))
+    }
+}
+
+/// Client side of the protocol, keeping a copy of the view up to date.
+#[derive(Debug, Clone, Default)]
+pub struct SyncClient {
+    current: Option<Snapshot>,
+}
+
+impl SyncClient {
+    pub fn view(&self) -> Option<&BoardView> {
+        self.current.as_ref().map(|s| &s.view)
+    }
+
+    pub fn seq(&self) -> Option<u64> {
+        self.current.as_ref().map(|s| s.seq)
+    }
+
+    /// Applies a message, ignoring updates that were already applied. On a gap
+    /// the view is left as is until a snapshot arrives.
+    pub fn apply(&mut self, message: &SyncMessage) -> Result<(), SyncError> {
+        let update = match message {
+            SyncMessage::Snapshot(snapshot) => {
+                self.current = Some(snapshot.clone());
+                return Ok(());
+            }
+            SyncMessage::Update(update) => update,
+        };
+        let current = self.current.as_mut().ok_or(SyncError::NoSnapshot)?;
+        if update.seq <= current.seq {
+            return Ok(());
+        }
+        if update.seq != current.seq + 1 {
+            return Err(SyncError::Gap {
+                expected: current.seq + 1,
+                received: update.seq,
+            });
+        }
+        let squares = &mut current.view.squares;
+        if let Some(&(pos, _)) = update
+            .cells
+            .iter()
+            .find(|((x, y), _)| squares.get(*y).is_none_or(|row| row.get(*x).is_none()))
+        {
+            return Err(SyncError::OutOfBounds(pos));
+        }
+        for &((x, y), square) in update.cells.iter() {
+            squares[y][x] = square;
+        }
+        current.view.state = update.state;
+        current.seq = update.seq;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_mirrors_board() {
+        let mut board = Board::new(9, 9, 10);
+        let mut server = SyncServer::new(&board);
+        let mut client = SyncClient::default();
+        client
+            .apply(&SyncMessage::Snapshot(server.snapshot()))
+            .unwrap();
+
+        board.init_mines((0, 0), Some(1));
+        let opening = server.update(&board).unwrap();
+        client.apply(&opening).unwrap();
+        assert!(server.update(&board).is_none());
+
+        board.flag((3, 1)).unwrap();
+        let SyncMessage::Update(flag) = server.update(&board).unwrap() else {
+            panic!("expected an update");
+        };
+        assert_eq!(flag.cells, vec![((3, 1), Square::Flag)]);
+        client.apply(&SyncMessage::Update(flag.clone())).unwrap();
+        // duplicates are harmless
+        client.apply(&SyncMessage::Update(flag)).unwrap();
+
+        // losing reveals every mine in one update
+        board.open((4, 3)).unwrap();
+        client.apply(&server.update(&board).unwrap()).unwrap();
+        assert_eq!(client.view(), Some(&board.view()));
+        assert_eq!(client.seq(), Some(3));
+    }
+
+    #[test]
+    fn test_updates_follow_undo_and_missed_moves() {
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        let mut server = SyncServer::new(&board);
+        let mut client = SyncClient::default();
+        client
+            .apply(&SyncMessage::Snapshot(server.snapshot()))
+            .unwrap();
+        for _ in 0..3 {
+            board.play(board.hint().unwrap()).unwrap();
+            client.apply(&server.update(&board).unwrap()).unwrap();
+            assert_eq!(client.view(), Some(&board.view()));
+        }
+
+        board.undo().unwrap();
+        client.apply(&server.update(&board).unwrap()).unwrap();
+        assert_eq!(client.view(), Some(&board.view()));
+
+        board.play(board.hint().unwrap()).unwrap();
+        board.play(board.hint().unwrap()).unwrap();
+        client.apply(&server.update(&board).unwrap()).unwrap();
+        assert_eq!(client.view(), Some(&board.view()));
+        assert!(server.update(&board).is_none());
+    }
+
+    #[test]
+    fn test_gap_needs_resync() {
+        let mut board = Board::new(9, 9, 10);
+        let mut server = SyncServer::new(&board);
+        let mut client = SyncClient::default();
+        let update = SyncMessage::Update(Update {
+            seq: 1,
+            state: GameState::Init,
+            cells: Vec::new(),
+        });
+        assert_eq!(client.apply(&update), Err(SyncError::NoSnapshot));
+        client
+            .apply(&SyncMessage::Snapshot(server.snapshot()))
+            .unwrap();
+        let outside = SyncMessage::Update(Update {
+            seq: 1,
+            state: GameState::OnGoing,
+            cells: vec![((0, 0), Square::Flag), ((9, 0), Square::Flag)],
+        });
+        assert_eq!(client.apply(&outside), Err(SyncError::OutOfBounds((9, 0))));
+        assert_eq!(client.view(), Some(&board.view()));
+
+        board.init_mines((0, 0), Some(1));
+        let _missed = server.update(&board).unwrap();
+        board.flag((3, 1)).unwrap();
+        let next = server.update(&board).unwrap();
+        assert_eq!(
+            client.apply(&next),
+            Err(SyncError::Gap {
+                expected: 1,
+                received: 2
+            })
+        );
+        client
+            .apply(&SyncMessage::Snapshot(server.snapshot()))
+            .unwrap();
+        assert_eq!(client.view(), Some(&board.view()));
+
+        // a new board of another size is sent whole
+        let bigger = Board::new(16, 16, 40);
+        assert!(matches!(
+            server.update(&bigger),
+            Some(SyncMessage::Snapshot(_))
+        ));
+    }
+}
@@ -0,0 +1,94 @@
+use crate::board::Square;
+use crate::view::BoardView;
+
+/// Low resolution RGB preview of a board, `scale` pixels per cell.
+///
+/// Built from a `BoardView`, so previews of games in progress never show mines.
+pub struct Thumbnail {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+fn square_color(square: Square) -> [u8; 3] {
+    match square {
+        Square::NotYetOpened => [128, 128, 128],
+        Square::Flag => [220, 40, 40],
+        Square::Mine => [20, 20, 20],
+        Square::Opened(..0) => [170, 80, 200],
+        Square::Opened(0) => [230, 230, 230],
+        Square::Opened(1) => [60, 90, 230],
+        Square::Opened(2) => [40, 160, 60],
+        Square::Opened(3) => [230, 60, 60],
+        Square::Opened(4) => [30, 30, 140],
+        Square::Opened(5) => [140, 30, 30],
+        Square::Opened(6) => [30, 150, 150],
+        Square::Opened(_) => [60, 60, 60],
+    }
+}
+
+impl Thumbnail {
+    pub fn new(view: &BoardView, scale: u32) -> Thumbnail {
+        let scale = scale.max(1);
+        let (width, height) = (view.cols as u32 * scale, view.rows as u32 * scale);
+        let mut pixels = Vec::with_capacity((width * height * 3) as usize);
+        for row in view.squares.iter() {
+            let line: Vec<u8> = row
+                .iter()
+                .flat_map(|&square| {
+                    let color = square_color(square);
+                    (0..scale).flat_map(move |_| color)
+                })
+                .collect();
+            for _ in 0..scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        Thumbnail {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 3] {
+        let i = ((y * self.width + x) * 3) as usize;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Board;
+
+    #[test]
+    fn test_thumbnail_png() {
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        board.flag((3, 1)).unwrap();
+        let thumbnail = Thumbnail::new(&board.view(), 2);
+        assert_eq!((thumbnail.width, thumbnail.height), (18, 18));
+        assert_eq!(thumbnail.pixel(0, 0), square_color(Square::Opened(0)));
+        assert_eq!(thumbnail.pixel(7, 3), square_color(Square::Flag));
+        assert_eq!(thumbnail.pixel(17, 17), square_color(Square::NotYetOpened));
+
+        let png = thumbnail.to_png().unwrap();
+        let decoder = png::Decoder::new(png.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut decoded = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut decoded).unwrap();
+        assert_eq!(decoded, thumbnail.pixels);
+    }
+}
@@ -0,0 +1,454 @@
+//! Errors as frontends show them: a message key plus the values to fill in.
+//! Every engine error converts into a `UserFacingError` with an exhaustive
+//! match, so a new variant doesn't compile until it has a message. The keys
+//! look up templates in a catalog, `ENGLISH` unless a frontend brings another
+//! language.
+
+use std::fmt::Display;
+
+use crate::board::{ChordError, FlagError, GenerationError, MoveError, OpenError, UndoError};
+use crate::codec::DecodeError;
+use crate::command::CommandError;
+use crate::layout::LayoutError;
+use crate::mbf::MbfError;
+use crate::pack::PackError;
+use crate::replay::ReplayError;
+#[cfg(feature = "signing")]
+use crate::signing::SignatureError;
+use crate::variant::VariantError;
+
+/// Message templates by key, `{name}` is replaced by the parameter `name`.
+pub type Catalog = [(&'static str, &'static str)];
+
+pub const ENGLISH: &Catalog = &[
+    ("out_of_bounds", "That cell is outside the board."),
+    (
+        "too_fast",
+        "Moves are rate limited, wait a moment and try again.",
+    ),
+    ("already_won", "The game is already won."),
+    ("already_lost", "The game is already lost."),
+    ("mines_not_placed", "Open a cell first."),
+    ("open.already_open", "This cell is already open."),
+    (
+        "open.flagged",
+        "This cell is flagged, remove the flag to open it.",
+    ),
+    ("flag.already_open", "Opened cells cannot be flagged."),
+    (
+        "flag.not_a_mine",
+        "There is no mine there, look again (assisted mode).",
+    ),
+    ("chord.not_open", "Only opened numbers can be chorded."),
+    (
+        "chord.flag_count",
+        "The flags around this number don't match it.",
+    ),
+    (
+        "chord.nothing_to_open",
+        "There is nothing left to open around this number.",
+    ),
+    ("undo.nothing", "There is no move to undo."),
+    (
+        "undo.no_replay",
+        "Moves on a loaded board cannot be undone.",
+    ),
+    ("command.unknown", "Unknown command, try again."),
+    (
+        "command.invalid_number",
+        "Could not parse the number, try again.",
+    ),
+    (
+        "command.not_a_move",
+        "Only open, flag and chord commands can be played from a file.",
+    ),
+    (
+        "generation.exhausted",
+        "No board satisfying the constraints was found.",
+    ),
+    (
+        "generation.cancelled",
+        "Generating the board was cancelled.",
+    ),
+    ("variant.unknown", "There is no variant called {name}."),
+    (
+        "variant.duplicate",
+        "A variant called {name} is already registered.",
+    ),
+    ("mbf.unexpected_end", "The board file ends too early."),
+    (
+        "mbf.trailing_bytes",
+        "The board file continues after the last mine.",
+    ),
+    (
+        "mbf.invalid_size",
+        "The board in the file has no room for a safe cell.",
+    ),
+    (
+        "mbf.mine_out_of_bounds",
+        "The mine at ({x},{y}) is outside the board.",
+    ),
+    (
+        "mbf.duplicate_mine",
+        "The mine at ({x},{y}) is listed twice.",
+    ),
+    ("mbf.too_large", "The board is too large for a board file."),
+    (
+        "mbf.mines_not_placed",
+        "The mines are placed at the first click, there is no layout yet.",
+    ),
+    ("mbf.anti_mines", "Board files cannot hold anti-mines."),
+    ("decode.unexpected_end", "The code ends too early."),
+    ("decode.wrong_tag", "The code holds something else."),
+    (
+        "replay.missing_first_click",
+        "The game does not start with opening a cell.",
+    ),
+    (
+        "replay.invalid_move",
+        "Move {move} of the game is not possible on its board.",
+    ),
+    (
+        "decode.unsupported_version",
+        "The code has version {version}, which is not supported.",
+    ),
+    ("decode.invalid_value", "The code holds an invalid value."),
+    ("decode.trailing_bytes", "The code continues after its end."),
+    ("layout.empty", "The layout has no rows."),
+    (
+        "layout.ragged_row",
+        "Row {row} of the layout has another length than the first.",
+    ),
+    (
+        "layout.invalid_char",
+        "The layout holds '{char}' at ({x},{y}), which is not a cell.",
+    ),
+    (
+        "layout.wrong_count",
+        "The number at ({x},{y}) doesn't match its neighboring mines.",
+    ),
+    ("layout.no_safe_cell", "Every cell of the layout is a mine."),
+    ("layout.invalid_wormhole", "Invalid wormhole line: {line}"),
+    ("pack.missing_name", "The pack has no name line."),
+    (
+        "pack.unknown_line",
+        "Line {line} of the pack is not understood.",
+    ),
+    ("pack.no_puzzles", "The pack holds no puzzles."),
+    (
+        "pack.invalid_puzzle",
+        "Puzzle {puzzle} of the pack is invalid: {reason}",
+    ),
+    ("signature.malformed", "The signature or key can't be read."),
+    (
+        "signature.hash_mismatch",
+        "The file was changed after it was signed.",
+    ),
+    (
+        "signature.other_key",
+        "The file was signed with another key.",
+    ),
+    ("signature.invalid", "The signature doesn't match the file."),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserFacingError {
+    pub key: &'static str,
+    pub params: Vec<(&'static str, String)>,
+}
+
+impl UserFacingError {
+    pub fn new(key: &'static str) -> UserFacingError {
+        UserFacingError {
+            key,
+            params: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, name: &'static str, value: impl Display) -> UserFacingError {
+        self.params.push((name, value.to_string()));
+        self
+    }
+
+    /// The message from `catalog`, the key itself if the catalog lacks it.
+    pub fn message(&self, catalog: &Catalog) -> String {
+        let template = catalog
+            .iter()
+            .find(|(key, _)| *key == self.key)
+            .map_or(self.key, |(_, template)| template);
+        self.params
+            .iter()
+            .fold(template.to_string(), |message, (name, value)| {
+                message.replace(&format!("{{{name}}}"), value)
+            })
+    }
+}
+
+/// The English message.
+impl Display for UserFacingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message(ENGLISH))
+    }
+}
+
+impl From<OpenError> for UserFacingError {
+    fn from(e: OpenError) -> UserFacingError {
+        UserFacingError::new(match e {
+            OpenError::AlreadyOpen => "open.already_open",
+            OpenError::AlreadyFlagged => "open.flagged",
+            OpenError::AlreadyLost => "already_lost",
+            OpenError::AlreadyWon => "already_won",
+            OpenError::MinesNotInit => "mines_not_placed",
+            OpenError::OutOfBounds => "out_of_bounds",
+            OpenError::TooFast => "too_fast",
+        })
+    }
+}
+
+impl From<FlagError> for UserFacingError {
+    fn from(e: FlagError) -> UserFacingError {
+        UserFacingError::new(match e {
+            FlagError::AlreadyOpen => "flag.already_open",
+            FlagError::AlreadyLost => "already_lost",
+            FlagError::AlreadyWon => "already_won",
+            FlagError::MinesNotInit => "mines_not_placed",
+            FlagError::NotAMine => "flag.not_a_mine",
+            FlagError::OutOfBounds => "out_of_bounds",
+            FlagError::TooFast => "too_fast",
+        })
+    }
+}
+
+impl From<ChordError> for UserFacingError {
+    fn from(e: ChordError) -> UserFacingError {
+        UserFacingError::new(match e {
+            ChordError::AlreadyLost => "already_lost",
+            ChordError::AlreadyWon => "already_won",
+            ChordError::FlagCountMismatch => "chord.flag_count",
+            ChordError::MinesNotInit => "mines_not_placed",
+            ChordError::NotOpen => "chord.not_open",
+            ChordError::NothingToOpen => "chord.nothing_to_open",
+            ChordError::OutOfBounds => "out_of_bounds",
+            ChordError::TooFast => "too_fast",
+        })
+    }
+}
+
+impl From<MoveError> for UserFacingError {
+    fn from(e: MoveError) -> UserFacingError {
+        match e {
+            MoveError::Open(e) => e.into(),
+            MoveError::Flag(e) => e.into(),
+            MoveError::Chord(e) => e.into(),
+        }
+    }
+}
+
+impl From<UndoError> for UserFacingError {
+    fn from(e: UndoError) -> UserFacingError {
+        UserFacingError::new(match e {
+            UndoError::NothingToUndo => "undo.nothing",
+            UndoError::NoReplay => "undo.no_replay",
+        })
+    }
+}
+
+impl From<CommandError> for UserFacingError {
+    fn from(e: CommandError) -> UserFacingError {
+        UserFacingError::new(match e {
+            CommandError::Unknown => "command.unknown",
+            CommandError::InvalidNumber => "command.invalid_number",
+            CommandError::NotAMove => "command.not_a_move",
+        })
+    }
+}
+
+impl From<GenerationError> for UserFacingError {
+    fn from(e: GenerationError) -> UserFacingError {
+        match e {
+            GenerationError::AttemptsExhausted => UserFacingError::new("generation.exhausted"),
+            GenerationError::Cancelled => UserFacingError::new("generation.cancelled"),
+        }
+    }
+}
+
+impl From<VariantError> for UserFacingError {
+    fn from(e: VariantError) -> UserFacingError {
+        match e {
+            VariantError::Unknown(name) => {
+                UserFacingError::new("variant.unknown").with("name", name)
+            }
+            VariantError::DuplicateName(name) => {
+                UserFacingError::new("variant.duplicate").with("name", name)
+            }
+        }
+    }
+}
+
+impl From<ReplayError> for UserFacingError {
+    fn from(e: ReplayError) -> UserFacingError {
+        match e {
+            ReplayError::MissingFirstClick => UserFacingError::new("replay.missing_first_click"),
+            ReplayError::InvalidMove(index, _) => {
+                UserFacingError::new("replay.invalid_move").with("move", index + 1)
+            }
+        }
+    }
+}
+
+impl From<MbfError> for UserFacingError {
+    fn from(e: MbfError) -> UserFacingError {
+        let at = |key, (x, y)| UserFacingError::new(key).with("x", x).with("y", y);
+        match e {
+            MbfError::UnexpectedEnd => UserFacingError::new("mbf.unexpected_end"),
+            MbfError::TrailingBytes => UserFacingError::new("mbf.trailing_bytes"),
+            MbfError::InvalidSize => UserFacingError::new("mbf.invalid_size"),
+            MbfError::MineOutOfBounds(pos) => at("mbf.mine_out_of_bounds", pos),
+            MbfError::DuplicateMine(pos) => at("mbf.duplicate_mine", pos),
+            MbfError::TooLarge => UserFacingError::new("mbf.too_large"),
+            MbfError::MinesNotInit => UserFacingError::new("mbf.mines_not_placed"),
+            MbfError::AntiMines => UserFacingError::new("mbf.anti_mines"),
+        }
+    }
+}
+
+impl From<DecodeError> for UserFacingError {
+    fn from(e: DecodeError) -> UserFacingError {
+        match e {
+            DecodeError::UnexpectedEnd => UserFacingError::new("decode.unexpected_end"),
+            DecodeError::WrongTag => UserFacingError::new("decode.wrong_tag"),
+            DecodeError::UnsupportedVersion(version) => {
+                UserFacingError::new("decode.unsupported_version").with("version", version)
+            }
+            DecodeError::InvalidValue => UserFacingError::new("decode.invalid_value"),
+            DecodeError::TrailingBytes => UserFacingError::new("decode.trailing_bytes"),
+        }
+    }
+}
+
+impl From<LayoutError> for UserFacingError {
+    fn from(e: LayoutError) -> UserFacingError {
+        let at = |key, (x, y)| UserFacingError::new(key).with("x", x).with("y", y);
+        match e {
+            LayoutError::Empty => UserFacingError::new("layout.empty"),
+            LayoutError::RaggedRow(row) => {
+                UserFacingError::new("layout.ragged_row").with("row", row + 1)
+            }
+            LayoutError::InvalidChar(pos, c) => at("layout.invalid_char", pos).with("char", c),
+            LayoutError::WrongCount(pos) => at("layout.wrong_count", pos),
+            LayoutError::NoSafeCell => UserFacingError::new("layout.no_safe_cell"),
+            LayoutError::InvalidWormhole(line) => {
+                UserFacingError::new("layout.invalid_wormhole").with("line", line)
+            }
+        }
+    }
+}
+
+impl From<PackError> for UserFacingError {
+    fn from(e: PackError) -> UserFacingError {
+        match e {
+            PackError::MissingName => UserFacingError::new("pack.missing_name"),
+            PackError::UnknownLine(line) => {
+                UserFacingError::new("pack.unknown_line").with("line", line + 1)
+            }
+            PackError::NoPuzzles => UserFacingError::new("pack.no_puzzles"),
+            PackError::InvalidPuzzle(index, e) => UserFacingError::new("pack.invalid_puzzle")
+                .with("puzzle", index + 1)
+                .with("reason", UserFacingError::from(e)),
+        }
+    }
+}
+
+#[cfg(feature = "signing")]
+impl From<SignatureError> for UserFacingError {
+    fn from(e: SignatureError) -> UserFacingError {
+        UserFacingError::new(match e {
+            SignatureError::Malformed => "signature.malformed",
+            SignatureError::HashMismatch => "signature.hash_mismatch",
+            SignatureError::OtherKey => "signature.other_key",
+            SignatureError::Invalid => "signature.invalid",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_message_is_in_the_catalog() {
+        #[cfg_attr(not(feature = "signing"), allow(unused_mut))]
+        let mut errors: Vec<UserFacingError> = vec![
+            OpenError::AlreadyOpen.into(),
+            OpenError::AlreadyFlagged.into(),
+            OpenError::AlreadyLost.into(),
+            OpenError::AlreadyWon.into(),
+            OpenError::MinesNotInit.into(),
+            OpenError::OutOfBounds.into(),
+            OpenError::TooFast.into(),
+            FlagError::AlreadyOpen.into(),
+            FlagError::NotAMine.into(),
+            ChordError::FlagCountMismatch.into(),
+            ChordError::NotOpen.into(),
+            ChordError::NothingToOpen.into(),
+            UndoError::NothingToUndo.into(),
+            UndoError::NoReplay.into(),
+            CommandError::Unknown.into(),
+            CommandError::InvalidNumber.into(),
+            CommandError::NotAMove.into(),
+            GenerationError::AttemptsExhausted.into(),
+            GenerationError::Cancelled.into(),
+            VariantError::Unknown("x".to_string()).into(),
+            VariantError::DuplicateName("x").into(),
+            MbfError::UnexpectedEnd.into(),
+            MbfError::TrailingBytes.into(),
+            MbfError::InvalidSize.into(),
+            MbfError::MineOutOfBounds((1, 2)).into(),
+            MbfError::DuplicateMine((1, 2)).into(),
+            MbfError::TooLarge.into(),
+            MbfError::MinesNotInit.into(),
+            MbfError::AntiMines.into(),
+            ReplayError::MissingFirstClick.into(),
+            ReplayError::InvalidMove(1, MoveError::Open(OpenError::AlreadyOpen)).into(),
+            DecodeError::UnexpectedEnd.into(),
+            DecodeError::WrongTag.into(),
+            DecodeError::UnsupportedVersion(3).into(),
+            DecodeError::InvalidValue.into(),
+            DecodeError::TrailingBytes.into(),
+            LayoutError::Empty.into(),
+            LayoutError::RaggedRow(1).into(),
+            LayoutError::InvalidChar((1, 2), '?').into(),
+            LayoutError::WrongCount((1, 2)).into(),
+            LayoutError::NoSafeCell.into(),
+            LayoutError::InvalidWormhole("wormhole 0,0".to_string()).into(),
+            PackError::MissingName.into(),
+            PackError::UnknownLine(1).into(),
+            PackError::NoPuzzles.into(),
+            PackError::InvalidPuzzle(1, LayoutError::Empty).into(),
+        ];
+        #[cfg(feature = "signing")]
+        errors.extend([
+            SignatureError::Malformed.into(),
+            SignatureError::HashMismatch.into(),
+            SignatureError::OtherKey.into(),
+            SignatureError::Invalid.into(),
+        ]);
+        for error in &errors {
+            let message = error.to_string();
+            assert_ne!(message, error.key);
+            assert!(!message.contains('{'), "{message}");
+        }
+        assert_eq!(
+            UserFacingError::from(MbfError::DuplicateMine((1, 2))).to_string(),
+            "The mine at (1,2) is listed twice."
+        );
+        let unknown = UserFacingError::new("missing").with("x", 1);
+        assert_eq!(unknown.message(ENGLISH), "missing");
+        let german = [("undo.nothing", "Es gibt keinen Zug zum Zurücknehmen.")];
+        let undo = UserFacingError::from(UndoError::NothingToUndo);
+        assert_eq!(
+            undo.message(&german),
+            "Es gibt keinen Zug zum Zurücknehmen."
+        );
+    }
+}
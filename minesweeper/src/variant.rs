@@ -0,0 +1,546 @@
+//! Game variants, bundles of rules that can be added without touching the engine.
+//!
+//! A variant adjusts the `GameConfig` (edges, win condition, ...), decides where
+//! the mines go and which of them are anti-mines, and can set its own objective. Variants are registered by name, boards remember the name of
+//! theirs in `GameConfig::variant` so replays place the same mines.
+
+use std::collections::HashSet;
+use std::sync::{LazyLock, RwLock};
+
+use rand::seq::index::sample;
+use rand::seq::IndexedRandom;
+use rand::{Rng, RngCore};
+
+use crate::board::{row_major, Adjacency, Position};
+use crate::config::{GameConfig, WinCondition};
+use crate::objective::{Objective, Treasures};
+
+/// What a placement strategy has to respect.
+#[derive(Debug, Clone, Copy)]
+pub struct Placement<'a> {
+    pub rows: usize,
+    pub cols: usize,
+    pub nr_mines: usize,
+    /// Cells around the first click that must stay free.
+    pub safe: &'a HashSet<Position>,
+}
+
+pub trait Variant: Send + Sync {
+    /// Unique name, also stored in saved games and replays.
+    fn name(&self) -> &'static str;
+
+    fn description(&self) -> &'static str;
+
+    /// Applies the rules of the variant on top of the player's settings.
+    fn configure(&self, _config: &mut GameConfig) {}
+
+    /// Exactly `nr_mines` positions on the board, none of them safe. Only `rng`
+    /// may be used for randomness, so a seed always gives the same layout.
+    fn place_mines(&self, placement: Placement<'_>, rng: &mut dyn RngCore) -> HashSet<Position> {
+        uniform_mines(placement, rng)
+    }
+
+    /// The `mines` that subtract one from the counts around them instead of adding
+    /// one, none by default. Called right after `place_mines` with the same `rng`.
+    fn place_anti_mines(
+        &self,
+        _mines: &HashSet<Position>,
+        _rng: &mut dyn RngCore,
+    ) -> HashSet<Position> {
+        HashSet::new()
+    }
+
+    /// Pairs of cells that touch each other's neighbors, none by default. Placed
+    /// before the mines, with a `rng` of its own.
+    fn place_wormholes(
+        &self,
+        _rows: usize,
+        _cols: usize,
+        _rng: &mut dyn RngCore,
+    ) -> Vec<(Position, Position)> {
+        Vec::new()
+    }
+
+    /// Safe cells the objective asks to open, none by default. Placed after the
+    /// mines, with a `rng` of its own.
+    fn place_treasures(
+        &self,
+        _placement: Placement<'_>,
+        _mines: &HashSet<Position>,
+        _rng: &mut dyn RngCore,
+    ) -> HashSet<Position> {
+        HashSet::new()
+    }
+
+    /// What wins the game, the `win_condition` of the config if `None`.
+    fn objective(&self) -> Option<&dyn Objective> {
+        None
+    }
+
+    /// Whether games should only be dealt when they can be solved without
+    /// guessing, see `Board::init_mines_no_guess`.
+    fn guess_free(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum VariantError {
+    /// No variant is registered under the name.
+    Unknown(String),
+    /// Another variant is already registered under the name.
+    DuplicateName(&'static str),
+}
+
+/// Every cell outside the safe area is equally likely to hold a mine.
+pub fn uniform_mines(placement: Placement<'_>, rng: &mut dyn RngCore) -> HashSet<Position> {
+    let mut mines = HashSet::new();
+    while mines.len() < placement.nr_mines {
+        let x: usize = rng.random_range(0..placement.cols);
+        let y: usize = rng.random_range(0..placement.rows);
+        if !placement.safe.contains(&(x, y)) {
+            mines.insert((x, y));
+        }
+    }
+    mines
+}
+
+/// The game as it has always been played.
+pub struct Classic;
+
+impl Variant for Classic {
+    fn name(&self) -> &'static str {
+        "classic"
+    }
+
+    fn description(&self) -> &'static str {
+        "Flag every mine and open every other cell"
+    }
+}
+
+/// Opposite edges of the board touch.
+pub struct Torus;
+
+impl Variant for Torus {
+    fn name(&self) -> &'static str {
+        "torus"
+    }
+
+    fn description(&self) -> &'static str {
+        "The board wraps around, cells on opposite edges are neighbors"
+    }
+
+    fn configure(&self, config: &mut GameConfig) {
+        config.wrap_edges = true;
+    }
+}
+
+/// Won by opening every safe cell, as in most other implementations.
+pub struct Flagless;
+
+impl Variant for Flagless {
+    fn name(&self) -> &'static str {
+        "flagless"
+    }
+
+    fn description(&self) -> &'static str {
+        "Opening every safe cell wins, flags are optional"
+    }
+
+    fn configure(&self, config: &mut GameConfig) {
+        config.win_condition = WinCondition::OpenAllSafe;
+    }
+}
+
+/// A third of the mines are anti-mines, so counts can be zero or negative next
+/// to mines.
+pub struct AntiMines;
+
+impl Variant for AntiMines {
+    fn name(&self) -> &'static str {
+        "anti-mines"
+    }
+
+    fn description(&self) -> &'static str {
+        "A third of the mines are anti-mines that count as -1 in the numbers"
+    }
+
+    fn place_anti_mines(
+        &self,
+        mines: &HashSet<Position>,
+        rng: &mut dyn RngCore,
+    ) -> HashSet<Position> {
+        let mines = row_major(mines.iter().copied());
+        mines
+            .choose_multiple(rng, mines.len() / 3)
+            .copied()
+            .collect()
+    }
+}
+
+/// Numbers count the mines a knight's move away.
+pub struct Knight;
+
+impl Variant for Knight {
+    fn name(&self) -> &'static str {
+        "knight"
+    }
+
+    fn description(&self) -> &'static str {
+        "Numbers count the mines a knight's move away instead of the surrounding ones"
+    }
+
+    fn configure(&self, config: &mut GameConfig) {
+        config.adjacency = Adjacency::Knight;
+    }
+}
+
+/// A few pairs of cells joined by wormholes.
+pub struct Wormholes;
+
+impl Variant for Wormholes {
+    fn name(&self) -> &'static str {
+        "wormholes"
+    }
+
+    fn description(&self) -> &'static str {
+        "Pairs of wormhole cells also touch the neighbors of their partner"
+    }
+
+    fn place_wormholes(
+        &self,
+        rows: usize,
+        cols: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<(Position, Position)> {
+        // one pair for every 40 cells, at least one
+        let pairs = (rows * cols / 40).max(1).min(rows * cols / 2);
+        let cells = sample(rng, rows * cols, 2 * pairs).into_vec();
+        let position = |i: usize| (i % cols, i / cols);
+        cells
+            .chunks(2)
+            .map(|pair| (position(pair[0]), position(pair[1])))
+            .collect()
+    }
+}
+
+/// Every number lies, showing one more or one less than the actual count.
+pub struct Liar;
+
+impl Variant for Liar {
+    fn name(&self) -> &'static str {
+        "liar"
+    }
+
+    fn description(&self) -> &'static str {
+        "Every number is off by exactly one, up or down"
+    }
+
+    fn configure(&self, config: &mut GameConfig) {
+        config.liar = true;
+    }
+
+    // lying numbers leave too many boards to chance otherwise
+    fn guess_free(&self) -> bool {
+        true
+    }
+}
+
+/// Open a few marked cells to win, the rest of the board doesn't matter.
+pub struct TreasureHunt;
+
+impl Variant for TreasureHunt {
+    fn name(&self) -> &'static str {
+        "treasure"
+    }
+
+    fn description(&self) -> &'static str {
+        "Open the marked treasure cells to win, the rest of the board doesn't matter"
+    }
+
+    fn place_treasures(
+        &self,
+        placement: Placement<'_>,
+        mines: &HashSet<Position>,
+        rng: &mut dyn RngCore,
+    ) -> HashSet<Position> {
+        // one for every 27 cells, at least one
+        let count = (placement.rows * placement.cols / 27).max(1);
+        let cells = (0..placement.rows).flat_map(|y| (0..placement.cols).map(move |x| (x, y)));
+        let free: Vec<Position> = cells
+            .filter(|pos| !mines.contains(pos) && !placement.safe.contains(pos))
+            .collect();
+        free.choose_multiple(rng, count).copied().collect()
+    }
+
+    fn objective(&self) -> Option<&dyn Objective> {
+        Some(&Treasures)
+    }
+}
+
+static REGISTRY: LazyLock<RwLock<Vec<&'static dyn Variant>>> = LazyLock::new(|| {
+    RwLock::new(vec![
+        &Classic,
+        &Torus,
+        &Flagless,
+        &AntiMines,
+        &Knight,
+        &Wormholes,
+        &Liar,
+        &TreasureHunt,
+    ])
+});
+
+/// Makes a variant available by its name for the rest of the program.
+pub fn register(variant: impl Variant + 'static) -> Result<(), VariantError> {
+    let mut registry = REGISTRY.write().unwrap();
+    if registry.iter().any(|v| v.name() == variant.name()) {
+        return Err(VariantError::DuplicateName(variant.name()));
+    }
+    registry.push(Box::leak(Box::new(variant)));
+    Ok(())
+}
+
+pub fn find(name: &str) -> Result<&'static dyn Variant, VariantError> {
+    REGISTRY
+        .read()
+        .unwrap()
+        .iter()
+        .find(|v| v.name() == name)
+        .copied()
+        .ok_or_else(|| VariantError::Unknown(name.to_string()))
+}
+
+/// Every registered variant, the built in ones first.
+pub fn variants() -> Vec<&'static dyn Variant> {
+    REGISTRY.read().unwrap().clone()
+}
+
+impl GameConfig {
+    /// The config for a game of `variant`.
+    pub fn with_variant(mut self, variant: &dyn Variant) -> GameConfig {
+        variant.configure(&mut self);
+        self.variant = Some(variant.name().to_string());
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::{Board, GameState, Topology};
+
+    /// Mines only in the top row, a variant defined outside the engine.
+    struct TopRow;
+
+    impl Variant for TopRow {
+        fn name(&self) -> &'static str {
+            "top-row"
+        }
+
+        fn description(&self) -> &'static str {
+            "Every mine is in the first row"
+        }
+
+        fn place_mines(
+            &self,
+            placement: Placement<'_>,
+            rng: &mut dyn RngCore,
+        ) -> HashSet<Position> {
+            let mut mines = HashSet::new();
+            while mines.len() < placement.nr_mines {
+                let pos = (rng.random_range(0..placement.cols), 0);
+                if !placement.safe.contains(&pos) {
+                    mines.insert(pos);
+                }
+            }
+            mines
+        }
+    }
+
+    #[test]
+    fn test_builtin_variants() {
+        let names: Vec<&str> = variants().iter().map(|v| v.name()).collect();
+        assert_eq!(
+            &names[..6],
+            [
+                "classic",
+                "torus",
+                "flagless",
+                "anti-mines",
+                "knight",
+                "wormholes"
+            ]
+        );
+        assert_eq!(
+            find("chess").err(),
+            Some(VariantError::Unknown("chess".to_string()))
+        );
+        let config = GameConfig::default().with_variant(find("torus").unwrap());
+        assert!(config.wrap_edges);
+        assert_eq!(config.variant.as_deref(), Some("torus"));
+
+        // the classic variant places the same mines as a board without one
+        let classic = GameConfig::default().with_variant(&Classic);
+        let mut board = Board::with_config(9, 9, 10, classic);
+        board.init_mines((0, 0), Some(1));
+        let mut plain = Board::new(9, 9, 10);
+        plain.init_mines((0, 0), Some(1));
+        assert_eq!(board.get_board_state(), plain.get_board_state());
+    }
+
+    #[test]
+    fn test_registered_variant() {
+        register(TopRow).unwrap();
+        assert_eq!(
+            register(TopRow),
+            Err(VariantError::DuplicateName("top-row"))
+        );
+        let config = GameConfig::default().with_variant(find("top-row").unwrap());
+        let mut board = Board::with_config(5, 5, 3, config);
+        board.init_mines((4, 4), Some(7));
+        let mines = board.mines.clone().unwrap();
+        assert!(mines.iter().all(|(_, y)| y == 0));
+        let replayed = board.replay().unwrap().play().unwrap();
+        assert_eq!(replayed.mines, Some(mines));
+    }
+
+    #[test]
+    fn test_anti_mines() {
+        let config = GameConfig::default().with_variant(&AntiMines);
+        let mut board = Board::with_config(9, 9, 10, config);
+        board.init_mines((0, 0), Some(1));
+        let mines = board.mines.clone().unwrap();
+        assert_eq!(board.anti_mines.len(), 3);
+        assert!(board.anti_mines.is_subset(&mines));
+        assert_eq!(board.view().nr_anti_mines, 3);
+        for (pos, &count) in board.grid().positions().zip(board.counts.iter()) {
+            let expected: i8 = board
+                .iter_neighbors(pos)
+                .filter(|&n| mines.contains(n))
+                .map(|n| if board.anti_mines.contains(n) { -1 } else { 1 })
+                .sum();
+            assert_eq!(count, expected);
+        }
+        // zero counts next to a mine don't cascade
+        assert_eq!(crate::perft::check(&board), Ok(()));
+        for pos in crate::solver::Solver::new(&board.view()).safe_cells() {
+            assert!(!mines.contains(pos));
+        }
+        let replayed = board.replay().unwrap().play().unwrap();
+        assert_eq!(replayed.anti_mines, board.anti_mines);
+    }
+
+    #[test]
+    fn test_knight() {
+        let config = GameConfig::default().with_variant(&Knight);
+        let mut board = Board::with_config(9, 9, 10, config);
+        board.init_mines((4, 4), Some(1));
+        let mines = board.mines.clone().unwrap();
+        for (pos, &count) in board.grid().positions().zip(board.counts.iter()) {
+            let jumps = crate::board::SquareGrid.adjacent(9, 9, pos, false, Adjacency::Knight);
+            assert_eq!(count as usize, jumps.filter(|&n| mines.contains(n)).count());
+        }
+        // the first click cascades over knight's moves
+        assert_eq!(crate::perft::check(&board), Ok(()));
+        for pos in crate::solver::Solver::new(&board.view()).safe_cells() {
+            assert!(!mines.contains(pos));
+        }
+        assert!(board.to_layout_string().is_none());
+        let replayed = board.replay().unwrap().play().unwrap();
+        assert_eq!(replayed.counts, board.counts);
+    }
+
+    #[test]
+    fn test_wormholes() {
+        let config = GameConfig::default().with_variant(&Wormholes);
+        let mut board = Board::with_config(16, 16, 40, config);
+        board.init_mines((8, 8), Some(1));
+        let mines = board.mines.clone().unwrap();
+        assert_eq!(board.config.wormholes.len(), 6);
+        for (pos, &count) in board.grid().positions().zip(board.counts.iter()) {
+            let around = board.iter_neighbors(pos).filter(|&n| mines.contains(n));
+            assert_eq!(count as usize, around.count());
+        }
+        let (a, b) = board.config.wormholes[0];
+        let across = board.iter_neighbors(b).find(|&n| n != b);
+        assert!(board.iter_neighbors(a).any(|n| Some(n) == across));
+        assert_eq!(crate::perft::check(&board), Ok(()));
+        for pos in crate::solver::Solver::new(&board.view()).safe_cells() {
+            assert!(!mines.contains(pos));
+        }
+        let replayed = board.replay().unwrap().play().unwrap();
+        assert_eq!(replayed.config.wormholes, board.config.wormholes);
+        assert_eq!(replayed.counts, board.counts);
+    }
+
+    #[test]
+    fn test_treasure_hunt() {
+        let config = GameConfig::default().with_variant(&TreasureHunt);
+        let mut board = Board::with_config(9, 9, 10, config);
+        assert_eq!(board.objective().description(), "Open every treasure cell");
+        board.config.min_move_interval = None;
+        board.init_mines((0, 0), Some(1));
+        let treasures = board.config.treasures.clone();
+        assert_eq!(treasures.len(), 3);
+        let mines = board.mines.clone().unwrap();
+        for &pos in &treasures {
+            assert!(!mines.contains(pos));
+        }
+        let replayed = board.replay().unwrap().play().unwrap();
+        assert_eq!(replayed.config.treasures, treasures);
+
+        for &pos in &treasures {
+            assert!(board.ongoing());
+            let _ = board.open(pos);
+        }
+        assert_eq!(board.state, GameState::Won);
+        assert!(board.open_fields.len() < 71);
+        assert_eq!(crate::perft::check(&board), Ok(()));
+    }
+
+    #[test]
+    fn test_liar() {
+        let config = GameConfig::default().with_variant(&Liar);
+        let mut board = Board::with_config(9, 9, 10, config);
+        assert!(board.guess_free());
+        board.init_mines_no_guess((4, 4), Some(1)).unwrap();
+        let mines = board.mines.clone().unwrap();
+        for pos in board.opened_cells() {
+            let around = board.iter_neighbors(pos).count() as i8;
+            let shown = board.shown_count(pos);
+            assert_eq!((shown - board.count(pos)).abs(), 1);
+            assert!((0..=around).contains(&shown));
+        }
+        assert_eq!(crate::perft::check(&board), Ok(()));
+        assert!(board.to_layout_string().is_none());
+        let replayed = board.replay().unwrap().play().unwrap();
+        assert_eq!(replayed.view(), board.view());
+        for pos in crate::solver::Solver::new(&board.view()).safe_cells() {
+            assert!(!mines.contains(pos));
+        }
+        // the lying numbers are enough to clear the board
+        assert!(crate::solver::solvable_without_guessing(&board, &mut {
+            usize::MAX
+        }));
+    }
+
+    #[test]
+    fn test_liar_expert_first_click() {
+        // enumerating lying numbers gets expensive on large boards, the generation
+        // gives up in time and frontends fall back to a regular layout
+        let config = GameConfig::default().with_variant(&Liar);
+        let mut board = Board::with_config(16, 30, 99, config);
+        let start = web_time::Instant::now();
+        if board.init_mines_no_guess((5, 5), Some(1)).is_err() {
+            board.init_mines((5, 5), Some(1));
+        }
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+        assert!(board.initialized());
+
+        let view = board.view();
+        let start = web_time::Instant::now();
+        assert!(crate::simulate::SolverStrategy.decide(&view).is_some());
+        assert!(start.elapsed() < std::time::Duration::from_secs(10));
+    }
+}
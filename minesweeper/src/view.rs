@@ -0,0 +1,74 @@
+use crate::board::Board;
+
+pub use minesweeper_solver::view::{BoardView, Constraint};
+
+impl From<&Board> for BoardView {
+    fn from(board: &Board) -> Self {
+        BoardView {
+            rows: board.rows,
+            cols: board.cols,
+            nr_mines: board.nr_mines,
+            state: board.state,
+            squares: board.get_board_state(),
+            wrap_edges: board.config.wrap_edges,
+            nr_anti_mines: board.anti_mines.len(),
+            adjacency: board.config.adjacency,
+            wormholes: board.config.wormholes.clone(),
+            liar: board.config.liar,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::Square;
+
+    #[test]
+    fn test_view_hides_mines_until_game_over() {
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        let view = board.view();
+        assert!(view.squares.iter().flatten().all(|s| *s != Square::Mine));
+        assert!(board.reveal_mines().is_none());
+
+        board.open((3, 1)).unwrap();
+        let view = board.view();
+        let shown = view.squares.iter().flatten().filter(|s| **s == Square::Mine);
+        assert_eq!(shown.count(), 10);
+        assert_eq!(board.reveal_mines().unwrap().len(), 10);
+    }
+
+    #[test]
+    fn test_constraint() {
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        let view = board.view();
+        assert_eq!(view.constraint((0, 0)).unwrap().unopened, 0);
+        assert!(view.constraint((5, 5)).is_none());
+
+        let constraint = view.constraint((2, 1)).unwrap();
+        assert_eq!((constraint.value, constraint.flags), (1, 0));
+        assert_eq!(constraint.unopened, 2);
+        assert!(!constraint.satisfied());
+
+        board.flag((3, 1)).unwrap();
+        board.flag((3, 0)).unwrap();
+        let constraint = board.view().constraint((2, 1)).unwrap();
+        assert_eq!(constraint.unopened, 0);
+        assert_eq!(constraint.remaining(), -1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialized_view_excludes_mines() {
+        let mut board = Board::new(9, 9, 10);
+        board.init_mines((0, 0), Some(1));
+        let json = serde_json::to_string(&board.view()).unwrap();
+        assert!(!json.contains("Mine"));
+        // (3, 1) is a mine, its coordinates must not be derivable from the payload
+        let restored: BoardView = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.get((3, 1)), Some(Square::NotYetOpened));
+        assert_eq!(restored, board.view());
+    }
+}
@@ -0,0 +1,203 @@
+//! Bindings for embedding the engine in web frontends other than the egui app,
+//! built with the `wasm` feature and packaged by the `minesweeper_wasm` crate:
+//!
+//! ```bash
+//! wasm-pack build minesweeper_wasm --target web
+//! ```
+//!
+//! Cells are addressed by column and row, board contents come back as flat
+//! row-major typed arrays.
+
+use wasm_bindgen::prelude::*;
+
+use crate::board::{row_major, Board, GameState, Move, Rect, Square};
+
+/// Codes of `WasmBoard::cells` besides the counts, which go from -8 to 8.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cell {
+    Closed = 9,
+    Flag = 10,
+    /// Only shown once the game is lost.
+    Mine = 11,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Init = 0,
+    OnGoing = 1,
+    Lost = 2,
+    Won = 3,
+}
+
+impl From<GameState> for State {
+    fn from(state: GameState) -> State {
+        match state {
+            GameState::Init => State::Init,
+            GameState::OnGoing => State::OnGoing,
+            GameState::Lost => State::Lost,
+            GameState::Won => State::Won,
+        }
+    }
+}
+
+#[wasm_bindgen]
+pub struct WasmBoard {
+    board: Board,
+}
+
+#[wasm_bindgen]
+impl WasmBoard {
+    /// A classic game. Returns an error instead of a board without safe cells.
+    #[wasm_bindgen(constructor)]
+    pub fn new(rows: usize, cols: usize, nr_mines: usize) -> Result<WasmBoard, String> {
+        if nr_mines >= rows * cols {
+            return Err("too many mines".to_string());
+        }
+        Ok(WasmBoard {
+            board: Board::new(rows, cols, nr_mines),
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rows(&self) -> usize {
+        self.board.rows
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cols(&self) -> usize {
+        self.board.cols
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nr_mines(&self) -> usize {
+        self.board.nr_mines
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn state(&self) -> State {
+        self.board.state.into()
+    }
+
+    /// Seed of the mine layout, once it is placed.
+    #[wasm_bindgen(getter)]
+    pub fn seed(&self) -> Option<u64> {
+        self.board.seed()
+    }
+
+    /// Seconds played.
+    #[wasm_bindgen(getter)]
+    pub fn elapsed(&self) -> f64 {
+        self.board.elapsed().as_secs_f64()
+    }
+
+    /// Places the mines around the first click at `(x, y)` from `seed`, or a
+    /// random seed, and opens it. Only the first open needs it, `open` starts
+    /// with a random seed otherwise.
+    pub fn start(&mut self, x: usize, y: usize, seed: Option<u64>) -> bool {
+        if self.board.initialized() || !self.board.contains((x, y)) {
+            return false;
+        }
+        self.board.init_mines((x, y), seed);
+        true
+    }
+
+    /// Whether the board accepted the move.
+    pub fn open(&mut self, x: usize, y: usize) -> bool {
+        if !self.board.initialized() {
+            return self.start(x, y, None);
+        }
+        self.board.play(Move::Open((x, y))).is_ok()
+    }
+
+    /// Places or removes a flag, whether the board accepted the move.
+    pub fn flag(&mut self, x: usize, y: usize) -> bool {
+        self.board.play(Move::Flag((x, y))).is_ok()
+    }
+
+    /// Whether the board accepted the move.
+    pub fn chord(&mut self, x: usize, y: usize) -> bool {
+        self.board.play(Move::Chord((x, y))).is_ok()
+    }
+
+    /// Starts over on the same mines, re-opening the first click.
+    pub fn restart(&mut self) {
+        self.board.restart();
+    }
+
+    /// Every cell, row-major: its count when opened, a `Cell` code otherwise.
+    pub fn cells(&self) -> Vec<i8> {
+        self.region(0, 0, self.board.rows, self.board.cols)
+    }
+
+    /// The cells of the `rows` x `cols` window at (`x`, `y`) as in `cells`, so a
+    /// page showing part of a large board fetches only what is in view.
+    pub fn region(&self, x: usize, y: usize, rows: usize, cols: usize) -> Vec<i8> {
+        self.board
+            .iter_region(Rect::new((x, y), rows, cols))
+            .map(|(_, square)| match square {
+                Square::Opened(count) => count,
+                Square::NotYetOpened => Cell::Closed as i8,
+                Square::Flag => Cell::Flag as i8,
+                Square::Mine => Cell::Mine as i8,
+            })
+            .collect()
+    }
+
+    /// Indices into `cells` that changed with the last move, to redraw only those.
+    pub fn changed(&self) -> Vec<u32> {
+        self.board
+            .last_changed()
+            .iter()
+            .map(|&(x, y)| (y * self.board.cols + x) as u32)
+            .collect()
+    }
+
+    /// Indices of the mines, once the game is over.
+    pub fn mines(&self) -> Option<Vec<u32>> {
+        let mines = self.board.reveal_mines()?;
+        Some(
+            row_major(mines)
+                .into_iter()
+                .map(|(x, y)| (y * self.board.cols + x) as u32)
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasm_board() {
+        assert!(WasmBoard::new(3, 3, 9).is_err());
+        let mut board = WasmBoard::new(9, 9, 10).unwrap();
+        assert!(board.cells().iter().all(|&c| c == Cell::Closed as i8));
+        assert!(!board.flag(0, 0));
+        assert!(!board.start(9, 0, Some(1)));
+        assert!(board.start(0, 0, Some(1)));
+        assert_eq!(board.state(), State::OnGoing);
+        assert_eq!(board.seed(), Some(1));
+        assert!(!board.start(0, 0, Some(2)));
+
+        let cells = board.cells();
+        assert_eq!((cells.len(), cells[0], cells[2]), (81, 0, 1));
+        assert_eq!(
+            cells.iter().filter(|&&c| c != Cell::Closed as i8).count(),
+            18
+        );
+        assert!(board.flag(3, 1));
+        assert_eq!(board.cells()[9 + 3], Cell::Flag as i8);
+        assert_eq!(board.changed(), vec![9 + 3]);
+        assert_eq!(board.mines(), None);
+
+        assert!(board.open(6, 1));
+        assert_eq!(board.state(), State::Lost);
+        assert_eq!(board.cells()[9 + 6], Cell::Mine as i8);
+        assert_eq!(board.mines().unwrap().len(), 10);
+        board.restart();
+        assert_eq!(board.state(), State::OnGoing);
+    }
+}
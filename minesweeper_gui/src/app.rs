@@ -1,47 +1,872 @@
-use minesweeper::board::{Board, Square};
+use std::collections::{HashMap, HashSet};
 
+use minesweeper::adaptive::Adaptive;
+use minesweeper::async_game::{AsyncGame, Request, Response, Ticket};
+use minesweeper::board::NoGuessGeneration;
+use minesweeper::command::{self, GameCommand};
+use minesweeper::drill::{self, Session};
+use minesweeper::metrics::PhaseSplits;
+use minesweeper::prelude::*;
+use minesweeper::share;
+use minesweeper::variant;
+
+use crate::atlas::{self, Atlas};
+use crate::packs::Packs;
+use crate::theme::{self, Themes};
+
+const DIFFICULTIES: [(&str, usize, usize, usize); 3] = [
+    ("Beginner", 9, 9, 10),
+    ("Intermediate", 16, 16, 40),
+    ("Expert", 16, 30, 99),
+];
+
+// version of the saved state, raised with every step in `migrate`
+const STATE_VERSION: u32 = 1;
+
+// how long the cells changed by a move stay outlined
+const HIGHLIGHT_SECONDS: f64 = 0.8;
+
+// background of the two cells of a wormhole, by pair
+const WORMHOLE_COLORS: [egui::Color32; 4] = [
+    egui::Color32::from_rgb(203, 166, 247),
+    egui::Color32::from_rgb(137, 220, 235),
+    egui::Color32::from_rgb(250, 179, 135),
+    egui::Color32::from_rgb(245, 194, 231),
+];
+
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct TemplateApp {
+    // version the state was saved with, 0 before states had one
+    #[serde(default)]
+    state_version: u32,
     rows: usize,
     cols: usize,
     mines: usize,
     seed: u64,
     use_seed: bool,
+    // the number of mines follows the results, see `adaptive`
+    adaptive_mode: bool,
+    adaptive: Adaptive,
+    // the result of the finished board went into `adaptive`
+    #[serde(skip)]
+    adaptive_recorded: bool,
+    // time per phase of the finished board, worked out once it ends
+    #[serde(skip)]
+    phases: Option<PhaseSplits>,
+    no_guess: bool,
+    // cells around the first click that never hold a mine
+    safe_radius: usize,
+    // tint the guaranteed free area under the cursor before the first click
+    preview_safe_area: bool,
+    // win by opening every safe cell instead of flagging every mine
+    win_without_flags: bool,
+    // cells on opposite edges are neighbors
+    wrap_edges: bool,
+    // hints start at the number to look at and give away more when asked again
+    progressive_hints: bool,
+    // assisted mode for learning, flags on safe cells are rejected
+    assisted_flags: bool,
+    // ask before opening a guess while another cell is provably safe
+    warn_guesses: bool,
+    // name of a registered variant, applied on top of the settings above
+    variant: String,
+    // name of the drill picked for practice
+    drill_name: String,
+    // drill being practiced, its rounds replace the board
+    #[serde(skip)]
+    drill: Option<Session>,
+    // whether the last round of the drill was passed
+    #[serde(skip)]
+    drill_result: Option<bool>,
+    // browser of the installed puzzle packs
+    show_packs: bool,
+    #[serde(skip)]
+    packs: Packs,
+    // pack and puzzle on the board, by index
+    #[serde(skip)]
+    pack_puzzle: Option<(usize, usize)>,
+    // guides across the hovered row and column plus a coordinate readout
+    crosshair: bool,
+    // hover tooltips explaining opened numbers
+    analysis: bool,
+    // draw the board as one mesh, the shapes and text per cell are the fallback
+    mesh_rendering: bool,
+    #[serde(skip)]
+    atlas: Option<Atlas>,
+    // name of the theme, see `theme`
+    theme_name: String,
+    #[serde(skip)]
+    themes: Themes,
+    // when the theme files were last checked for changes
+    #[cfg(feature = "debug")]
+    #[serde(skip)]
+    themes_checked: f64,
+    // open with the secondary button and flag with the primary one
+    swap_buttons: bool,
+    // text commands of the terminal game, toggled with the backtick key
+    show_console: bool,
+    #[serde(skip)]
+    console_input: String,
+    #[serde(skip)]
+    console_log: Vec<String>,
+    // first-run wizard, hidden for good once finished or skipped
+    show_wizard: bool,
+    #[serde(skip)]
+    wizard_step: usize,
     board: Board,
+    #[serde(skip)]
     previous_frame_time: Option<f64>,
+    // time of the last successful move, its changed cells stay outlined briefly
+    #[serde(skip)]
+    highlight_since: Option<f64>,
+    // safe cell the player tried to flag in assisted mode, and when
+    #[serde(skip)]
+    rejected_flag: Option<(Position, f64)>,
+    // no-guess layout being generated for the first click, a batch per frame
+    #[serde(skip)]
+    generating: Option<Generating>,
+    // solver work on the worker thread, keeping the frame free on large boards
+    #[serde(skip)]
+    worker: AsyncGame,
+    #[serde(skip)]
+    waiting: Option<Waiting>,
+    // guess waiting for the player to confirm it, see `warn_guesses`
+    #[serde(skip)]
+    pending_guess: Option<Position>,
+    // suggestion from the hint button, dropped after the next move
+    #[serde(skip)]
+    hint: Option<Hint>,
+    // cell under the cursor during the last frame
+    #[serde(skip)]
+    hovered: Option<Position>,
+    #[serde(skip)]
     primary_button_down_event_fired: bool,
+    #[serde(skip)]
     last_primary_press_processed: bool,
+    #[serde(skip)]
     secondary_button_down_event_fired: bool,
+    #[serde(skip)]
     last_secondary_press_processed: bool,
+    #[cfg(feature = "debug")]
+    #[serde(skip)]
+    time_travel: crate::debug::TimeTravel,
+}
+
+/// A no-guess layout being generated for the first click at `start`.
+struct Generating {
+    generation: NoGuessGeneration,
+    start: Position,
+    seed: Option<u64>,
+    // size and rules of the board it is for, another board drops it
+    board: (usize, usize, usize, GameConfig),
+}
+
+impl Generating {
+    fn key(board: &Board) -> (usize, usize, usize, GameConfig) {
+        (board.rows, board.cols, board.nr_mines, board.config.clone())
+    }
+}
+
+/// Solver work sent to the worker, its result is dropped if the board moved on
+/// in the meantime.
+struct Waiting {
+    ticket: Ticket,
+    key: PositionKey,
+    job: Job,
+}
+
+enum Job {
+    // a hint asked for in the console is answered there
+    Hint { from_console: bool },
+    // whether opening the clicked cell is a guess, see `warn_guesses`
+    Guess(Position),
+}
+
+type PositionKey = (Option<Replay>, Vec<Position>, Vec<Position>, bool);
+
+fn position_key(board: &Board) -> PositionKey {
+    (
+        board.replay(),
+        board.opened_cells(),
+        board.flagged_cells(),
+        board.clock.is_paused(),
+    )
+}
+
+fn default_board() -> Board {
+    // the first click opens an area, as in classic Windows minesweeper
+    let config = GameConfig {
+        safe_radius: 1,
+        ..GameConfig::default()
+    };
+    Board::with_config(9, 9, 10, config)
 }
 
 impl Default for TemplateApp {
     fn default() -> Self {
         Self {
+            state_version: STATE_VERSION,
             rows: 9,
             cols: 9,
             mines: 10,
             seed: 1,
             use_seed: false,
-            board: Board::new(9, 9, 10),
+            adaptive_mode: false,
+            adaptive: Adaptive::default(),
+            adaptive_recorded: false,
+            phases: None,
+            no_guess: false,
+            safe_radius: 1,
+            preview_safe_area: false,
+            win_without_flags: false,
+            wrap_edges: false,
+            progressive_hints: false,
+            assisted_flags: false,
+            warn_guesses: false,
+            variant: variant::Classic.name().to_string(),
+            drill_name: drill::DRILLS[0].name.to_string(),
+            drill: None,
+            drill_result: None,
+            show_packs: false,
+            packs: Packs::load(),
+            pack_puzzle: None,
+            crosshair: false,
+            analysis: false,
+            mesh_rendering: true,
+            atlas: None,
+            theme_name: "Mocha (dark)".to_string(),
+            themes: Themes::load(),
+            #[cfg(feature = "debug")]
+            themes_checked: 0.0,
+            swap_buttons: false,
+            show_console: false,
+            console_input: String::new(),
+            console_log: Vec::new(),
+            show_wizard: true,
+            wizard_step: 0,
+            board: default_board(),
             previous_frame_time: None,
+            highlight_since: None,
+            rejected_flag: None,
+            generating: None,
+            worker: AsyncGame::new(),
+            waiting: None,
+            pending_guess: None,
+            hint: None,
+            hovered: None,
             primary_button_down_event_fired: false,
             last_primary_press_processed: false,
             secondary_button_down_event_fired: false,
             last_secondary_press_processed: false,
+            #[cfg(feature = "debug")]
+            time_travel: Default::default(),
         }
     }
 }
 
 impl TemplateApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        // Load previous settings and the game in progress (if any).
+        if let Some(storage) = cc.storage {
+            let mut app: Self = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+            app.migrate();
+            let board = &app.board;
+            if (board.rows, board.cols, board.nr_mines) != (app.rows, app.cols, app.mines) {
+                app.board = app.new_board();
+            }
+            return app;
+        }
         Default::default()
     }
+
+    /// Brings a state saved by an older version up to date, a step per version.
+    /// Added settings need no step, they take their default.
+    fn migrate(&mut self) {
+        if self.state_version < 1 {
+            // whoever saved a state before has played already
+            self.show_wizard = false;
+        }
+        self.state_version = STATE_VERSION;
+    }
+
+    fn set_size(&mut self, rows: usize, cols: usize, mines: usize) {
+        self.rows = rows;
+        self.cols = cols;
+        self.mines = mines;
+        self.board = self.new_board();
+    }
+
+    fn new_board(&self) -> Board {
+        Board::with_config(self.rows, self.cols, self.mines, self.game_config())
+    }
+
+    /// The rules picked in the settings.
+    fn game_config(&self) -> GameConfig {
+        let config = GameConfig {
+            safe_radius: self.safe_radius,
+            win_condition: if self.win_without_flags {
+                WinCondition::OpenAllSafe
+            } else {
+                WinCondition::FlagAllMines
+            },
+            wrap_edges: self.wrap_edges,
+            first_hint: self.first_hint(),
+            assisted_flags: self.assisted_flags,
+            ..GameConfig::default()
+        };
+        match variant::find(&self.variant) {
+            Ok(variant) => config.with_variant(variant),
+            Err(_) => config,
+        }
+    }
+
+    fn first_hint(&self) -> HintLevel {
+        if self.progressive_hints {
+            HintLevel::Number
+        } else {
+            HintLevel::Cell
+        }
+    }
+
+    /// Runs a line of the terminal command language and returns the reply.
+    fn run_command(&mut self, line: &str, now: f64) -> String {
+        let result = match line.parse::<GameCommand>() {
+            Err(e) => return UserFacingError::from(e).to_string(),
+            Ok(GameCommand::Restart { same }) => {
+                if same {
+                    self.board.restart();
+                } else {
+                    self.board = self.new_board();
+                }
+                self.hint = None;
+                return "Restarted".to_string();
+            }
+            Ok(GameCommand::Hint) => {
+                let job = Job::Hint { from_console: true };
+                self.ask_worker(Request::Hint(self.board.clone()), job);
+                return "Thinking...".to_string();
+            }
+            Ok(GameCommand::Seed(None)) => {
+                return match self.board.seed() {
+                    Some(seed) => format!("Seed: {seed}"),
+                    None => "The mines are placed at the first click".to_string(),
+                };
+            }
+            Ok(GameCommand::Seed(Some(seed))) => {
+                self.seed = seed;
+                self.use_seed = true;
+                self.board = self.new_board();
+                return format!("New board with seed {seed}");
+            }
+            Ok(GameCommand::Open(pos)) if !self.board.initialized() => {
+                if !self.board.contains(pos) {
+                    return UserFacingError::from(OpenError::OutOfBounds).to_string();
+                }
+                self.first_click(pos, now);
+                Ok(())
+            }
+            Ok(GameCommand::Open(pos)) => self
+                .board
+                .open(pos)
+                .map(|_| ())
+                .map_err(UserFacingError::from),
+            Ok(GameCommand::Flag(pos)) => self
+                .board
+                .flag(pos)
+                .map(|_| ())
+                .map_err(UserFacingError::from),
+            Ok(GameCommand::Chord(pos)) => self
+                .board
+                .chord(pos)
+                .map(|_| ())
+                .map_err(UserFacingError::from),
+            Ok(GameCommand::Undo) => self.board.undo().map_err(UserFacingError::from),
+            Ok(GameCommand::Save(_) | GameCommand::Load(_)) => {
+                // the app keeps the game in progress across restarts by itself
+                return "Files are only saved and loaded by the terminal game".to_string();
+            }
+        };
+        match result {
+            Ok(()) => {
+                self.highlight_since = Some(now);
+                self.hint = None;
+                format!("{:?}", self.board.state)
+            }
+            Err(e) => e.to_string(),
+        }
+    }
+
+    fn console(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_console;
+        let mut submitted = None;
+        egui::Window::new("Console")
+            .open(&mut open)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for line in &self.console_log {
+                            ui.monospace(line);
+                        }
+                    });
+                let response = ui.add(
+                    egui::TextEdit::singleline(&mut self.console_input)
+                        .font(egui::TextStyle::Monospace)
+                        .hint_text("open 3 4, flag 5 5, chord 3 4, hint, undo, seed")
+                        .desired_width(f32::INFINITY),
+                );
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    submitted = Some(std::mem::take(&mut self.console_input));
+                    response.request_focus();
+                }
+            });
+        self.show_console = open;
+        if let Some(line) = submitted.filter(|line| !line.trim().is_empty()) {
+            let reply = self.run_command(&line, ctx.input(|i| i.time));
+            self.console_log.push(format!("> {}", line.trim()));
+            self.console_log.push(reply);
+        }
+    }
+
+    fn open(&mut self, col: usize, row: usize, now: f64) {
+        // TODO handle result
+        if self.board.open((col, row)).is_ok() {
+            self.highlight_since = Some(now);
+            self.hint = None;
+        }
+    }
+
+    /// Places the mines around the first click. No-guess layouts are generated a
+    /// batch at a time by `follow_generation` while the board stays closed.
+    fn first_click(&mut self, pos: Position, now: f64) {
+        if self.generating.is_some() {
+            return;
+        }
+        let seed = self.use_seed.then_some(self.seed);
+        let no_guess = self.no_guess || self.board.guess_free();
+        match no_guess.then(|| self.board.no_guess_generation(pos, seed)) {
+            Some(Some(generation)) => {
+                self.generating = Some(Generating {
+                    generation,
+                    start: pos,
+                    seed,
+                    board: Generating::key(&self.board),
+                });
+            }
+            _ => self.board.init_mines(pos, seed),
+        }
+        self.highlight_since = Some(now);
+        self.hint = None;
+    }
+
+    /// Runs the no-guess generation for about a frame and shows its progress,
+    /// placing the mines once it is done. The player can cancel it, which leaves
+    /// the board closed.
+    fn follow_generation(&mut self, ctx: &egui::Context) {
+        const FRAME_BUDGET: std::time::Duration = std::time::Duration::from_millis(12);
+        let Some(generating) = &mut self.generating else {
+            return;
+        };
+        if self.board.initialized() || generating.board != Generating::key(&self.board) {
+            self.generating = None;
+            return;
+        }
+        let started = web_time::Instant::now();
+        let result = loop {
+            if let Some(result) = generating.generation.step(1) {
+                break Some(result);
+            }
+            if started.elapsed() > FRAME_BUDGET {
+                break None;
+            }
+        };
+        if let Some(result) = result {
+            let (start, seed) = (generating.start, generating.seed);
+            self.generating = None;
+            // also the fallback when no guess-free layout was found
+            self.board.init_mines(start, result.ok().or(seed));
+            self.highlight_since = Some(ctx.input(|i| i.time));
+            return;
+        }
+        let progress = generating.generation.progress();
+        let mut cancel = false;
+        egui::Window::new("Generating")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label("Looking for a board that needs no guessing");
+                ui.add(
+                    egui::ProgressBar::new(progress.fraction())
+                        .text(format!("{} attempts", progress.attempts)),
+                );
+                cancel = ui.button("Cancel").clicked();
+            });
+        if cancel {
+            self.generating = None;
+        }
+        ctx.request_repaint();
+    }
+
+    /// Sends solver work to the worker, replacing the answer still awaited.
+    fn ask_worker(&mut self, request: Request, job: Job) {
+        self.waiting = Some(Waiting {
+            ticket: self.worker.submit(request),
+            key: position_key(&self.board),
+            job,
+        });
+    }
+
+    /// Applies the answer of the worker once it arrives, if the board is still in
+    /// the position it was asked about.
+    fn follow_worker(&mut self, ctx: &egui::Context) {
+        let Some(waiting) = &self.waiting else {
+            return;
+        };
+        let Some((ticket, response)) = self.worker.poll() else {
+            ctx.request_repaint();
+            return;
+        };
+        if ticket != waiting.ticket {
+            return self.follow_worker(ctx);
+        }
+        let waiting = self.waiting.take().expect("checked above");
+        if waiting.key != position_key(&self.board) {
+            return;
+        }
+        match (waiting.job, response) {
+            (Job::Hint { from_console }, Response::Hint(board, hint)) => {
+                // the worker's copy of the board counted the hint
+                self.board = board;
+                if from_console {
+                    self.console_log.push(command::hint_message(hint.as_ref()));
+                }
+                self.hint = hint;
+            }
+            (Job::Guess(pos), Response::SafeAlternative(Some(_))) => self.pending_guess = Some(pos),
+            (Job::Guess((col, row)), Response::SafeAlternative(None)) => {
+                self.open(col, row, ctx.input(|i| i.time));
+            }
+            _ => unreachable!("the worker answers every request in kind"),
+        }
+    }
+
+    /// Feeds the result of a finished board to the adaptive difficulty, which
+    /// sets the mines of the next one.
+    fn follow_adaptive(&mut self) {
+        let finished = self.board.initialized() && !self.board.ongoing();
+        if !finished {
+            self.adaptive_recorded = false;
+        } else if self.adaptive_mode && self.drill.is_none() && !self.adaptive_recorded {
+            self.adaptive.record(!self.board.lost());
+            self.mines = self.adaptive.nr_mines(self.rows, self.cols);
+            self.adaptive_recorded = true;
+        }
+    }
+
+    /// Works out the phases of the board once it is finished, so replaying the
+    /// game doesn't happen every frame.
+    fn follow_phases(&mut self) {
+        let finished = self.board.initialized() && !self.board.ongoing();
+        if !finished {
+            self.phases = None;
+        } else if self.phases.is_none() {
+            self.phases = self.board.phase_splits();
+        }
+    }
+
+    /// Judges the round of the drill after the moves of the last frame and moves
+    /// on to the next one. Ends the drill when the player started another board.
+    fn follow_drill(&mut self) {
+        let Some(session) = &mut self.drill else {
+            return;
+        };
+        if !session.owns(&self.board) {
+            self.drill = None;
+            self.drill_result = None;
+            return;
+        }
+        if let Some(passed) = session.update(&self.board) {
+            self.drill_result = Some(passed);
+            if !session.is_complete() {
+                self.board = session.next_board();
+                self.hint = None;
+                self.highlight_since = None;
+            }
+        }
+    }
+
+    fn practice(&mut self, ui: &mut egui::Ui) {
+        ui.label("Practice");
+        if let Some(session) = &self.drill {
+            let drill = session.drill;
+            ui.label(drill.description);
+            ui.label(format!(
+                "Passed {}/{}, failed {}",
+                session.passed, drill.rounds, session.failed
+            ));
+            if let Some(best) = session.best {
+                ui.label(format!("Fastest round: {:.3}s", best.as_secs_f64()));
+            }
+            if session.is_complete() {
+                ui.label("Drill complete!");
+            } else if let Some(passed) = self.drill_result {
+                ui.label(if passed {
+                    "Last round passed"
+                } else {
+                    "Last round failed"
+                });
+            }
+            if ui.button("Stop drill").clicked() {
+                self.drill = None;
+                self.drill_result = None;
+                self.board = self.new_board();
+            }
+            return;
+        }
+        egui::ComboBox::from_label("Drill")
+            .selected_text(&self.drill_name)
+            .show_ui(ui, |ui| {
+                for drill in drill::DRILLS.iter() {
+                    ui.selectable_value(&mut self.drill_name, drill.name.to_string(), drill.name)
+                        .on_hover_text(drill.description);
+                }
+            });
+        if let Some(drill) = drill::find(&self.drill_name) {
+            if ui
+                .button("Start drill")
+                .on_hover_text(drill.description)
+                .clicked()
+            {
+                let mut session = Session::new(drill, self.use_seed.then_some(self.seed));
+                self.board = session.next_board();
+                self.drill = Some(session);
+                self.drill_result = None;
+                self.hint = None;
+            }
+        }
+    }
+
+    /// Puts a puzzle of an installed pack on the board.
+    fn play_puzzle(&mut self, pack: usize, puzzle: usize) {
+        self.board = self.packs.packs[pack].puzzles[puzzle]
+            .board_with(&self.game_config())
+            .expect("checked when the pack was read");
+        self.pack_puzzle = Some((pack, puzzle));
+        self.drill = None;
+        self.hint = None;
+        self.highlight_since = None;
+    }
+
+    /// Forgets the puzzle once another board replaced it. Puzzles are the boards
+    /// without a seed, a new board of the same size only counts as another one
+    /// after its first click.
+    fn follow_pack(&mut self) {
+        let Some((pack, puzzle)) = self.pack_puzzle else {
+            return;
+        };
+        let size = (self.board.rows, self.board.cols, self.board.nr_mines);
+        let owned = self.packs.packs[pack].puzzles[puzzle]
+            .board()
+            .is_ok_and(|board| (board.rows, board.cols, board.nr_mines) == size);
+        if !owned || self.board.seed().is_some() {
+            self.pack_puzzle = None;
+        }
+    }
+
+    fn pack_browser(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_packs;
+        let mut picked = None;
+        egui::Window::new("Puzzle packs")
+            .open(&mut open)
+            .default_width(280.0)
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for (i, pack) in self.packs.packs.iter().enumerate() {
+                        let current = self.pack_puzzle.filter(|&(p, _)| p == i);
+                        egui::CollapsingHeader::new(&pack.name)
+                            .id_salt(i)
+                            .default_open(current.is_some())
+                            .show(ui, |ui| {
+                                if let Some(author) = &pack.author {
+                                    ui.label(format!("by {author}"));
+                                }
+                                if let Some(description) = &pack.description {
+                                    ui.label(description);
+                                }
+                                for (j, puzzle) in pack.puzzles.iter().enumerate() {
+                                    let text = format!("{}. {}", j + 1, puzzle.title);
+                                    let selected = current == Some((i, j));
+                                    if ui.selectable_label(selected, text).clicked() {
+                                        picked = Some((i, j));
+                                    }
+                                }
+                            });
+                    }
+                });
+                if let Some((pack, puzzle)) = self.pack_puzzle {
+                    let next = puzzle + 1;
+                    if self.board.state == GameState::Won
+                        && next < self.packs.packs[pack].puzzles.len()
+                    {
+                        ui.separator();
+                        if ui.button("Next puzzle").clicked() {
+                            picked = Some((pack, next));
+                        }
+                    }
+                }
+            });
+        self.show_packs = open;
+        if let Some((pack, puzzle)) = picked {
+            self.play_puzzle(pack, puzzle);
+        }
+    }
+
+    /// Asks whether to open the pending guess.
+    fn guess_warning(&mut self, ctx: &egui::Context) {
+        let Some((col, row)) = self.pending_guess else {
+            return;
+        };
+        egui::Window::new("Guess?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "({col},{row}) is not provably safe, but another cell is."
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Open anyway").clicked() {
+                        self.pending_guess = None;
+                        self.open(col, row, ctx.input(|i| i.time));
+                    }
+                    if ui.button("Keep looking").clicked() {
+                        self.pending_guess = None;
+                    }
+                });
+            });
+    }
+
+    fn onboarding_wizard(&mut self, ctx: &egui::Context) {
+        egui::Window::new("Welcome to Minesweeper")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                match self.wizard_step {
+                    0 => {
+                        ui.label("Pick a difficulty to start with:");
+                        for (name, rows, cols, mines) in DIFFICULTIES {
+                            let selected =
+                                (self.rows, self.cols, self.mines) == (rows, cols, mines);
+                            let text = format!("{name} ({cols}x{rows}, {mines} mines)");
+                            if ui.radio(selected, text).clicked() {
+                                self.set_size(rows, cols, mines);
+                            }
+                        }
+                    }
+                    1 => {
+                        ui.label("Pick a theme:");
+                        for theme in &self.themes.themes {
+                            ui.radio_value(&mut self.theme_name, theme.name.clone(), &theme.name);
+                        }
+                    }
+                    _ => {
+                        ui.label("Pick a mouse scheme:");
+                        ui.radio_value(
+                            &mut self.swap_buttons,
+                            false,
+                            "Left click opens, right click flags",
+                        );
+                        ui.radio_value(
+                            &mut self.swap_buttons,
+                            true,
+                            "Right click opens, left click flags",
+                        );
+                    }
+                }
+                ui.add_space(10.0);
+                ui.horizontal(|ui| {
+                    if self.wizard_step > 0 && ui.button("Back").clicked() {
+                        self.wizard_step -= 1;
+                    }
+                    if self.wizard_step < 2 {
+                        if ui.button("Next").clicked() {
+                            self.wizard_step += 1;
+                        }
+                        if ui.button("Skip").clicked() {
+                            self.show_wizard = false;
+                        }
+                    } else if ui.button("Start playing").clicked() {
+                        self.show_wizard = false;
+                    }
+                });
+            });
+    }
 }
 
 impl eframe::App for TemplateApp {
+    /// Called by the framework to save settings before shutdown.
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, eframe::APP_KEY, self);
+    }
+
     /// Called each time the UI needs repainting, which may be many times per second.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        catppuccin_egui::set_theme(ctx, catppuccin_egui::MOCHA);
+        #[cfg(all(feature = "debug", not(target_arch = "wasm32")))]
+        {
+            let now = ctx.input(|i| i.time);
+            if now - self.themes_checked > 1.0 {
+                self.themes_checked = now;
+                self.themes.reload_if_changed();
+            }
+            ctx.request_repaint_after(std::time::Duration::from_secs(1));
+        }
+        catppuccin_egui::set_theme(ctx, self.themes.get(&self.theme_name).catppuccin());
+        if self.show_wizard {
+            self.onboarding_wizard(ctx);
+        }
+        #[cfg(feature = "debug")]
+        self.time_travel.show(ctx, &mut self.board);
+        if ctx.input(|i| i.key_pressed(egui::Key::Backtick)) && !ctx.wants_keyboard_input() {
+            self.show_console = !self.show_console;
+        }
+        if self.show_console {
+            self.console(ctx);
+        }
+        if self.show_packs {
+            self.pack_browser(ctx);
+        }
+        self.guess_warning(ctx);
+        self.follow_generation(ctx);
+        self.follow_worker(ctx);
+
+        // quick restart: F2/R for a new layout, Shift+F2 for the same layout, not
+        // while typing in the console or another text field
+        let typing = ctx.wants_keyboard_input();
+        let (restart_new, restart_same) = ctx.input(|i| {
+            if typing {
+                return (false, false);
+            }
+            let f2 = i.key_pressed(egui::Key::F2);
+            (
+                (f2 && !i.modifiers.shift) || i.key_pressed(egui::Key::R),
+                f2 && i.modifiers.shift,
+            )
+        });
+        if restart_same {
+            self.board.restart();
+            self.hint = None;
+        } else if restart_new {
+            self.board = self.new_board();
+        }
+        self.follow_drill();
+        self.follow_pack();
+        self.follow_adaptive();
+        self.follow_phases();
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 // NOTE: no File->Quit on web pages!
@@ -64,31 +889,90 @@ impl eframe::App for TemplateApp {
                 ui.heading("Minesweeper configuration");
                 ui.add_space(10.0);
 
-                if ui.button("Reset board").clicked() {
-                    self.board = Board::new(self.rows, self.cols, self.mines);
+                if ui.button("Reset board").on_hover_text("F2 or R").clicked() {
+                    self.board = self.new_board();
+                }
+                if ui
+                    .button("Restart same board")
+                    .on_hover_text("Shift+F2")
+                    .clicked()
+                {
+                    self.board.restart();
+                    self.hint = None;
+                }
+                if ui
+                    .add_enabled(self.board.ongoing(), egui::Button::new("Hint"))
+                    .on_hover_text("Outline a safe cell, or the safest guess")
+                    .clicked()
+                {
+                    let job = Job::Hint {
+                        from_console: false,
+                    };
+                    self.ask_worker(Request::Hint(self.board.clone()), job);
                 }
+                ui.horizontal(|ui| {
+                    if ui.button("Copy board").clicked() {
+                        ctx.copy_text(share::markdown_snapshot(&self.board.view()));
+                    }
+                    let summary = share::result_summary(&self.board);
+                    if ui
+                        .add_enabled(summary.is_some(), egui::Button::new("Copy result"))
+                        .clicked()
+                    {
+                        if let Some(summary) = summary {
+                            let snapshot = share::markdown_snapshot(&self.board.view());
+                            ctx.copy_text(format!("{summary}\n{snapshot}"));
+                        }
+                    }
+                    let grid = share::emoji_grid(&self.board);
+                    if ui
+                        .add_enabled(grid.is_some(), egui::Button::new("Share"))
+                        .on_hover_text("Copy a spoiler free emoji summary")
+                        .clicked()
+                    {
+                        if let Some(grid) = grid {
+                            ctx.copy_text(grid);
+                        }
+                    }
+                    let code = share::replay_code(&self.board);
+                    if ui
+                        .add_enabled(code.is_some(), egui::Button::new("Copy replay"))
+                        .on_hover_text("Copy a short code that replays this game")
+                        .clicked()
+                    {
+                        if let Some(code) = code {
+                            ctx.copy_text(code);
+                        }
+                    }
+                });
 
                 ui.add_space(10.0);
 
-                if ui.button("Beginner").clicked() {
-                    self.rows = 9;
-                    self.cols = 9;
-                    self.mines = 10;
-                    self.board = Board::new(9, 9, 10);
+                for (name, rows, cols, mines) in DIFFICULTIES {
+                    if ui.button(name).clicked() {
+                        self.adaptive_mode = false;
+                        self.set_size(rows, cols, mines);
+                    }
                 }
-
-                if ui.button("Intermediate").clicked() {
-                    self.rows = 16;
-                    self.cols = 16;
-                    self.mines = 40;
-                    self.board = Board::new(16, 16, 40);
+                if ui
+                    .button("Adaptive")
+                    .on_hover_text(
+                        "Keep the size, the number of mines rises after win streaks and drops after losses",
+                    )
+                    .clicked()
+                {
+                    self.adaptive_mode = true;
+                    self.set_size(
+                        self.rows,
+                        self.cols,
+                        self.adaptive.nr_mines(self.rows, self.cols),
+                    );
                 }
-
-                if ui.button("Expert").clicked() {
-                    self.rows = 16;
-                    self.cols = 30;
-                    self.mines = 99;
-                    self.board = Board::new(16, 30, 99);
+                if self.adaptive_mode {
+                    ui.label(format!(
+                        "Adaptive: {:.1}% mines",
+                        100.0 * self.adaptive.density
+                    ));
                 }
 
                 ui.add_space(10.0);
@@ -105,7 +989,7 @@ impl eframe::App for TemplateApp {
 
                 for r in sliders {
                     if r.changed() {
-                        self.board = Board::new(self.rows, self.cols, self.mines);
+                        self.board = self.new_board();
                     }
                 }
 
@@ -118,11 +1002,115 @@ impl eframe::App for TemplateApp {
                     ui.add(egui::Slider::new(&mut self.seed, 0..=1000).text("Seed"));
                 // reset board
                 if seed_toggle.clicked() {
-                    self.board = Board::new(self.rows, self.cols, self.mines);
+                    self.board = self.new_board();
                 }
                 if seed_response.changed() && self.use_seed {
-                    self.board = Board::new(self.rows, self.cols, self.mines);
+                    self.board = self.new_board();
+                }
+                ui.checkbox(&mut self.no_guess, "No guessing")
+                    .on_hover_text("Generate boards that can be solved without guessing");
+                if ui
+                    .add(egui::Slider::new(&mut self.safe_radius, 0..=4).text("Safe start radius"))
+                    .on_hover_text("Cells this close to the first click never hold a mine")
+                    .changed()
+                {
+                    // only read when the mines are generated
+                    self.board.config.safe_radius = self.safe_radius;
+                }
+                if ui
+                    .checkbox(&mut self.win_without_flags, "Win without flagging")
+                    .on_hover_text("Opening every safe cell wins, flags are optional")
+                    .changed()
+                {
+                    self.board = self.new_board();
+                }
+                if ui
+                    .checkbox(&mut self.wrap_edges, "Wrap around edges")
+                    .on_hover_text(
+                        "Cells on opposite edges are neighbors, the board has no corners",
+                    )
+                    .changed()
+                {
+                    self.board = self.new_board();
+                }
+                if ui
+                    .checkbox(&mut self.progressive_hints, "Progressive hints")
+                    .on_hover_text(
+                        "Hints first point at a number, then at a region, then at the safe cell",
+                    )
+                    .changed()
+                {
+                    self.board.config.first_hint = self.first_hint();
+                }
+                if ui
+                    .checkbox(&mut self.assisted_flags, "Assisted flags")
+                    .on_hover_text(
+                        "For learning: flags on safe cells are rejected, results are marked as assisted",
+                    )
+                    .changed()
+                {
+                    self.board = self.new_board();
+                }
+                ui.checkbox(&mut self.warn_guesses, "Warn before guessing")
+                    .on_hover_text("Ask before opening a guess while another cell is provably safe");
+                let previous = self.variant.clone();
+                egui::ComboBox::from_label("Variant")
+                    .selected_text(&self.variant)
+                    .show_ui(ui, |ui| {
+                        for variant in variant::variants() {
+                            ui.selectable_value(
+                                &mut self.variant,
+                                variant.name().to_string(),
+                                variant.name(),
+                            )
+                            .on_hover_text(variant.description());
+                        }
+                    });
+                if self.variant != previous {
+                    self.board = self.new_board();
+                }
+                ui.checkbox(&mut self.preview_safe_area, "Preview safe start")
+                    .on_hover_text("Show the area kept free of mines around the first click");
+
+                ui.add_space(10.0);
+                ui.separator();
+                ui.add_space(10.0);
+
+                self.practice(ui);
+                ui.checkbox(&mut self.show_packs, "Puzzle packs")
+                    .on_hover_text("Play the puzzles of the installed packs");
+
+                ui.add_space(10.0);
+                ui.separator();
+                ui.add_space(10.0);
+
+                egui::ComboBox::from_label("Theme")
+                    .selected_text(&self.themes.get(&self.theme_name).name)
+                    .show_ui(ui, |ui| {
+                        for theme in &self.themes.themes {
+                            ui.selectable_value(
+                                &mut self.theme_name,
+                                theme.name.clone(),
+                                &theme.name,
+                            );
+                        }
+                    });
+                ui.checkbox(&mut self.swap_buttons, "Swap mouse buttons");
+                ui.checkbox(&mut self.crosshair, "Crosshair")
+                    .on_hover_text("Highlight the hovered row and column and show its coordinates");
+                ui.checkbox(&mut self.analysis, "Analysis")
+                    .on_hover_text("Explain opened numbers when hovering them");
+                ui.checkbox(&mut self.mesh_rendering, "Fast board drawing")
+                    .on_hover_text("Draw the board in one go, turn off if cells look wrong");
+                ui.checkbox(&mut self.show_console, "Console")
+                    .on_hover_text("Type the commands of the terminal game (`)");
+                if ui.button("Show welcome wizard").clicked() {
+                    self.wizard_step = 0;
+                    self.show_wizard = true;
                 }
+                #[cfg(feature = "debug")]
+                ui.checkbox(&mut self.time_travel.open, "Time travel")
+                    .on_hover_text("Step through the moves of this game and inspect its state");
 
                 ui.add_space(10.0);
                 ui.separator();
@@ -148,8 +1136,58 @@ impl eframe::App for TemplateApp {
             ui.vertical_centered(|ui| {
                 ui.heading("Minesweeper");
                 ui.label(format!("FPS: {:.2}", fps));
+                ui.label(format!("Goal: {}", self.board.objective().description()));
+                ui.horizontal(|ui| {
+                    ui.label(format!("Time: {:.3}s", self.board.elapsed().as_secs_f64()));
+                    let paused = self.board.clock.is_paused();
+                    if ui
+                        .add_enabled(
+                            self.board.ongoing(),
+                            egui::Button::new(if paused { "Resume" } else { "Pause" }),
+                        )
+                        .clicked()
+                    {
+                        if paused {
+                            self.board.resume();
+                        } else {
+                            self.board.pause();
+                        }
+                    }
+                });
+                if let Some(score) = self.board.score() {
+                    ui.label(format!("3BV/s: {score:.3}"));
+                }
+                if let Some(phases) = self.phases {
+                    ui.label(format!(
+                        "First opening: {:.3}s\nMid game: {:.3}s\nEndgame: {:.3}s",
+                        phases.first_opening.as_secs_f64(),
+                        phases.mid_game.as_secs_f64(),
+                        phases.endgame.as_secs_f64()
+                    ));
+                }
+                if self.board.config.assisted_flags {
+                    ui.label(format!(
+                        "Assisted: {} wrong flags rejected",
+                        self.board.flags_rejected
+                    ));
+                }
+                if self.board.hints_used.iter().any(|&n| n > 0) {
+                    let [number, region, cell] = self.board.hints_used;
+                    ui.label(format!(
+                        "Hints: {number} number, {region} region, {cell} cell"
+                    ));
+                }
+                if self.crosshair {
+                    ui.label(match self.hovered {
+                        Some((col, row)) => format!("Cell: ({col},{row})"),
+                        None => "Cell: -".to_string(),
+                    });
+                }
             });
             ui.separator();
+            if self.board.clock.is_running() && !self.board.clock.is_paused() {
+                ctx.request_repaint_after(std::time::Duration::from_millis(50));
+            }
 
             let central_panel_rect = ui.min_rect();
             let center_x = central_panel_rect.center().x;
@@ -158,8 +1196,8 @@ impl eframe::App for TemplateApp {
 
             let max_square_size = 50.;
 
-            let max_col_size = ui.available_width() / self.cols as f32;
-            let max_row_size = ui.available_height() / self.rows as f32;
+            let max_col_size = ui.available_width() / self.board.cols as f32;
+            let max_row_size = ui.available_height() / self.board.rows as f32;
 
             let square_size = if max_row_size > max_square_size && max_col_size > max_square_size {
                 max_square_size
@@ -170,88 +1208,308 @@ impl eframe::App for TemplateApp {
             };
 
             let board_top_left = egui::Pos2 {
-                x: center_x - (self.cols as f32 / 2. * square_size),
-                y: center_y - (self.rows as f32 / 2. * square_size),
+                x: center_x - (self.board.cols as f32 / 2. * square_size),
+                y: center_y - (self.board.rows as f32 / 2. * square_size),
             };
 
-            let grid = self.board.get_board_state();
-
-            for row in 0..grid.len() {
-                for col in 0..grid[0].len() {
-                    let square = grid[row][col];
-                    let color = match square {
-                        Square::NotYetOpened => egui::Color32::from_rgb(255, 255, 255),
-                        Square::Mine => egui::Color32::from_rgb(255, 255, 255),
-                        Square::Flag => egui::Color32::from_rgb(255, 255, 255),
-                        Square::Opened(_) => egui::Color32::from_rgb(255, 255, 255),
-                    };
-                    let top_left = egui::Pos2 {
-                        x: board_top_left.x + (col as f32 * square_size),
-                        y: board_top_left.y + (row as f32 * square_size),
-                    };
-                    let bottom_right = egui::Pos2 {
-                        x: top_left.x + square_size,
-                        y: top_left.y + square_size,
-                    };
-                    let rect = egui::Rect::from_two_pos(top_left, bottom_right);
-                    let response = ui.allocate_rect(rect, egui::Sense::click_and_drag());
-                    responses.push((response, rect, color, col, row, square));
+            // the view is only needed for the constraints shown in analysis mode
+            let view = self.analysis.then(|| self.board.view());
+            let highlighted: HashSet<Position> = match self.highlight_since {
+                Some(since) if now - since < HIGHLIGHT_SECONDS => {
+                    ctx.request_repaint();
+                    self.board.last_changed().iter().copied().collect()
                 }
+                _ => HashSet::new(),
+            };
+            let rejected = match self.rejected_flag {
+                Some((pos, since)) if now - since < HIGHLIGHT_SECONDS => {
+                    ctx.request_repaint();
+                    Some(pos)
+                }
+                _ => None,
+            };
+            let hint_cells = match &self.hint {
+                Some(hint) if self.board.ongoing() => hint.positions(),
+                _ => Vec::new(),
+            };
+
+            let wormholes: HashMap<Position, usize> = self
+                .board
+                .config
+                .wormholes
+                .iter()
+                .enumerate()
+                .flat_map(|(i, &(a, b))| [(a, i), (b, i)])
+                .collect();
+            let board_theme = self.themes.get(&self.theme_name).clone();
+            let treasures: HashSet<Position> =
+                self.board.config.treasures.iter().copied().collect();
+
+            // only the cells inside the clip rect are drawn, large boards don't
+            // pay for the part that is out of view
+            let clip = ui.clip_rect();
+            let first_col = ((clip.min.x - board_top_left.x) / square_size).max(0.) as usize;
+            let first_row = ((clip.min.y - board_top_left.y) / square_size).max(0.) as usize;
+            let last_col = ((clip.max.x - board_top_left.x) / square_size)
+                .ceil()
+                .max(0.) as usize;
+            let last_row = ((clip.max.y - board_top_left.y) / square_size)
+                .ceil()
+                .max(0.) as usize;
+            let visible = Rect::new(
+                (first_col, first_row),
+                last_row.saturating_sub(first_row),
+                last_col.saturating_sub(first_col),
+            );
+            let board_rect = egui::Rect::from_min_size(
+                board_top_left,
+                egui::vec2(self.board.cols as f32, self.board.rows as f32) * square_size,
+            );
+            let cell_at = |pos: egui::Pos2| {
+                let offset = (pos - board_top_left) / square_size;
+                let cell = (offset.x as usize, offset.y as usize);
+                (board_rect.contains(pos) && self.board.contains(cell)).then_some(cell)
+            };
+            // the mesh takes the input for the whole board with one response, the
+            // fallback gives every cell its own
+            let board_response = self
+                .mesh_rendering
+                .then(|| ui.allocate_rect(board_rect, egui::Sense::click_and_drag()));
+            if self.mesh_rendering && self.atlas.is_none() {
+                self.atlas = Some(Atlas::new(ctx));
+            }
+            let atlas = self.atlas.clone().filter(|_| self.mesh_rendering);
+            for ((col, row), square) in self.board.iter_region(visible) {
+                let color = match square {
+                    _ if wormholes.contains_key(&(col, row)) => {
+                        WORMHOLE_COLORS[wormholes[&(col, row)] % WORMHOLE_COLORS.len()]
+                    }
+                    Square::NotYetOpened | Square::Mine | Square::Flag => {
+                        theme::color(board_theme.closed)
+                    }
+                    Square::Opened(_) => theme::color(board_theme.opened),
+                };
+                let top_left = egui::Pos2 {
+                    x: board_top_left.x + (col as f32 * square_size),
+                    y: board_top_left.y + (row as f32 * square_size),
+                };
+                let bottom_right = egui::Pos2 {
+                    x: top_left.x + square_size,
+                    y: top_left.y + square_size,
+                };
+                let rect = egui::Rect::from_two_pos(top_left, bottom_right);
+                let response = match board_response {
+                    Some(_) => None,
+                    None => Some(ui.allocate_rect(rect, egui::Sense::click_and_drag())),
+                };
+                responses.push((response, rect, color, col, row, square));
             }
+            let (open_button, flag_button) = if self.swap_buttons {
+                (egui::PointerButton::Secondary, egui::PointerButton::Primary)
+            } else {
+                (egui::PointerButton::Primary, egui::PointerButton::Secondary)
+            };
+            // area the first click under the cursor would keep free of mines
+            self.hovered = match &board_response {
+                Some(response) => response.hover_pos().and_then(cell_at),
+                None => responses
+                    .iter()
+                    .find(|(response, ..)| response.as_ref().is_some_and(|r| r.hovered()))
+                    .map(|&(_, _, _, col, row, _)| (col, row)),
+            };
+            let pressed_cell = board_response
+                .as_ref()
+                .filter(|response| response.is_pointer_button_down_on())
+                .and_then(|response| response.interact_pointer_pos())
+                .and_then(cell_at);
+            let preview: HashSet<Position> = match self.hovered {
+                Some(pos) if self.preview_safe_area && !self.board.initialized() => {
+                    self.board.safe_area(pos).into_iter().collect()
+                }
+                _ => HashSet::new(),
+            };
             let painter = ui.painter();
+            // reserved below the outlines, filled in once all cells are in the mesh
+            let mesh_index = painter.add(egui::Shape::Noop);
+            let mut mesh = atlas.as_ref().map(|atlas| {
+                let mut mesh = atlas.mesh();
+                let grid = board_rect.intersect(painter.clip_rect());
+                atlas.fill(&mut mesh, grid, theme::color(board_theme.grid));
+                mesh
+            });
             for (response, rect, color, col, row, square) in responses {
-                painter.rect_filled(rect, 0.0, color);
-                let stroke = egui::Stroke::new(rect.width() * 0.02, egui::Color32::BLACK);
-                painter.rect_stroke(rect, 0.0, stroke, egui::StrokeKind::Middle);
+                let on_crosshair = self.crosshair
+                    && self
+                        .hovered
+                        .is_some_and(|(hover_col, hover_row)| hover_col == col || hover_row == row);
+                let color = if preview.contains(&(col, row)) {
+                    egui::Color32::from_rgb(166, 227, 161)
+                } else if on_crosshair {
+                    egui::Color32::from_rgb(220, 224, 232)
+                } else {
+                    color
+                };
+                match (&mut mesh, &atlas) {
+                    (Some(mesh), Some(atlas)) => {
+                        atlas.fill(mesh, rect.shrink(rect.width() * 0.01), color)
+                    }
+                    _ => {
+                        painter.rect_filled(rect, 0.0, color);
+                        let stroke =
+                            egui::Stroke::new(rect.width() * 0.02, theme::color(board_theme.grid));
+                        painter.rect_stroke(rect, 0.0, stroke, egui::StrokeKind::Middle);
+                    }
+                }
+                if hint_cells.contains(&(col, row)) {
+                    let outline = egui::Stroke::new(
+                        rect.width() * 0.08,
+                        egui::Color32::from_rgb(64, 160, 43),
+                    );
+                    painter.rect_stroke(
+                        rect.shrink(rect.width() * 0.04),
+                        0.0,
+                        outline,
+                        egui::StrokeKind::Middle,
+                    );
+                }
+                if highlighted.contains(&(col, row)) {
+                    let outline = egui::Stroke::new(
+                        rect.width() * 0.08,
+                        egui::Color32::from_rgb(255, 165, 0),
+                    );
+                    painter.rect_stroke(
+                        rect.shrink(rect.width() * 0.04),
+                        0.0,
+                        outline,
+                        egui::StrokeKind::Middle,
+                    );
+                }
+                if rejected == Some((col, row)) {
+                    let outline = egui::Stroke::new(
+                        rect.width() * 0.08,
+                        egui::Color32::from_rgb(210, 15, 57),
+                    );
+                    painter.rect_stroke(
+                        rect.shrink(rect.width() * 0.04),
+                        0.0,
+                        outline,
+                        egui::StrokeKind::Middle,
+                    );
+                }
                 let text_pos = rect.center();
                 let pos_str = match square {
+                    Square::NotYetOpened if treasures.contains(&(col, row)) => "💎",
                     Square::NotYetOpened => "",
                     Square::Mine => "💣",
                     Square::Flag => "🚩",
                     Square::Opened(count) => &format!("{}", count),
                 };
+                // anti-mines and the negative counts around them stand out in purple
+                let text_color = match square {
+                    Square::Opened(count) if count < 0 => theme::color(board_theme.negative),
+                    Square::Mine if self.board.is_anti_mine((col, row)) => {
+                        theme::color(board_theme.negative)
+                    }
+                    _ => theme::color(board_theme.text),
+                };
+                let pressed = match &response {
+                    Some(response) => response.is_pointer_button_down_on(),
+                    None => pressed_cell == Some((col, row)),
+                };
                 // check for primary button press
-                if response.is_pointer_button_down_on()
+                if pressed
                     && !self.last_primary_press_processed
-                    && ctx.input(|i| i.pointer.button_down(egui::PointerButton::Primary))
+                    && ctx.input(|i| i.pointer.button_down(open_button))
                 {
                     self.primary_button_down_event_fired = true;
                     self.last_primary_press_processed = true;
                     if !self.board.initialized() {
-                        self.board.init_mines(
-                            (col, row),
-                            if self.use_seed { Some(self.seed) } else { None },
-                        );
-                    } else {
+                        self.first_click((col, row), now);
+                    } else if let Square::Opened(_) = square {
                         // TODO handle result
-                        let _open_res = self.board.open((col, row));
+                        if self.board.chord((col, row)).is_ok() {
+                            self.highlight_since = Some(now);
+                            self.hint = None;
+                        }
+                    } else if self.warn_guesses {
+                        let request = Request::SafeAlternative(self.board.clone(), (col, row));
+                        self.ask_worker(request, Job::Guess((col, row)));
+                    } else {
+                        self.open(col, row, now);
                     }
                 }
                 // Reset the processed flag when button is use released
-                if ctx.input(|i| i.pointer.button_released(egui::PointerButton::Primary)) {
+                if ctx.input(|i| i.pointer.button_released(open_button)) {
                     self.last_primary_press_processed = false;
                 }
                 // check for secondary button press
-                if response.is_pointer_button_down_on()
+                if pressed
                     && !self.last_secondary_press_processed
-                    && ctx.input(|i| i.pointer.button_down(egui::PointerButton::Secondary))
+                    && ctx.input(|i| i.pointer.button_down(flag_button))
                 {
                     self.secondary_button_down_event_fired = true;
                     self.last_secondary_press_processed = true;
-                    // TODO handle result
-                    let _flag_res = self.board.flag((col, row));
+                    match self.board.flag((col, row)) {
+                        Ok(_) => {
+                            self.highlight_since = Some(now);
+                            self.hint = None;
+                        }
+                        Err(FlagError::NotAMine) => self.rejected_flag = Some(((col, row), now)),
+                        Err(_) => {}
+                    }
                 }
                 // Reset the processed flag when button is use released
-                if ctx.input(|i| i.pointer.button_released(egui::PointerButton::Secondary)) {
+                if ctx.input(|i| i.pointer.button_released(flag_button)) {
                     self.last_secondary_press_processed = false;
                 }
-                painter.text(
-                    text_pos,
-                    egui::Align2::CENTER_CENTER,
-                    pos_str,
-                    egui::FontId::proportional(square_size * 0.25),
-                    egui::Color32::BLACK,
-                );
+                match (&mut mesh, &atlas) {
+                    (Some(mesh), Some(atlas)) => {
+                        let (glyphs, color) = match square {
+                            Square::NotYetOpened if treasures.contains(&(col, row)) => (
+                                atlas::TREASURE.to_string(),
+                                egui::Color32::from_rgb(4, 165, 229),
+                            ),
+                            Square::NotYetOpened => (String::new(), text_color),
+                            Square::Mine => (atlas::MINE.to_string(), text_color),
+                            Square::Flag => (
+                                atlas::FLAG.to_string(),
+                                egui::Color32::from_rgb(210, 15, 57),
+                            ),
+                            Square::Opened(count) => (count.to_string(), text_color),
+                        };
+                        atlas.text(mesh, rect, &glyphs, square_size * 0.3, color);
+                    }
+                    _ => {
+                        painter.text(
+                            text_pos,
+                            egui::Align2::CENTER_CENTER,
+                            pos_str,
+                            egui::FontId::proportional(square_size * 0.25),
+                            text_color,
+                        );
+                    }
+                }
+                let tooltip = match response {
+                    Some(response) => Some(response),
+                    None if self.hovered == Some((col, row)) => board_response.clone(),
+                    None => None,
+                };
+                if let (Some(view), Some(response)) = (&view, tooltip) {
+                    if let Some(constraint) = view.constraint((col, row)) {
+                        let status = if constraint.satisfied() {
+                            "satisfied"
+                        } else {
+                            "not satisfied"
+                        };
+                        response.on_hover_text(format!(
+                            "Value: {}\nFlags around: {}\nUnopened neighbors: {}\n{status}",
+                            constraint.value, constraint.flags, constraint.unopened
+                        ));
+                    }
+                }
+            }
+            if let Some(mesh) = mesh {
+                painter.set(mesh_index, egui::Shape::mesh(mesh));
             }
 
             ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
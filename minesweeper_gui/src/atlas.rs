@@ -0,0 +1,117 @@
+//! Drawing the board as a single mesh: the glyphs of the cells are pixel
+//! bitmaps packed into one texture, so every cell is a couple of textured quads
+//! instead of shapes and text laid out one by one. Large boards stay at full
+//! frame rate this way.
+
+// glyphs drawn with `#`, all of them 7 pixels high
+#[rustfmt::skip]
+const GLYPHS: [(char, [&str; 7]); 14] = [
+    ('0', [" ### ", "#   #", "#  ##", "# # #", "##  #", "#   #", " ### "]),
+    ('1', ["  #  ", " ##  ", "  #  ", "  #  ", "  #  ", "  #  ", " ### "]),
+    ('2', [" ### ", "#   #", "    #", "   # ", "  #  ", " #   ", "#####"]),
+    ('3', ["#####", "   # ", "  #  ", "   # ", "    #", "#   #", " ### "]),
+    ('4', ["   # ", "  ## ", " # # ", "#  # ", "#####", "   # ", "   # "]),
+    ('5', ["#####", "#    ", "#### ", "    #", "    #", "#   #", " ### "]),
+    ('6', ["  ## ", " #   ", "#    ", "#### ", "#   #", "#   #", " ### "]),
+    ('7', ["#####", "    #", "   # ", "  #  ", " #   ", " #   ", " #   "]),
+    ('8', [" ### ", "#   #", "#   #", " ### ", "#   #", "#   #", " ### "]),
+    ('9', [" ### ", "#   #", "#   #", " ####", "    #", "   # ", " ##  "]),
+    ('-', ["   ", "   ", "   ", "###", "   ", "   ", "   "]),
+    (FLAG, [" ##    ", " ####  ", " ######", " ####  ", " #     ", " #     ", "#####  "]),
+    (MINE, ["   #   ", " # # # ", "  ###  ", "#######", "  ###  ", " # # # ", "   #   "]),
+    (TREASURE, ["  ###  ", " ##### ", "#######", " ##### ", "  ###  ", "   #   ", "       "]),
+];
+
+pub const FLAG: char = 'F';
+pub const MINE: char = '*';
+pub const TREASURE: char = 'D';
+
+const HEIGHT: usize = 7;
+// space between glyphs in the texture, so neighbors don't bleed into each other
+const PADDING: usize = 1;
+
+#[derive(Clone)]
+pub struct Atlas {
+    texture: egui::TextureHandle,
+    size: egui::Vec2,
+    // pixel rect of every glyph
+    glyphs: Vec<(char, egui::Rect)>,
+    // a block of white pixels for the plain quads
+    white: egui::Pos2,
+}
+
+impl Atlas {
+    pub fn new(ctx: &egui::Context) -> Atlas {
+        let glyph_width: usize = GLYPHS.iter().map(|(_, rows)| rows[0].len() + PADDING).sum();
+        let size = [glyph_width + 2 + PADDING, HEIGHT + PADDING];
+        let mut image = egui::ColorImage::new(size, egui::Color32::TRANSPARENT);
+        let mut glyphs = Vec::new();
+        let mut x0 = 0;
+        for (c, rows) in GLYPHS {
+            for (y, row) in rows.iter().enumerate() {
+                for (x, pixel) in row.chars().enumerate() {
+                    if pixel == '#' {
+                        image[(x0 + x, y)] = egui::Color32::WHITE;
+                    }
+                }
+            }
+            let width = rows[0].len();
+            let rect = egui::Rect::from_min_size(
+                egui::pos2(x0 as f32, 0.),
+                egui::vec2(width as f32, HEIGHT as f32),
+            );
+            glyphs.push((c, rect));
+            x0 += width + PADDING;
+        }
+        for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            image[(x0 + x, y)] = egui::Color32::WHITE;
+        }
+        Atlas {
+            texture: ctx.load_texture("cell glyphs", image, egui::TextureOptions::NEAREST),
+            size: egui::vec2(size[0] as f32, size[1] as f32),
+            glyphs,
+            white: egui::pos2(x0 as f32 + 1., 1.),
+        }
+    }
+
+    pub fn mesh(&self) -> egui::Mesh {
+        egui::Mesh::with_texture(self.texture.id())
+    }
+
+    /// A quad of a single color.
+    pub fn fill(&self, mesh: &mut egui::Mesh, rect: egui::Rect, color: egui::Color32) {
+        let white = egui::pos2(self.white.x / self.size.x, self.white.y / self.size.y);
+        let uv = egui::Rect::from_center_size(white, egui::Vec2::ZERO);
+        mesh.add_rect_with_uv(rect, uv, color);
+    }
+
+    /// `text` centered in `rect`, `height` pixels high. Characters without a
+    /// glyph are skipped.
+    pub fn text(
+        &self,
+        mesh: &mut egui::Mesh,
+        rect: egui::Rect,
+        text: &str,
+        height: f32,
+        color: egui::Color32,
+    ) {
+        let scale = height / HEIGHT as f32;
+        let glyphs: Vec<egui::Rect> = text
+            .chars()
+            .filter_map(|c| self.glyphs.iter().find(|(g, _)| *g == c))
+            .map(|&(_, pixels)| pixels)
+            .collect();
+        let pixels: f32 = glyphs.iter().map(|g| g.width() + 1.).sum::<f32>() - 1.;
+        let mut x = rect.center().x - pixels * scale / 2.;
+        let y = rect.center().y - height / 2.;
+        for pixels in glyphs {
+            let quad = egui::Rect::from_min_size(egui::pos2(x, y), pixels.size() * scale);
+            let uv = egui::Rect::from_min_max(
+                egui::pos2(pixels.min.x / self.size.x, pixels.min.y / self.size.y),
+                egui::pos2(pixels.max.x / self.size.x, pixels.max.y / self.size.y),
+            );
+            mesh.add_rect_with_uv(quad, uv, color);
+            x += (pixels.width() + 1.) * scale;
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use minesweeper::board::{row_major, Board, Move, Position};
+use minesweeper::replay::Replay;
+
+/// Developer window to step through the history of the current game.
+#[derive(Default)]
+pub struct TimeTravel {
+    pub open: bool,
+    // the timeline being browsed, kept when jumping back so later moves stay reachable
+    timeline: Option<Replay>,
+    // number of actions applied to the shown board
+    step: usize,
+}
+
+impl TimeTravel {
+    /// Shows the window, replaces `board` when jumping to another move.
+    pub fn show(&mut self, ctx: &egui::Context, board: &mut Board) {
+        self.follow(board);
+        let mut open = self.open;
+        let mut jump = None;
+        egui::Window::new("Time travel")
+            .open(&mut open)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                let Some(timeline) = &self.timeline else {
+                    ui.label("No moves yet");
+                    return;
+                };
+                ui.label(format!(
+                    "Move {} of {}, seed {}",
+                    self.step,
+                    timeline.actions.len(),
+                    timeline.seed
+                ));
+                egui::ScrollArea::vertical()
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        for (i, action) in timeline.actions.iter().enumerate() {
+                            let text = format!(
+                                "{:>3}  {:>8.3}s  {}",
+                                i + 1,
+                                action.elapsed.as_secs_f64(),
+                                describe(action.mv)
+                            );
+                            if ui.selectable_label(self.step == i + 1, text).clicked() {
+                                jump = Some(i + 1);
+                            }
+                        }
+                    });
+                ui.separator();
+                inspect(ui, board);
+            });
+        self.open = open;
+
+        if let Some(step) = jump {
+            if let Some(snapshot) = self.timeline.as_ref().and_then(|t| t.board_at(step).ok()) {
+                *board = snapshot;
+                self.step = step;
+            }
+        }
+    }
+
+    /// Picks up moves made on the board. A move that leaves the timeline starts a new one.
+    fn follow(&mut self, board: &Board) {
+        let Some(live) = board.replay() else {
+            self.timeline = None;
+            self.step = 0;
+            return;
+        };
+        let on_timeline = self.timeline.as_ref().is_some_and(|timeline| {
+            timeline.seed == live.seed
+                && live.actions.len() <= timeline.actions.len()
+                && live
+                    .actions
+                    .iter()
+                    .zip(&timeline.actions)
+                    .all(|(a, b)| a.mv == b.mv)
+        });
+        self.step = live.actions.len();
+        if !on_timeline {
+            self.timeline = Some(live);
+        }
+    }
+}
+
+fn describe(mv: Move) -> String {
+    match mv {
+        Move::Open((x, y)) => format!("open ({x},{y})"),
+        Move::Flag((x, y)) => format!("flag ({x},{y})"),
+        Move::Chord((x, y)) => format!("chord ({x},{y})"),
+    }
+}
+
+fn positions(positions: impl IntoIterator<Item = Position>) -> String {
+    row_major(positions)
+        .iter()
+        .map(|(x, y)| format!("({x},{y})"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The internal state of `board`, with the text layout to copy into tests or bug reports.
+fn inspect(ui: &mut egui::Ui, board: &Board) {
+    ui.label(format!("State: {:?}", board.state));
+    ui.label(format!("Elapsed: {:.3}s", board.elapsed().as_secs_f64()));
+    egui::CollapsingHeader::new(format!("Open fields ({})", board.open_fields.len()))
+        .show(ui, |ui| ui.label(positions(board.open_fields.iter())));
+    egui::CollapsingHeader::new(format!("Flagged fields ({})", board.flagged_fields.len()))
+        .show(ui, |ui| ui.label(positions(board.flagged_fields.iter())));
+    egui::CollapsingHeader::new(format!("Last changed ({})", board.last_changed().len()))
+        .show(ui, |ui| {
+            ui.label(positions(board.last_changed().iter().copied()))
+        });
+    if let Some(layout) = board.to_layout_string() {
+        egui::CollapsingHeader::new("Layout").show(ui, |ui| {
+            ui.monospace(layout.trim_end());
+            if ui.button("Copy layout").clicked() {
+                ui.ctx().copy_text(layout.clone());
+            }
+        });
+    }
+}
@@ -0,0 +1,59 @@
+//! Installed puzzle packs, see `minesweeper::pack`. The built-in ones are
+//! embedded from `packs/`, natively more are read from the `*.pack` files in the
+//! directory named by `MINESWEEPER_PACKS`.
+
+use minesweeper::pack::Pack;
+#[cfg(not(target_arch = "wasm32"))]
+use minesweeper::prelude::UserFacingError;
+
+const BUILTIN: [&str; 1] = [include_str!("../packs/first_steps.pack")];
+
+pub struct Packs {
+    pub packs: Vec<Pack>,
+}
+
+impl Default for Packs {
+    fn default() -> Packs {
+        Packs::load()
+    }
+}
+
+impl Packs {
+    pub fn load() -> Packs {
+        #[cfg_attr(target_arch = "wasm32", allow(unused_mut))]
+        let mut packs: Vec<Pack> = BUILTIN
+            .iter()
+            .map(|source| Pack::parse(source).expect("Invalid built-in pack"))
+            .collect();
+        #[cfg(not(target_arch = "wasm32"))]
+        for path in user_files() {
+            let pack = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| {
+                    Pack::parse(&source).map_err(|e| UserFacingError::from(e).to_string())
+                });
+            match pack {
+                Ok(pack) => packs.push(pack),
+                Err(e) => log::warn!("Skipping pack {}: {e}", path.display()),
+            }
+        }
+        Packs { packs }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn user_files() -> Vec<std::path::PathBuf> {
+    let Some(dir) = std::env::var_os("MINESWEEPER_PACKS") else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "pack"))
+        .collect();
+    files.sort();
+    files
+}
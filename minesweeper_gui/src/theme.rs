@@ -0,0 +1,137 @@
+//! Themes of the app: a catppuccin flavor for the widgets plus the colors of the
+//! board. The built-in ones are embedded from `themes/`, natively more are read
+//! from the `*.toml` files in the directory named by `MINESWEEPER_THEMES`. A
+//! file with the name of a built-in theme replaces it.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::PathBuf;
+
+const BUILTIN: [&str; 4] = [
+    include_str!("../themes/latte.toml"),
+    include_str!("../themes/frappe.toml"),
+    include_str!("../themes/macchiato.toml"),
+    include_str!("../themes/mocha.toml"),
+];
+
+// picked when the saved theme no longer exists
+const DEFAULT: &str = "Mocha (dark)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Base {
+    Latte,
+    Frappe,
+    Macchiato,
+    Mocha,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct Theme {
+    pub name: String,
+    pub base: Base,
+    pub closed: [u8; 3],
+    pub opened: [u8; 3],
+    pub text: [u8; 3],
+    /// Counts below zero and anti-mines.
+    pub negative: [u8; 3],
+    pub grid: [u8; 3],
+}
+
+impl Theme {
+    pub fn catppuccin(&self) -> catppuccin_egui::Theme {
+        match self.base {
+            Base::Latte => catppuccin_egui::LATTE,
+            Base::Frappe => catppuccin_egui::FRAPPE,
+            Base::Macchiato => catppuccin_egui::MACCHIATO,
+            Base::Mocha => catppuccin_egui::MOCHA,
+        }
+    }
+}
+
+pub fn color([r, g, b]: [u8; 3]) -> egui::Color32 {
+    egui::Color32::from_rgb(r, g, b)
+}
+
+pub struct Themes {
+    pub themes: Vec<Theme>,
+    // files of the user themes and when they were last modified
+    #[cfg(all(feature = "debug", not(target_arch = "wasm32")))]
+    files: Vec<(PathBuf, Option<std::time::SystemTime>)>,
+}
+
+impl Default for Themes {
+    fn default() -> Themes {
+        Themes::load()
+    }
+}
+
+impl Themes {
+    pub fn load() -> Themes {
+        #[cfg_attr(target_arch = "wasm32", allow(unused_mut))]
+        let mut themes: Vec<Theme> = BUILTIN
+            .iter()
+            .map(|source| toml::from_str(source).expect("Invalid built-in theme"))
+            .collect();
+        #[cfg(not(target_arch = "wasm32"))]
+        let files = user_files();
+        #[cfg(not(target_arch = "wasm32"))]
+        for (path, _) in &files {
+            let theme = std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| toml::from_str::<Theme>(&source).map_err(|e| e.to_string()));
+            match theme {
+                Ok(theme) => match themes.iter_mut().find(|t| t.name == theme.name) {
+                    Some(builtin) => *builtin = theme,
+                    None => themes.push(theme),
+                },
+                Err(e) => log::warn!("Skipping theme {}: {e}", path.display()),
+            }
+        }
+        Themes {
+            themes,
+            #[cfg(all(feature = "debug", not(target_arch = "wasm32")))]
+            files,
+        }
+    }
+
+    /// The theme called `name`, or the default one.
+    pub fn get(&self, name: &str) -> &Theme {
+        self.themes
+            .iter()
+            .find(|theme| theme.name == name)
+            .or_else(|| self.themes.iter().find(|theme| theme.name == DEFAULT))
+            .unwrap_or(&self.themes[0])
+    }
+
+    /// Loads the themes again when a file was added, removed or changed, so
+    /// theme authors see their changes without restarting.
+    #[cfg(all(feature = "debug", not(target_arch = "wasm32")))]
+    pub fn reload_if_changed(&mut self) -> bool {
+        if user_files() == self.files {
+            return false;
+        }
+        *self = Themes::load();
+        true
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn user_files() -> Vec<(PathBuf, Option<std::time::SystemTime>)> {
+    let Some(dir) = std::env::var_os("MINESWEEPER_THEMES") else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .map(|path| {
+            let modified = path.metadata().and_then(|m| m.modified()).ok();
+            (path, modified)
+        })
+        .collect();
+    files.sort();
+    files
+}
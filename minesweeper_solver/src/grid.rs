@@ -0,0 +1,717 @@
+use std::collections::VecDeque;
+
+/// Cell coordinates as `(x, y)`, i.e. `(column, row)`.
+///
+/// Every list of positions returned by this crate is sorted in row-major order
+/// (by row, then by column), so results never depend on hash set iteration order.
+pub type Position = (usize, usize);
+
+/// Collects positions in row-major order.
+pub fn row_major(positions: impl IntoIterator<Item = Position>) -> Vec<Position> {
+    let mut positions = Vec::from_iter(positions);
+    positions.sort_unstable_by_key(|&(x, y)| (y, x));
+    positions
+}
+
+const DIRS: [(isize, isize); 8] = [
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+];
+
+// the cells a chess knight can jump to
+const KNIGHT_DIRS: [(isize, isize); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+
+// hexagons in offset rows, odd rows are shifted half a cell to the right
+const HEX_DIRS_EVEN: [(isize, isize); 6] = [(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)];
+const HEX_DIRS_ODD: [(isize, isize); 6] = [(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)];
+
+/// The in-bounds cells surrounding `(x, y)` on a `rows` x `cols` grid.
+pub fn neighbors(rows: usize, cols: usize, pos: Position) -> impl Iterator<Item = Position> {
+    offsets(rows, cols, pos, &DIRS, false)
+}
+
+/// The cells surrounding `(x, y)` when the edges of the grid wrap around, so the
+/// first and last columns and the first and last rows touch (a torus).
+pub fn wrapping_neighbors(
+    rows: usize,
+    cols: usize,
+    pos: Position,
+) -> impl Iterator<Item = Position> {
+    offsets(rows, cols, pos, &DIRS, true)
+}
+
+fn offsets(
+    rows: usize,
+    cols: usize,
+    (x, y): Position,
+    dirs: &'static [(isize, isize)],
+    wrap_edges: bool,
+) -> impl Iterator<Item = Position> {
+    let (r, c) = (rows as isize, cols as isize);
+    let (x, y) = (x as isize, y as isize);
+    let step = move |&(dx, dy): &(isize, isize)| {
+        let (nx, ny) = (x + dx, y + dy);
+        if wrap_edges {
+            Some((nx.rem_euclid(c), ny.rem_euclid(r)))
+        } else {
+            (nx >= 0 && nx < c && ny >= 0 && ny < r).then_some((nx, ny))
+        }
+    };
+    dirs.iter().enumerate().filter_map(move |(i, dir)| {
+        let n = step(dir)?;
+        // on wrapped boards this narrow, several directions reach the same cell
+        let repeated = wrap_edges && dirs[..i].iter().any(|earlier| step(earlier) == Some(n));
+        (n != (x, y) && !repeated).then_some((n.0 as usize, n.1 as usize))
+    })
+}
+
+/// Which cells of a square grid are neighbors, and so count towards each other's numbers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Adjacency {
+    /// The eight surrounding cells.
+    #[default]
+    King,
+    /// The eight cells a knight's move away, as in Knight sweeper.
+    Knight,
+}
+
+impl Adjacency {
+    fn dirs(self) -> &'static [(isize, isize)] {
+        match self {
+            Adjacency::King => &DIRS,
+            Adjacency::Knight => &KNIGHT_DIRS,
+        }
+    }
+}
+
+/// The cells touching `pos` only through a wormhole, given the plain `neighbors`
+/// of any cell. The cells of a pair in `wormholes` touch the neighbors of each
+/// other, so a wormhole cell touches its partner's neighbors and those touch it.
+pub fn wormhole_neighbors<I: Iterator<Item = Position>>(
+    wormholes: &[(Position, Position)],
+    pos: Position,
+    neighbors: impl Fn(Position) -> I,
+) -> Vec<Position> {
+    if wormholes.is_empty() {
+        return Vec::new();
+    }
+    let partner = |cell: Position| {
+        wormholes
+            .iter()
+            .find_map(|&(a, b)| (cell == a).then_some(b).or((cell == b).then_some(a)))
+    };
+    let plain: Vec<Position> = neighbors(pos).collect();
+    let mut extra = Vec::new();
+    let through_partner = partner(pos).into_iter().flat_map(&neighbors);
+    let partners_of_neighbors = plain.iter().filter_map(|&n| partner(n));
+    for n in through_partner.chain(partners_of_neighbors) {
+        if n != pos && !plain.contains(&n) && !extra.contains(&n) {
+            extra.push(n);
+        }
+    }
+    extra
+}
+
+/// How the cells of a `rows` x `cols` board touch each other. Cells are always
+/// addressed by column and row, so only the neighbors differ between topologies.
+pub trait Topology: std::fmt::Debug + Default + Clone + Copy + PartialEq + Eq {
+    /// The in-bounds cells touching `pos`, also those across the edges with
+    /// `wrap_edges`. Wrapping hexagonal rows needs an even number of rows.
+    fn neighbors(
+        self,
+        rows: usize,
+        cols: usize,
+        pos: Position,
+        wrap_edges: bool,
+    ) -> impl Iterator<Item = Position>;
+
+    /// Like `neighbors`, with the cells touching as `adjacency` says. Only square
+    /// cells have other adjacencies, the other topologies ignore it.
+    fn adjacent(
+        self,
+        rows: usize,
+        cols: usize,
+        pos: Position,
+        wrap_edges: bool,
+        _adjacency: Adjacency,
+    ) -> impl Iterator<Item = Position> {
+        self.neighbors(rows, cols, pos, wrap_edges)
+    }
+
+    fn contains(self, rows: usize, cols: usize, (x, y): Position) -> bool {
+        x < cols && y < rows
+    }
+
+    /// Row-major index of a cell, `None` if it is outside the board.
+    fn index(self, rows: usize, cols: usize, pos: Position) -> Option<usize> {
+        self.contains(rows, cols, pos).then(|| pos.1 * cols + pos.0)
+    }
+}
+
+/// Square cells touching their eight surrounding cells, the classic board.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SquareGrid;
+
+impl Topology for SquareGrid {
+    fn neighbors(
+        self,
+        rows: usize,
+        cols: usize,
+        pos: Position,
+        wrap_edges: bool,
+    ) -> impl Iterator<Item = Position> {
+        offsets(rows, cols, pos, &DIRS, wrap_edges)
+    }
+
+    fn adjacent(
+        self,
+        rows: usize,
+        cols: usize,
+        pos: Position,
+        wrap_edges: bool,
+        adjacency: Adjacency,
+    ) -> impl Iterator<Item = Position> {
+        offsets(rows, cols, pos, adjacency.dirs(), wrap_edges)
+    }
+}
+
+/// Hexagonal cells with six neighbors, laid out in rows where every odd row is
+/// shifted half a cell to the right.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HexGrid;
+
+impl Topology for HexGrid {
+    fn neighbors(
+        self,
+        rows: usize,
+        cols: usize,
+        pos: Position,
+        wrap_edges: bool,
+    ) -> impl Iterator<Item = Position> {
+        let dirs = match pos.1 % 2 {
+            0 => &HEX_DIRS_EVEN,
+            _ => &HEX_DIRS_ODD,
+        };
+        offsets(rows, cols, pos, dirs, wrap_edges)
+    }
+}
+
+/// Cubic cells in `layers` stacked layers, touching the 26 cells around them.
+///
+/// Positions stay two dimensional: the layers are placed below each other, so
+/// the board has `layers` times the rows of one layer and cell `(x, y)` of layer
+/// `z` is at `(x, z * layer_rows + y)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CubeGrid {
+    pub layers: usize,
+}
+
+impl Default for CubeGrid {
+    fn default() -> Self {
+        CubeGrid { layers: 1 }
+    }
+}
+
+impl CubeGrid {
+    /// Rows of one layer of a board with `rows` rows in total.
+    pub fn layer_rows(self, rows: usize) -> usize {
+        rows / self.layers.max(1)
+    }
+}
+
+impl Topology for CubeGrid {
+    fn neighbors(
+        self,
+        rows: usize,
+        cols: usize,
+        (x, y): Position,
+        wrap_edges: bool,
+    ) -> impl Iterator<Item = Position> {
+        let layer_rows = self.layer_rows(rows).max(1) as isize;
+        let (layers, cols) = (self.layers as isize, cols as isize);
+        let (x, y, z) = (x as isize, y as isize % layer_rows, y as isize / layer_rows);
+        let step = move |(dx, dy, dz): (isize, isize, isize)| {
+            let (nx, ny, nz) = (x + dx, y + dy, z + dz);
+            if wrap_edges {
+                Some((
+                    nx.rem_euclid(cols),
+                    ny.rem_euclid(layer_rows),
+                    nz.rem_euclid(layers),
+                ))
+            } else {
+                let inside = (0..cols).contains(&nx)
+                    && (0..layer_rows).contains(&ny)
+                    && (0..layers).contains(&nz);
+                inside.then_some((nx, ny, nz))
+            }
+        };
+        let dirs = (-1..=1)
+            .flat_map(|dz| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dx| (dx, dy, dz))));
+        dirs.clone().enumerate().filter_map(move |(i, dir)| {
+            let n = step(dir)?;
+            // on wrapped boards this narrow, several directions reach the same cell
+            let repeated =
+                wrap_edges && dirs.clone().take(i).any(|earlier| step(earlier) == Some(n));
+            (n != (x, y, z) && !repeated)
+                .then_some((n.0 as usize, (n.2 * layer_rows + n.1) as usize))
+        })
+    }
+}
+
+/// Dimensions of a rectangular board, mapping positions to flat row-major indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Grid {
+    pub rows: usize,
+    pub cols: usize,
+    /// Whether the edges wrap around, see `wrapping_neighbors`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub wrap_edges: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub adjacency: Adjacency,
+}
+
+impl Grid {
+    pub fn new(rows: usize, cols: usize) -> Grid {
+        Grid {
+            rows,
+            cols,
+            wrap_edges: false,
+            adjacency: Adjacency::King,
+        }
+    }
+
+    /// Number of cells.
+    pub fn len(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains(&self, (x, y): Position) -> bool {
+        x < self.cols && y < self.rows
+    }
+
+    pub fn index(&self, pos: Position) -> Option<usize> {
+        self.contains(pos).then(|| pos.1 * self.cols + pos.0)
+    }
+
+    pub fn position(&self, index: usize) -> Option<Position> {
+        (index < self.len()).then(|| (index % self.cols, index / self.cols))
+    }
+
+    /// Every cell in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let cols = self.cols;
+        (0..self.rows).flat_map(move |y| (0..cols).map(move |x| (x, y)))
+    }
+
+    pub fn neighbors(&self, pos: Position) -> impl Iterator<Item = Position> {
+        offsets(
+            self.rows,
+            self.cols,
+            pos,
+            self.adjacency.dirs(),
+            self.wrap_edges,
+        )
+    }
+
+    /// Cells reachable from `start` by stepping between neighbors for which
+    /// `passable` holds, `start` included. Empty if `start` itself is not passable.
+    pub fn flood_fill(
+        &self,
+        start: Position,
+        mut passable: impl FnMut(Position) -> bool,
+    ) -> Vec<Position> {
+        let mut seen = Mask::new(*self);
+        if !self.contains(start) || !passable(start) {
+            return Vec::new();
+        }
+        seen.insert(start);
+        let mut queue = VecDeque::from([start]);
+        while let Some(pos) = queue.pop_front() {
+            for n in self.neighbors(pos) {
+                if !seen.contains(n) && passable(n) {
+                    seen.insert(n);
+                    queue.push_back(n);
+                }
+            }
+        }
+        seen.iter().collect()
+    }
+}
+
+/// A set of cells of a grid, one bit per cell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Mask {
+    grid: Grid,
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl Mask {
+    pub fn new(grid: Grid) -> Mask {
+        Mask {
+            grid,
+            bits: vec![0; grid.len().div_ceil(64)],
+            len: 0,
+        }
+    }
+
+    /// Positions outside the grid are ignored.
+    pub fn from_positions(grid: Grid, positions: impl IntoIterator<Item = Position>) -> Mask {
+        let mut mask = Mask::new(grid);
+        for pos in positions {
+            mask.insert(pos);
+        }
+        mask
+    }
+
+    pub fn grid(&self) -> Grid {
+        self.grid
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        self.grid
+            .index(pos)
+            .is_some_and(|i| self.bits[i / 64] & (1 << (i % 64)) != 0)
+    }
+
+    /// Returns whether the cell was newly added, `false` when out of bounds.
+    pub fn insert(&mut self, pos: Position) -> bool {
+        let Some(i) = self.grid.index(pos) else {
+            return false;
+        };
+        let added = self.bits[i / 64] & (1 << (i % 64)) == 0;
+        self.bits[i / 64] |= 1 << (i % 64);
+        self.len += added as usize;
+        added
+    }
+
+    /// Returns whether the cell was present.
+    pub fn remove(&mut self, pos: Position) -> bool {
+        let Some(i) = self.grid.index(pos) else {
+            return false;
+        };
+        let present = self.bits[i / 64] & (1 << (i % 64)) != 0;
+        self.bits[i / 64] &= !(1 << (i % 64));
+        self.len -= present as usize;
+        present
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+        self.len = 0;
+    }
+
+    /// Whether every cell of the mask is also in `other`, which covers the same grid.
+    pub fn is_subset(&self, other: &Mask) -> bool {
+        self.bits.iter().zip(&other.bits).all(|(a, b)| a & !b == 0)
+    }
+
+    /// Cells in the mask in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = Position> + '_ {
+        self.grid.positions().filter(|&pos| self.contains(pos))
+    }
+}
+
+/// Neighbors of every cell of a grid, computed once for hot loops.
+#[derive(Debug, Clone)]
+pub struct NeighborCache {
+    grid: Grid,
+    // neighbors of cell i are neighbors[offsets[i]..offsets[i + 1]]
+    offsets: Vec<usize>,
+    neighbors: Vec<Position>,
+}
+
+impl NeighborCache {
+    pub fn new(grid: Grid) -> NeighborCache {
+        let mut offsets = Vec::with_capacity(grid.len() + 1);
+        let mut neighbors = Vec::with_capacity(grid.len() * 8);
+        offsets.push(0);
+        for pos in grid.positions() {
+            neighbors.extend(grid.neighbors(pos));
+            offsets.push(neighbors.len());
+        }
+        NeighborCache {
+            grid,
+            offsets,
+            neighbors,
+        }
+    }
+
+    /// Neighbors of `pos`, empty when out of bounds.
+    pub fn get(&self, pos: Position) -> &[Position] {
+        match self.grid.index(pos) {
+            Some(i) => &self.neighbors[self.offsets[i]..self.offsets[i + 1]],
+            None => &[],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_major() {
+        assert_eq!(
+            row_major([(2, 1), (0, 2), (1, 0), (0, 1)]),
+            vec![(1, 0), (0, 1), (2, 1), (0, 2)]
+        );
+    }
+
+    #[test]
+    fn test_neighbors() {
+        assert_eq!(
+            row_major(neighbors(3, 3, (0, 0))),
+            vec![(1, 0), (0, 1), (1, 1)]
+        );
+        assert_eq!(neighbors(3, 3, (1, 0)).count(), 5);
+        assert_eq!(neighbors(3, 3, (1, 1)).count(), 8);
+        assert_eq!(neighbors(1, 1, (0, 0)).count(), 0);
+        assert_eq!(neighbors(1, 3, (1, 0)).count(), 2);
+    }
+
+    #[test]
+    fn test_wrapping_neighbors() {
+        assert_eq!(
+            row_major(wrapping_neighbors(4, 5, (0, 0))),
+            vec![
+                (1, 0),
+                (4, 0),
+                (0, 1),
+                (1, 1),
+                (4, 1),
+                (0, 3),
+                (1, 3),
+                (4, 3)
+            ]
+        );
+        // every cell of a 3 x 3 torus touches all others, narrower boards repeat cells
+        assert_eq!(wrapping_neighbors(3, 3, (1, 1)).count(), 8);
+        assert_eq!(
+            row_major(wrapping_neighbors(1, 3, (1, 0))),
+            vec![(0, 0), (2, 0)]
+        );
+        assert_eq!(
+            row_major(wrapping_neighbors(2, 2, (0, 0))),
+            vec![(1, 0), (0, 1), (1, 1)]
+        );
+        assert_eq!(wrapping_neighbors(1, 1, (0, 0)).count(), 0);
+        let grid = Grid {
+            wrap_edges: true,
+            ..Grid::new(3, 5)
+        };
+        assert_eq!(grid.flood_fill((0, 0), |(x, _)| x != 2).len(), 12);
+        for pos in grid.positions() {
+            for n in grid.neighbors(pos) {
+                assert!(grid.neighbors(n).any(|m| m == pos));
+            }
+        }
+    }
+
+    #[test]
+    fn test_hex_neighbors() {
+        let hex = HexGrid;
+        assert_eq!(
+            row_major(hex.neighbors(3, 3, (1, 1), false)),
+            vec![(1, 0), (2, 0), (0, 1), (2, 1), (1, 2), (2, 2)]
+        );
+        assert_eq!(
+            row_major(hex.neighbors(3, 3, (1, 2), false)),
+            vec![(0, 1), (1, 1), (0, 2), (2, 2)]
+        );
+        // touching is symmetric
+        for pos in Grid::new(4, 4).positions() {
+            for n in hex.neighbors(4, 4, pos, true) {
+                assert!(hex.neighbors(4, 4, n, true).any(|m| m == pos));
+            }
+        }
+        assert_eq!(SquareGrid.neighbors(3, 3, (1, 1), false).count(), 8);
+        assert_eq!(HexGrid.index(3, 4, (3, 2)), Some(11));
+        assert_eq!(HexGrid.index(3, 4, (4, 0)), None);
+    }
+
+    #[test]
+    fn test_knight_neighbors() {
+        let knight = |rows, cols, pos, wrap| {
+            row_major(SquareGrid.adjacent(rows, cols, pos, wrap, Adjacency::Knight))
+        };
+        assert_eq!(knight(3, 3, (0, 0), false), vec![(2, 1), (1, 2)]);
+        assert_eq!(knight(3, 3, (1, 1), false), vec![]);
+        assert_eq!(knight(5, 5, (2, 2), false).len(), 8);
+        // a knight's jump on a 3 x 3 torus always lands on a corner
+        assert_eq!(
+            knight(3, 3, (1, 1), true),
+            vec![(0, 0), (2, 0), (0, 2), (2, 2)]
+        );
+        // other topologies keep their own neighbors
+        assert_eq!(
+            HexGrid
+                .adjacent(3, 3, (1, 1), false, Adjacency::Knight)
+                .count(),
+            6
+        );
+        let grid = Grid {
+            adjacency: Adjacency::Knight,
+            ..Grid::new(4, 4)
+        };
+        assert_eq!(row_major(grid.neighbors((0, 0))), vec![(2, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn test_wormhole_neighbors() {
+        let wormholes = [((0, 0), (4, 4))];
+        let touching = |pos| {
+            let plain = |p| neighbors(5, 5, p);
+            row_major(plain(pos).chain(wormhole_neighbors(&wormholes, pos, plain)))
+        };
+        assert_eq!(
+            touching((0, 0)),
+            vec![(1, 0), (0, 1), (1, 1), (3, 3), (4, 3), (3, 4)]
+        );
+        assert_eq!(
+            touching((3, 3)),
+            vec![
+                (0, 0),
+                (2, 2),
+                (3, 2),
+                (4, 2),
+                (2, 3),
+                (4, 3),
+                (2, 4),
+                (3, 4),
+                (4, 4)
+            ]
+        );
+        assert!(wormhole_neighbors(&[], (0, 0), |p| neighbors(5, 5, p)).is_empty());
+        for pos in Grid::new(5, 5).positions() {
+            for n in touching(pos) {
+                assert!(touching(n).contains(&pos));
+            }
+        }
+    }
+
+    #[test]
+    fn test_cube_neighbors() {
+        let cube = CubeGrid { layers: 3 };
+        // a 3 x 3 x 3 cube is 9 rows of 3 columns, its center is in the middle layer
+        assert_eq!(cube.neighbors(9, 3, (1, 4), false).count(), 26);
+        assert_eq!(
+            row_major(cube.neighbors(9, 3, (0, 0), false)),
+            vec![(1, 0), (0, 1), (1, 1), (0, 3), (1, 3), (0, 4), (1, 4)]
+        );
+        // layers only touch the layer above and below
+        assert!(cube.neighbors(9, 3, (1, 1), false).all(|(_, y)| y < 6));
+        assert_eq!(cube.neighbors(9, 3, (0, 0), true).count(), 26);
+        let flat = CubeGrid::default();
+        for pos in Grid::new(4, 4).positions() {
+            assert_eq!(
+                row_major(flat.neighbors(4, 4, pos, false)),
+                row_major(neighbors(4, 4, pos))
+            );
+        }
+    }
+
+    #[test]
+    fn test_grid_indexing() {
+        let grid = Grid::new(3, 4);
+        assert_eq!(grid.len(), 12);
+        assert_eq!(grid.index((3, 2)), Some(11));
+        assert_eq!(grid.index((4, 0)), None);
+        assert_eq!(grid.index((0, 3)), None);
+        assert_eq!(grid.position(5), Some((1, 1)));
+        assert_eq!(grid.position(12), None);
+        for (i, pos) in grid.positions().enumerate() {
+            assert_eq!(grid.index(pos), Some(i));
+            assert_eq!(grid.position(i), Some(pos));
+        }
+        assert!(Grid::new(0, 5).is_empty());
+        assert_eq!(Grid::new(0, 5).positions().count(), 0);
+    }
+
+    #[test]
+    fn test_mask() {
+        let grid = Grid::new(9, 9);
+        let mut mask = Mask::new(grid);
+        assert!(mask.is_empty());
+        assert!(mask.insert((8, 8)));
+        assert!(mask.insert((0, 1)));
+        assert!(!mask.insert((0, 1)));
+        assert!(!mask.insert((9, 0)));
+        assert_eq!(mask.len(), 2);
+        assert!(mask.contains((8, 8)));
+        assert!(!mask.contains((1, 0)));
+        assert_eq!(mask.iter().collect::<Vec<_>>(), vec![(0, 1), (8, 8)]);
+
+        assert!(mask.remove((8, 8)));
+        assert!(!mask.remove((8, 8)));
+        assert_eq!(mask.len(), 1);
+        mask.clear();
+        assert!(mask.is_empty() && !mask.contains((0, 1)));
+
+        let mask = Mask::from_positions(grid, [(1, 1), (1, 1), (20, 20)]);
+        assert_eq!(mask.len(), 1);
+        let larger = Mask::from_positions(grid, [(1, 1), (8, 8)]);
+        assert!(mask.is_subset(&larger));
+        assert!(!larger.is_subset(&mask));
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let grid = Grid::new(3, 5);
+        // a wall down column 2 splits the grid in two
+        let wall = |(x, _): Position| x != 2;
+        let left = grid.flood_fill((0, 0), wall);
+        assert_eq!(left.len(), 6);
+        assert!(left.iter().all(|&(x, _)| x < 2));
+        assert_eq!(grid.flood_fill((4, 2), wall).len(), 6);
+        assert!(grid.flood_fill((2, 0), wall).is_empty());
+        assert!(grid.flood_fill((7, 0), |_| true).is_empty());
+        assert_eq!(grid.flood_fill((1, 1), |_| true).len(), 15);
+        // diagonal steps connect cells
+        let diagonal = grid.flood_fill((0, 0), |(x, y)| x == y);
+        assert_eq!(diagonal, vec![(0, 0), (1, 1), (2, 2)]);
+    }
+
+    #[test]
+    fn test_neighbor_cache() {
+        let grid = Grid::new(4, 7);
+        let cache = NeighborCache::new(grid);
+        for pos in grid.positions() {
+            assert_eq!(cache.get(pos), grid.neighbors(pos).collect::<Vec<_>>());
+        }
+        assert!(cache.get((7, 0)).is_empty());
+    }
+}
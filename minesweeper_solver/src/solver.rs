@@ -0,0 +1,732 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::grid::{row_major, Position};
+use crate::view::{BoardView, Square};
+
+/// Deduces provably safe cells and provably placed mines from the visible state.
+///
+/// Three rules are applied until nothing changes: the single cell rule (a number
+/// is already satisfied, or needs all its unknown neighbors), the subset rule (if
+/// one number's unknown neighbors are a subset of another's, the difference holds
+/// the difference in mines) and the global mine count.
+///
+/// Flags are player annotations and may be wrong, so they are treated like any
+/// other unopened cell.
+///
+/// With anti-mines the numbers are signed, and in liar games they are off by one,
+/// so the rules above no longer hold. The cells are then settled by
+/// `mine_probabilities` instead.
+#[derive(Debug, Clone)]
+pub struct Solver {
+    safe: HashSet<Position>,
+    mines: HashSet<Position>,
+}
+
+impl Solver {
+    pub fn new(view: &BoardView) -> Solver {
+        let mut unlimited = usize::MAX;
+        Solver::bounded(view, &mut unlimited).expect("the search steps are unlimited")
+    }
+
+    /// Like `new`, but takes the steps of enumerating the placements from `budget`
+    /// and gives up with `None` once it runs out. Only the variants whose numbers
+    /// need the enumeration take steps, the rules above are free.
+    pub fn bounded(view: &BoardView, budget: &mut usize) -> Option<Solver> {
+        let mut solver = Solver {
+            safe: HashSet::new(),
+            mines: HashSet::new(),
+        };
+        if enumerated(view) {
+            for (pos, p) in probabilities(view, &solver, budget)? {
+                if p < CERTAIN {
+                    solver.safe.insert(pos);
+                } else if p > 1.0 - CERTAIN {
+                    solver.mines.insert(pos);
+                }
+            }
+            return Some(solver);
+        }
+        let mut hidden = Vec::new();
+        let mut numbers = Vec::new();
+        for (y, row) in view.squares.iter().enumerate() {
+            for (x, square) in row.iter().enumerate() {
+                match square {
+                    Square::Opened(0) => {}
+                    Square::Opened(count) => numbers.push(((x, y), *count as usize)),
+                    Square::Mine => {
+                        solver.mines.insert((x, y));
+                    }
+                    Square::NotYetOpened | Square::Flag => hidden.push((x, y)),
+                }
+            }
+        }
+        let opened = |view: &BoardView, pos| matches!(view.get(pos), Some(Square::Opened(_)));
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            // unknown neighbors of every number and the mines still missing among them
+            let mut constraints: Vec<(Vec<Position>, usize)> = Vec::new();
+            for &(pos, count) in numbers.iter() {
+                let mut unknown = Vec::new();
+                let mut known_mines = 0;
+                for n in view.neighbors(pos) {
+                    if solver.mines.contains(&n) {
+                        known_mines += 1;
+                    } else if !opened(view, n) && !solver.safe.contains(&n) {
+                        unknown.push(n);
+                    }
+                }
+                if !unknown.is_empty() {
+                    constraints.push((unknown, count.saturating_sub(known_mines)));
+                }
+            }
+            for (unknown, mines) in constraints.iter() {
+                changed |= solver.resolve(unknown, *mines);
+            }
+            if !changed {
+                changed |= solver.apply_subset_rule(&constraints);
+            }
+            // global rule: the total mine count can settle the remaining unknown cells
+            let unknown: Vec<Position> = hidden
+                .iter()
+                .copied()
+                .filter(|p| !solver.safe.contains(p) && !solver.mines.contains(p))
+                .collect();
+            let remaining = view.nr_mines.saturating_sub(solver.mines.len());
+            changed |= solver.resolve(&unknown, remaining);
+        }
+        Some(solver)
+    }
+
+    fn apply_subset_rule(&mut self, constraints: &[(Vec<Position>, usize)]) -> bool {
+        let mut by_cell: HashMap<Position, Vec<usize>> = HashMap::new();
+        for (i, (cells, _)) in constraints.iter().enumerate() {
+            for &cell in cells {
+                by_cell.entry(cell).or_default().push(i);
+            }
+        }
+        let mut changed = false;
+        for (i, (small, small_mines)) in constraints.iter().enumerate() {
+            // any superset must contain the first cell of the subset as well
+            for &j in by_cell[&small[0]].iter() {
+                let (large, large_mines) = &constraints[j];
+                if i == j || large.len() <= small.len() || large_mines < small_mines {
+                    continue;
+                }
+                if small.iter().all(|c| large.contains(c)) {
+                    let rest: Vec<Position> = large
+                        .iter()
+                        .copied()
+                        .filter(|c| !small.contains(c))
+                        .collect();
+                    // mines found by an earlier subset in this pass are already placed
+                    let placed = rest.iter().filter(|c| self.mines.contains(c)).count();
+                    let Some(missing) = (large_mines - small_mines).checked_sub(placed) else {
+                        continue;
+                    };
+                    let rest: Vec<Position> = rest
+                        .into_iter()
+                        .filter(|c| !self.safe.contains(c) && !self.mines.contains(c))
+                        .collect();
+                    changed |= self.resolve(&rest, missing);
+                }
+            }
+        }
+        changed
+    }
+
+    /// Marks `cells` when they hold either no mines or only mines.
+    fn resolve(&mut self, cells: &[Position], mines: usize) -> bool {
+        if cells.is_empty() {
+            false
+        } else if mines == 0 {
+            self.safe.extend(cells);
+            true
+        } else if mines == cells.len() {
+            self.mines.extend(cells);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Unopened cells that are provably free of mines, flagged ones included.
+    pub fn safe_cells(&self) -> Vec<Position> {
+        row_major(self.safe.iter().copied())
+    }
+
+    /// Cells that provably hold a mine.
+    pub fn mine_cells(&self) -> Vec<Position> {
+        row_major(self.mines.iter().copied())
+    }
+}
+
+/// Exact mine probability of every unopened cell, flags included. Anti-mines
+/// count as mines here, opening either loses.
+///
+/// Cells the `Solver` settles get 0 or 1. The rest of the frontier is split into
+/// independent groups whose consistent mine placements are enumerated, and the
+/// groups are combined with the global mine count, the cells away from the
+/// frontier sharing the leftover mines evenly.
+pub fn mine_probabilities(view: &BoardView) -> HashMap<Position, f64> {
+    let mut unlimited = usize::MAX;
+    bounded_mine_probabilities(view, &mut unlimited).expect("the search steps are unlimited")
+}
+
+/// Like `mine_probabilities`, but takes the search steps from `budget` like
+/// `Solver::bounded`.
+pub fn bounded_mine_probabilities(
+    view: &BoardView,
+    budget: &mut usize,
+) -> Option<HashMap<Position, f64>> {
+    let settled = if enumerated(view) {
+        // the solver reads its deductions off the probabilities
+        Solver {
+            safe: HashSet::new(),
+            mines: HashSet::new(),
+        }
+    } else {
+        Solver::new(view)
+    };
+    probabilities(view, &settled, budget)
+}
+
+// probabilities closer than this to 0 or 1 are taken as certain
+const CERTAIN: f64 = 1e-9;
+
+/// Whether only enumerating the placements reads the numbers right.
+fn enumerated(view: &BoardView) -> bool {
+    view.nr_anti_mines > 0 || view.liar
+}
+
+fn probabilities(
+    view: &BoardView,
+    solver: &Solver,
+    budget: &mut usize,
+) -> Option<HashMap<Position, f64>> {
+    let signed = view.nr_anti_mines > 0;
+    let mut probabilities = HashMap::new();
+    let mut unknown = Vec::new();
+    let mut numbers = Vec::new();
+    // mines shown after a loss, with signed numbers they are not known to be anti-mines or not
+    let mut revealed = HashSet::new();
+    let mut known_mines = 0;
+    for (y, row) in view.squares.iter().enumerate() {
+        for (x, square) in row.iter().enumerate() {
+            let pos = (x, y);
+            match square {
+                Square::Opened(0) if !signed && !view.liar => {}
+                Square::Opened(count) => numbers.push((pos, *count as isize)),
+                Square::Mine if signed => {
+                    revealed.insert(pos);
+                    unknown.push(pos);
+                }
+                Square::Mine => known_mines += 1,
+                Square::NotYetOpened | Square::Flag => {
+                    if solver.safe.contains(&pos) {
+                        probabilities.insert(pos, 0.0);
+                    } else if solver.mines.contains(&pos) {
+                        known_mines += 1;
+                        probabilities.insert(pos, 1.0);
+                    } else {
+                        unknown.push(pos);
+                    }
+                }
+            }
+        }
+    }
+    if unknown.is_empty() {
+        return Some(probabilities);
+    }
+    let is_unknown: HashSet<Position> = unknown.iter().copied().collect();
+
+    // unknown neighbors of every number and what is still missing among them
+    let mut constraints: Vec<(Vec<Position>, isize)> = Vec::new();
+    for &(pos, count) in numbers.iter() {
+        let cells: Vec<Position> = view
+            .neighbors(pos)
+            .filter(|n| is_unknown.contains(n))
+            .collect();
+        if cells.is_empty() {
+            continue;
+        }
+        let placed = view
+            .neighbors(pos)
+            .filter(|&n| !is_unknown.contains(&n))
+            .filter(|&n| solver.mines.contains(&n) || view.get(n) == Some(Square::Mine))
+            .count();
+        constraints.push((cells, count - placed as isize));
+    }
+
+    let groups: Vec<Group> = frontier_groups(&constraints)
+        .into_iter()
+        .map(|(cells, group_constraints)| {
+            Group::enumerate(cells, &group_constraints, signed, view.liar, budget)
+        })
+        .collect::<Option<_>>()?;
+    let frontier: HashSet<Position> = groups.iter().flat_map(|g| g.cells.clone()).collect();
+    let interior = unknown.len() - frontier.len();
+    let remaining = view.nr_mines.saturating_sub(known_mines);
+    let anti = view.nr_anti_mines.min(remaining);
+
+    // relative number of ways to place the leftover mines, t of them with b
+    // anti-mines already on the frontier, away from the frontier
+    let ln_weights: Vec<Vec<Option<f64>>> = (0..=remaining)
+        .map(|t| {
+            let rest = remaining - t;
+            (0..=anti)
+                .map(|b| {
+                    (rest <= interior && anti - b <= rest)
+                        .then(|| ln_binomial(interior, rest) + ln_binomial(rest, anti - b))
+                })
+                .collect()
+        })
+        .collect();
+    let max_ln = ln_weights
+        .iter()
+        .flatten()
+        .flatten()
+        .copied()
+        .fold(f64::MIN, f64::max);
+    let weight = |t: usize, b: usize| match ln_weights.get(t).and_then(|row| row.get(b)) {
+        Some(Some(ln)) => (ln - max_ln).exp(),
+        _ => 0.0,
+    };
+    let weighted = |table: &[Vec<f64>], k: usize, a: usize| -> f64 {
+        let mut sum = 0.0;
+        for (t, row) in table.iter().enumerate() {
+            for (b, w) in row.iter().enumerate() {
+                sum += w * weight(k + t, a + b);
+            }
+        }
+        sum
+    };
+
+    let all = groups
+        .iter()
+        .fold(vec![vec![1.0]], |acc, g| convolve(&acc, &g.ways));
+    let total = weighted(&all, 0, 0);
+    if total == 0.0 {
+        // the visible state is inconsistent, e.g. after editing the counts by hand
+        return Some(probabilities);
+    }
+
+    for (i, group) in groups.iter().enumerate() {
+        let others = groups
+            .iter()
+            .enumerate()
+            .filter(|&(j, _)| j != i)
+            .fold(vec![vec![1.0]], |acc, (_, g)| convolve(&acc, &g.ways));
+        for (c, &cell) in group.cells.iter().enumerate() {
+            let mut p = 0.0;
+            for (k, row) in group.cell_mines.iter().enumerate() {
+                for (a, counts) in row.iter().enumerate() {
+                    if counts[c] != 0.0 {
+                        p += counts[c] * weighted(&others, k, a);
+                    }
+                }
+            }
+            probabilities.insert(cell, p / total);
+        }
+    }
+    if interior > 0 {
+        let mut expected = 0.0;
+        for (t, row) in all.iter().enumerate().take(remaining + 1) {
+            for (b, w) in row.iter().enumerate() {
+                expected += w * weight(t, b) * (remaining - t) as f64;
+            }
+        }
+        let p = expected / total / interior as f64;
+        for pos in unknown.into_iter().filter(|pos| !frontier.contains(pos)) {
+            probabilities.insert(pos, p);
+        }
+    }
+    probabilities.retain(|pos, _| !revealed.contains(pos));
+    Some(probabilities)
+}
+
+/// Mine placements of one independent part of the frontier, bucketed by the
+/// number of mines and of anti-mines among them.
+struct Group {
+    cells: Vec<Position>,
+    // ways[k][a]: placements with k mines, a of them anti-mines
+    ways: Vec<Vec<f64>>,
+    // cell_mines[k][a][c]: those placements that put a mine of either kind on cells[c]
+    cell_mines: Vec<Vec<Vec<f64>>>,
+}
+
+impl Group {
+    fn enumerate(
+        cells: Vec<Position>,
+        constraints: &[(Vec<usize>, isize)],
+        signed: bool,
+        liar: bool,
+        budget: &mut usize,
+    ) -> Option<Group> {
+        let anti = if signed { cells.len() + 1 } else { 1 };
+        let mut group = Group {
+            ways: vec![vec![0.0; anti]; cells.len() + 1],
+            cell_mines: vec![vec![vec![0.0; cells.len()]; anti]; cells.len() + 1],
+            cells,
+        };
+        let mut by_cell = vec![Vec::new(); group.cells.len()];
+        for (i, (cells, _)) in constraints.iter().enumerate() {
+            for &c in cells {
+                by_cell[c].push(i);
+            }
+        }
+        // a cell adds 1 with a mine and, with signed numbers, -1 with an anti-mine
+        let values: &[isize] = if signed { &[0, 1, -1] } else { &[0, 1] };
+        let mut search = Search {
+            constraints,
+            by_cell: &by_cell,
+            values,
+            lie: liar as isize,
+            placed: vec![0; constraints.len()],
+            open: constraints.iter().map(|(cells, _)| cells.len()).collect(),
+            assignment: vec![0; group.cells.len()],
+            budget: *budget,
+        };
+        search.run(&mut group, 0);
+        *budget = search.budget;
+        (search.budget > 0).then_some(group)
+    }
+}
+
+/// State of the depth first enumeration of a group's placements.
+struct Search<'a> {
+    constraints: &'a [(Vec<usize>, isize)],
+    by_cell: &'a [Vec<usize>],
+    values: &'a [isize],
+    // how far off every number is, a sum must then be exactly that far from it
+    lie: isize,
+    // sum of the values placed around every constraint so far
+    placed: Vec<isize>,
+    // cells of every constraint still without a value
+    open: Vec<usize>,
+    assignment: Vec<isize>,
+    // search steps left, the search stops when they run out
+    budget: usize,
+}
+
+impl Search<'_> {
+    fn run(&mut self, group: &mut Group, cell: usize) {
+        if self.budget == 0 {
+            return;
+        }
+        self.budget -= 1;
+        if cell == group.cells.len() {
+            let k = self.assignment.iter().filter(|&&v| v != 0).count();
+            let a = self.assignment.iter().filter(|&&v| v < 0).count();
+            group.ways[k][a] += 1.0;
+            for (c, &value) in self.assignment.iter().enumerate() {
+                if value != 0 {
+                    group.cell_mines[k][a][c] += 1.0;
+                }
+            }
+            return;
+        }
+        let lowest = *self.values.iter().min().unwrap();
+        for &value in self.values {
+            let mut consistent = true;
+            for &i in self.by_cell[cell].iter() {
+                self.open[i] -= 1;
+                self.placed[i] += value;
+                let need = self.constraints[i].1;
+                let open = self.open[i] as isize;
+                let placed = self.placed[i];
+                if placed + lowest * open > need + self.lie
+                    || placed + open < need - self.lie
+                    || open == 0 && (placed - need).abs() != self.lie
+                {
+                    consistent = false;
+                }
+            }
+            if consistent {
+                self.assignment[cell] = value;
+                self.run(group, cell + 1);
+                self.assignment[cell] = 0;
+            }
+            for &i in self.by_cell[cell].iter() {
+                self.open[i] += 1;
+                self.placed[i] -= value;
+            }
+        }
+    }
+}
+
+/// Splits the constraints into groups that share no cells, with the cells of each
+/// group in discovery order and the constraints re-indexed against them.
+#[allow(clippy::type_complexity)]
+fn frontier_groups(
+    constraints: &[(Vec<Position>, isize)],
+) -> Vec<(Vec<Position>, Vec<(Vec<usize>, isize)>)> {
+    let mut by_cell: HashMap<Position, Vec<usize>> = HashMap::new();
+    for (i, (cells, _)) in constraints.iter().enumerate() {
+        for &cell in cells {
+            by_cell.entry(cell).or_default().push(i);
+        }
+    }
+    let mut done = vec![false; constraints.len()];
+    let mut groups = Vec::new();
+    for start in 0..constraints.len() {
+        if done[start] {
+            continue;
+        }
+        done[start] = true;
+        let mut queue = vec![start];
+        let mut members = Vec::new();
+        let mut cells: Vec<Position> = Vec::new();
+        let mut index: HashMap<Position, usize> = HashMap::new();
+        while let Some(i) = queue.pop() {
+            members.push(i);
+            for &cell in constraints[i].0.iter() {
+                if index.contains_key(&cell) {
+                    continue;
+                }
+                index.insert(cell, cells.len());
+                cells.push(cell);
+                for &j in by_cell[&cell].iter() {
+                    if !done[j] {
+                        done[j] = true;
+                        queue.push(j);
+                    }
+                }
+            }
+        }
+        let local = members
+            .into_iter()
+            .map(|i| {
+                let (cells, need) = &constraints[i];
+                (cells.iter().map(|c| index[c]).collect(), *need)
+            })
+            .collect();
+        groups.push((cells, local));
+    }
+    groups
+}
+
+fn convolve(a: &[Vec<f64>], b: &[Vec<f64>]) -> Vec<Vec<f64>> {
+    let mut out = vec![vec![0.0; a[0].len() + b[0].len() - 1]; a.len() + b.len() - 1];
+    for (i, row_a) in a.iter().enumerate() {
+        for (j, x) in row_a.iter().enumerate() {
+            for (k, row_b) in b.iter().enumerate() {
+                for (l, y) in row_b.iter().enumerate() {
+                    out[i + k][j + l] += x * y;
+                }
+            }
+        }
+    }
+    out
+}
+
+fn ln_binomial(n: usize, k: usize) -> f64 {
+    (1..=k).map(|i| ((n - k + i) as f64 / i as f64).ln()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::Adjacency;
+    use crate::view::GameState;
+
+    #[test]
+    fn test_subset_rule() {
+        // the 1-2-1 pattern against the bottom edge needs the subset rule
+        let view = BoardView {
+            rows: 2,
+            cols: 3,
+            nr_mines: 2,
+            state: GameState::OnGoing,
+            squares: vec![
+                vec![Square::NotYetOpened; 3],
+                vec![Square::Opened(1), Square::Opened(2), Square::Opened(1)],
+            ],
+            wrap_edges: false,
+            nr_anti_mines: 0,
+            adjacency: Adjacency::King,
+            wormholes: Vec::new(),
+            liar: false,
+        };
+        let solver = Solver::new(&view);
+        assert_eq!(solver.safe_cells(), vec![(1, 0)]);
+        assert_eq!(solver.mine_cells(), vec![(0, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn test_subset_rule_after_placing_mines() {
+        // (1, 2) is found from the left 1 and the right 2 first, then must still
+        // count towards the left 2, leaving (0, 2) safe
+        let row = |squares: &str| {
+            squares
+                .chars()
+                .map(|c| match c.to_digit(10) {
+                    Some(count) => Square::Opened(count as i8),
+                    None => Square::NotYetOpened,
+                })
+                .collect()
+        };
+        let view = BoardView {
+            rows: 3,
+            cols: 4,
+            nr_mines: 2,
+            state: GameState::OnGoing,
+            squares: vec![row("1.10"), row("2.20"), row("..10")],
+            wrap_edges: false,
+            nr_anti_mines: 0,
+            adjacency: Adjacency::King,
+            wormholes: Vec::new(),
+            liar: false,
+        };
+        let solver = Solver::new(&view);
+        assert_eq!(solver.safe_cells(), vec![(1, 1), (0, 2)]);
+        assert_eq!(solver.mine_cells(), vec![(1, 0), (1, 2)]);
+    }
+
+    #[test]
+    fn test_probabilities_fifty_fifty() {
+        let view = BoardView {
+            rows: 2,
+            cols: 2,
+            nr_mines: 1,
+            state: GameState::OnGoing,
+            squares: vec![
+                vec![Square::NotYetOpened; 2],
+                vec![Square::Opened(1), Square::Opened(1)],
+            ],
+            wrap_edges: false,
+            nr_anti_mines: 0,
+            adjacency: Adjacency::King,
+            wormholes: Vec::new(),
+            liar: false,
+        };
+        let probabilities = mine_probabilities(&view);
+        assert_eq!(probabilities.len(), 2);
+        assert!((probabilities[&(0, 0)] - 0.5).abs() < 1e-9);
+        assert!((probabilities[&(1, 0)] - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_probabilities_use_mine_count() {
+        // both 1s see the closed cell between them, or each sees a mine of its own
+        let view = |nr_mines| BoardView {
+            rows: 2,
+            cols: 3,
+            nr_mines,
+            state: GameState::OnGoing,
+            squares: vec![
+                vec![Square::NotYetOpened; 3],
+                vec![Square::Opened(1), Square::NotYetOpened, Square::Opened(1)],
+            ],
+            wrap_edges: false,
+            nr_anti_mines: 0,
+            adjacency: Adjacency::King,
+            wormholes: Vec::new(),
+            liar: false,
+        };
+        let one = mine_probabilities(&view(1));
+        assert_eq!(one[&(0, 0)], 0.0);
+        assert!((one[&(1, 0)] - 0.5).abs() < 1e-9);
+        assert!((one[&(1, 1)] - 0.5).abs() < 1e-9);
+        let two = mine_probabilities(&view(2));
+        assert_eq!(two[&(0, 0)], 1.0);
+        assert_eq!(two[&(2, 0)], 1.0);
+        assert_eq!(two[&(1, 0)], 0.0);
+    }
+
+    #[test]
+    fn test_signed_constraints() {
+        let view = |squares| BoardView {
+            rows: 1,
+            cols: 4,
+            nr_mines: 2,
+            state: GameState::OnGoing,
+            squares: vec![squares],
+            wrap_edges: false,
+            nr_anti_mines: 1,
+            adjacency: Adjacency::King,
+            wormholes: Vec::new(),
+            liar: false,
+        };
+        // the -1 needs the anti-mine, so the mine is the last cell
+        let closed = Square::NotYetOpened;
+        let negative = view(vec![closed, Square::Opened(-1), closed, closed]);
+        let probabilities = mine_probabilities(&negative);
+        assert!((probabilities[&(0, 0)] - 0.5).abs() < 1e-9);
+        assert!((probabilities[&(2, 0)] - 0.5).abs() < 1e-9);
+        assert!((probabilities[&(3, 0)] - 1.0).abs() < 1e-9);
+        let solver = Solver::new(&negative);
+        assert_eq!(solver.mine_cells(), vec![(3, 0)]);
+        assert!(solver.safe_cells().is_empty());
+
+        // a 0 next to closed cells is a mine and an anti-mine cancelling out
+        let zero = view(vec![closed, Square::Opened(0), closed, Square::Opened(1)]);
+        let solver = Solver::new(&zero);
+        assert_eq!(solver.mine_cells(), vec![(0, 0), (2, 0)]);
+    }
+
+    #[test]
+    fn test_knight_adjacency() {
+        // the 1 only reaches (2, 1) and (1, 2) with a knight's move
+        let closed = Square::NotYetOpened;
+        let mut view = BoardView {
+            rows: 3,
+            cols: 3,
+            nr_mines: 1,
+            state: GameState::OnGoing,
+            squares: vec![
+                vec![Square::Opened(1), closed, closed],
+                vec![closed, closed, Square::Opened(0)],
+                vec![closed, closed, closed],
+            ],
+            wrap_edges: false,
+            nr_anti_mines: 0,
+            adjacency: Adjacency::Knight,
+            wormholes: Vec::new(),
+            liar: false,
+        };
+        let solver = Solver::new(&view);
+        assert_eq!(solver.mine_cells(), vec![(1, 2)]);
+        assert_eq!(solver.safe_cells().len(), 6);
+        view.adjacency = Adjacency::King;
+        assert!(Solver::new(&view).mine_cells().is_empty());
+    }
+
+    #[test]
+    fn test_liar_constraints() {
+        let closed = Square::NotYetOpened;
+        let mut view = BoardView {
+            rows: 1,
+            cols: 4,
+            nr_mines: 1,
+            state: GameState::OnGoing,
+            squares: vec![vec![Square::Opened(1), closed, closed, Square::Opened(2)]],
+            wrap_edges: false,
+            nr_anti_mines: 0,
+            adjacency: Adjacency::King,
+            wormholes: Vec::new(),
+            liar: true,
+        };
+        // a lying 1 with a single closed neighbor can only mean 0, a lying 2 only 1
+        let solver = Solver::new(&view);
+        assert_eq!(solver.safe_cells(), vec![(1, 0)]);
+        assert_eq!(solver.mine_cells(), vec![(2, 0)]);
+        let probabilities = mine_probabilities(&view);
+        assert_eq!(probabilities[&(1, 0)], 0.0);
+        assert_eq!(probabilities[&(2, 0)], 1.0);
+
+        // a shown 0 is a 1
+        view.squares = vec![vec![closed, Square::Opened(0), closed, closed]];
+        let probabilities = mine_probabilities(&view);
+        assert!((probabilities[&(0, 0)] - 0.5).abs() < 1e-9);
+        assert_eq!(probabilities[&(3, 0)], 0.0);
+
+        // the enumeration takes search steps, the plain rules don't
+        let mut budget = 2;
+        assert!(Solver::bounded(&view, &mut budget).is_none());
+        assert!(bounded_mine_probabilities(&view, &mut 100).is_some());
+        view.liar = false;
+        assert!(Solver::bounded(&view, &mut 0).is_some());
+    }
+}
@@ -0,0 +1,154 @@
+use crate::grid::{wormhole_neighbors, Adjacency, Position, SquareGrid, Topology};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameState {
+    Init,
+    OnGoing,
+    Lost,
+    Won,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Square {
+    Mine,
+    /// Mines minus anti-mines among the neighbors.
+    Opened(i8),
+    Flag,
+    NotYetOpened,
+}
+
+/// Player-visible snapshot of a board.
+///
+/// Unlike `Board`, a view never contains the hidden mine layout while the game
+/// is ongoing, so it is the only board data that should be handed to clients.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoardView {
+    pub rows: usize,
+    pub cols: usize,
+    pub nr_mines: usize,
+    pub state: GameState,
+    pub squares: Vec<Vec<Square>>,
+    /// Whether cells on opposite edges touch.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub wrap_edges: bool,
+    /// How many of the mines are anti-mines, which count as -1 in the numbers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub nr_anti_mines: usize,
+    /// Which cells count towards each other's numbers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub adjacency: Adjacency,
+    /// Pairs of cells that touch each other's neighbors.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub wormholes: Vec<(Position, Position)>,
+    /// Every number is one more or one less than the count of its neighbors.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub liar: bool,
+}
+
+/// What an opened number says about its neighborhood.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Constraint {
+    pub value: i8,
+    pub flags: usize,
+    /// Neighbors that are neither opened nor flagged.
+    pub unopened: usize,
+}
+
+impl Constraint {
+    pub fn satisfied(&self) -> bool {
+        self.flags as isize == self.value as isize
+    }
+
+    /// Mines still to be found around the cell, negative when over-flagged.
+    pub fn remaining(&self) -> isize {
+        self.value as isize - self.flags as isize
+    }
+}
+
+impl BoardView {
+    pub fn get(&self, (x, y): Position) -> Option<Square> {
+        self.squares.get(y).and_then(|row| row.get(x)).copied()
+    }
+
+    pub fn neighbors(&self, pos: Position) -> impl Iterator<Item = Position> {
+        let (rows, cols) = (self.rows, self.cols);
+        let (wrap_edges, adjacency) = (self.wrap_edges, self.adjacency);
+        let plain = move |p| SquareGrid.adjacent(rows, cols, p, wrap_edges, adjacency);
+        plain(pos).chain(wormhole_neighbors(&self.wormholes, pos, plain))
+    }
+
+    /// The constraint of an opened cell, `None` for any other square.
+    pub fn constraint(&self, pos: Position) -> Option<Constraint> {
+        let Some(Square::Opened(value)) = self.get(pos) else {
+            return None;
+        };
+        let mut constraint = Constraint {
+            value,
+            flags: 0,
+            unopened: 0,
+        };
+        for n in self.neighbors(pos) {
+            match self.get(n) {
+                Some(Square::Flag) => constraint.flags += 1,
+                Some(Square::NotYetOpened) => constraint.unopened += 1,
+                _ => {}
+            }
+        }
+        Some(constraint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constraint() {
+        let view = BoardView {
+            rows: 1,
+            cols: 3,
+            nr_mines: 1,
+            state: GameState::OnGoing,
+            squares: vec![vec![Square::Flag, Square::Opened(1), Square::NotYetOpened]],
+            wrap_edges: false,
+            nr_anti_mines: 0,
+            adjacency: Adjacency::King,
+            wormholes: Vec::new(),
+            liar: false,
+        };
+        let constraint = view.constraint((1, 0)).unwrap();
+        assert_eq!((constraint.flags, constraint.unopened), (1, 1));
+        assert!(constraint.satisfied());
+        assert_eq!(constraint.remaining(), 0);
+        assert!(view.constraint((0, 0)).is_none());
+    }
+
+    #[test]
+    fn test_constraint_wrapping() {
+        let mut view = BoardView {
+            rows: 1,
+            cols: 4,
+            nr_mines: 1,
+            state: GameState::OnGoing,
+            squares: vec![vec![
+                Square::Opened(1),
+                Square::NotYetOpened,
+                Square::NotYetOpened,
+                Square::Flag,
+            ]],
+            wrap_edges: false,
+            nr_anti_mines: 0,
+            adjacency: Adjacency::King,
+            wormholes: Vec::new(),
+            liar: false,
+        };
+        let constraint = view.constraint((0, 0)).unwrap();
+        assert_eq!((constraint.flags, constraint.unopened), (0, 1));
+        view.wrap_edges = true;
+        let constraint = view.constraint((0, 0)).unwrap();
+        assert_eq!((constraint.flags, constraint.unopened), (1, 1));
+    }
+}
@@ -0,0 +1,8 @@
+//! The bindings of the `wasm` feature of `minesweeper` as a package for
+//! web frontends:
+//!
+//! ```bash
+//! wasm-pack build minesweeper_wasm --target web
+//! ```
+
+pub use minesweeper::wasm::*;